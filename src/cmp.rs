@@ -0,0 +1,106 @@
+//! Combinators for building comparators.
+//!
+//! The comparators can be passed to the `_by` sorting and searching functions,
+//! like [`sort::merge_by`](crate::sort::merge_by) or
+//! [`search::binary_by`](crate::search::binary_by).
+
+use std::cmp::Ordering;
+
+/// Reverses the order of a comparator.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::{cmp, sort};
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::merge_in_place_by(&mut slice, cmp::reverse(i32::cmp));
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn reverse<T, F>(mut compare: F) -> impl FnMut(&T, &T) -> Ordering
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    move |a, b| compare(b, a)
+}
+
+/// Returns a comparator that compares the keys extracted by `key_fn`.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::{cmp, sort};
+///
+/// let mut slice = ["apple", "fig", "banana", "kiwi"];
+/// sort::tim_by(&mut slice, cmp::by_key(|s: &&str| s.len()));
+/// assert_eq!(slice, ["fig", "kiwi", "apple", "banana"]);
+/// ```
+pub fn by_key<T, K, F>(mut key_fn: F) -> impl FnMut(&T, &T) -> Ordering
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    move |a, b| key_fn(a).cmp(&key_fn(b))
+}
+
+/// Chains two comparators: the elements that are equal by `first` are
+/// compared by `second`.
+///
+/// `second` is called only when needed. The comparators can be chained
+/// further to compare by any number of keys.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::{cmp, sort};
+///
+/// let mut people = [("Kim", 31), ("Alex", 25), ("Sam", 25), ("Jo", 31)];
+/// let by_age_desc = cmp::reverse(cmp::by_key(|p: &(&str, u32)| p.1));
+/// let by_name = cmp::by_key(|p: &(&str, u32)| p.0);
+/// sort::quick_by(&mut people, cmp::then_by(by_age_desc, by_name));
+/// assert_eq!(people, [("Jo", 31), ("Kim", 31), ("Alex", 25), ("Sam", 25)]);
+/// ```
+pub fn then_by<T, F, G>(mut first: F, mut second: G) -> impl FnMut(&T, &T) -> Ordering
+where
+    F: FnMut(&T, &T) -> Ordering,
+    G: FnMut(&T, &T) -> Ordering,
+{
+    move |a, b| first(a, b).then_with(|| second(a, b))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::by_key;
+    use super::reverse;
+    use super::then_by;
+    use std::cmp::Ordering;
+
+    #[test]
+    fn reverse_test() {
+        let mut desc = reverse(i32::cmp);
+        assert_eq!(desc(&1, &2), Ordering::Greater);
+        assert_eq!(desc(&2, &2), Ordering::Equal);
+        assert_eq!(desc(&3, &2), Ordering::Less);
+    }
+
+    #[test]
+    fn by_key_test() {
+        let mut by_abs = by_key(|x: &i32| x.abs());
+        assert_eq!(by_abs(&-5, &3), Ordering::Greater);
+        assert_eq!(by_abs(&-3, &3), Ordering::Equal);
+    }
+
+    #[test]
+    fn then_by_test() {
+        let mut calls = 0;
+        let mut compare = then_by(by_key(|p: &(i32, i32)| p.0), |a, b| {
+            calls += 1;
+            a.1.cmp(&b.1)
+        });
+        assert_eq!(compare(&(1, 9), &(2, 0)), Ordering::Less);
+        assert_eq!(compare(&(2, 9), &(2, 0)), Ordering::Greater);
+        assert_eq!(compare(&(2, 0), &(2, 0)), Ordering::Equal);
+        drop(compare);
+        assert_eq!(calls, 2);
+    }
+}
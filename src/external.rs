@@ -0,0 +1,337 @@
+//! Sorting of data that doesn't fit in memory.
+//!
+//! The input is read in parts that fit in a memory budget. Every part is
+//! sorted and spilled to a temporary file as a sorted run, and then the runs
+//! are merged into the output, a limited number of them at a time.
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::mem;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::sort;
+
+/// The number of bytes a record takes in memory besides its contents.
+const RECORD_OVERHEAD: usize = mem::size_of::<Vec<u8>>();
+
+/// The smallest read buffer of a run, when the memory budget is tiny.
+const MIN_BUFFER: usize = 512;
+
+/// Distinguishes the temporary files of the sorts running in this process.
+static NEXT_SORT: AtomicUsize = AtomicUsize::new(0);
+
+/// Parameters of the external [`sort()`].
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::external::ExternalConfig;
+///
+/// let config = ExternalConfig::new()
+///     .memory(16 << 20)
+///     .fan_in(32)
+///     .temp_dir(std::env::temp_dir());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalConfig {
+    memory: usize,
+    fan_in: usize,
+    temp_dir: PathBuf,
+}
+
+impl ExternalConfig {
+    /// Creates the default configuration: a memory budget of 64 MiB, a fan-in
+    /// of 64 runs and the temporary directory of the system.
+    pub fn new() -> Self {
+        ExternalConfig {
+            memory: 64 << 20,
+            fan_in: 64,
+            temp_dir: env::temp_dir(),
+        }
+    }
+
+    /// Sets the number of bytes the records of a single run may take in
+    /// memory. A record longer than the budget makes a run of its own.
+    pub fn memory(mut self, bytes: usize) -> Self {
+        self.memory = bytes;
+        self
+    }
+
+    /// Sets the largest number of runs merged at once, which is the number of
+    /// temporary files open at the same time. When there are more runs, they
+    /// are merged in several passes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `runs` is less than 2.
+    pub fn fan_in(mut self, runs: usize) -> Self {
+        assert!(runs >= 2, "at least 2 runs have to be merged at once");
+        self.fan_in = runs;
+        self
+    }
+
+    /// Sets the directory, in which the sorted runs are stored.
+    pub fn temp_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.temp_dir = dir.into();
+        self
+    }
+}
+
+impl Default for ExternalConfig {
+    fn default() -> Self {
+        ExternalConfig::new()
+    }
+}
+
+/// The temporary files of the runs, which are removed on drop.
+struct Runs {
+    paths: Vec<PathBuf>,
+    sort_id: usize,
+    // the number of the runs created so far, which names the next one
+    created: usize,
+}
+
+impl Runs {
+    /// Creates the file of a new run in the temporary directory.
+    fn create(&mut self, config: &ExternalConfig) -> io::Result<File> {
+        let path = config.temp_dir.join(format!(
+            "search-sort-{}-{}-{}.run",
+            process::id(),
+            self.sort_id,
+            self.created
+        ));
+        let file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)?;
+        self.paths.push(path);
+        self.created += 1;
+        Ok(file)
+    }
+}
+
+impl Drop for Runs {
+    fn drop(&mut self) {
+        for path in &self.paths {
+            let _ = fs::remove_file(path);
+        }
+    }
+}
+
+/// Sorts the lines of `reader` bytewise, and writes them to `writer`.
+///
+/// Every line is a record, which ends with `\n`; a missing newline at the
+/// end of the input is added. The lines are compared without their newlines,
+/// like `LC_ALL=C sort` does, so a line sorts before the longer ones it's a
+/// prefix of. When the lines don't fit in the memory budget
+/// of `config`, they are sorted in parts with [`intro`](sort::intro) sort,
+/// written to temporary files and then merged. At most the fan-in of
+/// `config` runs are merged at once, and the merged runs are written to new
+/// temporary files until they are few enough for the last merge. The read
+/// buffers of the runs share the memory budget. The temporary files are
+/// removed before returning, also on errors.
+///
+/// # Errors
+///
+/// Returns the first I/O error of reading the input, writing the output or
+/// handling the temporary files.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::external::{self, ExternalConfig};
+///
+/// let input = "pear\napple\nfig\nbanana\n";
+/// let mut output = Vec::new();
+/// let config = ExternalConfig::new().memory(64);
+/// external::sort(input.as_bytes(), &mut output, &config).unwrap();
+/// assert_eq!(output, b"apple\nbanana\nfig\npear\n");
+/// ```
+pub fn sort<R: BufRead, W: Write>(
+    mut reader: R,
+    writer: W,
+    config: &ExternalConfig,
+) -> io::Result<()> {
+    let mut runs = Runs {
+        paths: Vec::new(),
+        sort_id: NEXT_SORT.fetch_add(1, Ordering::Relaxed),
+        created: 0,
+    };
+    let mut records = Vec::new();
+    let mut used = 0;
+
+    loop {
+        let mut record = Vec::new();
+        if reader.read_until(b'\n', &mut record)? == 0 {
+            break;
+        }
+        if record.last() == Some(&b'\n') {
+            record.pop();
+        }
+
+        if used + record.len() + RECORD_OVERHEAD > config.memory && !records.is_empty() {
+            let file = runs.create(config)?;
+            write_sorted(&mut records, file)?;
+            records.clear();
+            used = 0;
+        }
+
+        used += record.len() + RECORD_OVERHEAD;
+        records.push(record);
+    }
+
+    if runs.paths.is_empty() {
+        return write_sorted(&mut records, writer);
+    }
+
+    // the last run is spilled as well, so the read buffers get all the memory
+    let file = runs.create(config)?;
+    write_sorted(&mut records, file)?;
+    drop(records);
+
+    while runs.paths.len() > config.fan_in {
+        let readers = open_runs(&runs.paths[..config.fan_in], config)?;
+        let file = runs.create(config)?;
+        merge_runs(readers, file)?;
+
+        for path in &runs.paths[..config.fan_in] {
+            fs::remove_file(path)?;
+        }
+        runs.paths.drain(..config.fan_in);
+    }
+
+    let readers = open_runs(&runs.paths, config)?;
+    merge_runs(readers, writer)
+}
+
+/// Opens the files of runs, with read buffers that share the memory budget.
+fn open_runs(paths: &[PathBuf], config: &ExternalConfig) -> io::Result<Vec<BufReader<File>>> {
+    let capacity = (config.memory / paths.len()).max(MIN_BUFFER);
+    paths
+        .iter()
+        .map(|path| Ok(BufReader::with_capacity(capacity, File::open(path)?)))
+        .collect()
+}
+
+/// Sorts the records, and writes them to `writer`, each followed by `\n`.
+fn write_sorted<W: Write>(records: &mut [Vec<u8>], writer: W) -> io::Result<()> {
+    sort::intro(records);
+
+    let mut writer = BufWriter::new(writer);
+    for record in records.iter() {
+        writer.write_all(record)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+/// Reads the next line of a run into `line`, without its newline. Returns
+/// whether there was one.
+fn read_line<R: BufRead>(reader: &mut R, line: &mut Vec<u8>) -> io::Result<bool> {
+    if reader.read_until(b'\n', line)? == 0 {
+        return Ok(false);
+    }
+    if line.last() == Some(&b'\n') {
+        line.pop();
+    }
+    Ok(true)
+}
+
+/// Merges the sorted runs of lines into `writer`.
+fn merge_runs<R: BufRead, W: Write>(mut readers: Vec<R>, writer: W) -> io::Result<()> {
+    let mut writer = BufWriter::new(writer);
+
+    // the smallest line of every run, with the index of the run
+    let mut heap = BinaryHeap::with_capacity(readers.len());
+    for (i, reader) in readers.iter_mut().enumerate() {
+        let mut line = Vec::new();
+        if read_line(reader, &mut line)? {
+            heap.push(Reverse((line, i)));
+        }
+    }
+
+    while let Some(Reverse((mut line, i))) = heap.pop() {
+        writer.write_all(&line)?;
+        writer.write_all(b"\n")?;
+        line.clear();
+        if read_line(&mut readers[i], &mut line)? {
+            heap.push(Reverse((line, i)));
+        }
+    }
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sort;
+    use super::ExternalConfig;
+    use std::fs;
+
+    #[test]
+    fn sort_test() {
+        let lines: Vec<_> = (0..500).map(|x| format!("{}", (x * 37) % 101)).collect();
+        let input = lines.join("\n");
+        let mut expected = lines.clone();
+        expected.sort();
+        let expected: String = expected.iter().map(|l| format!("{}\n", l)).collect();
+
+        let dir = std::env::temp_dir().join(format!("search-sort-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for &memory in &[0, 100, 1000, 1 << 20] {
+            for &fan_in in &[2, 3, 64] {
+                let config = ExternalConfig::new()
+                    .memory(memory)
+                    .fan_in(fan_in)
+                    .temp_dir(&dir);
+                let mut output = Vec::new();
+                sort(input.as_bytes(), &mut output, &config).unwrap();
+                assert_eq!(String::from_utf8(output).unwrap(), expected);
+            }
+        }
+        // the runs have been removed
+        fs::remove_dir(&dir).unwrap();
+
+        let mut output = Vec::new();
+        sort(&b""[..], &mut output, &ExternalConfig::new()).unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn tab_test() {
+        // a tab is below the newline, so the newline mustn't be compared
+        let input = "a\tb\na\nb\n\na\t\n";
+        let expected = "\na\na\t\na\tb\nb\n";
+        let dir = std::env::temp_dir().join(format!("search-sort-tab-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for &memory in &[0, 1 << 20] {
+            let config = ExternalConfig::new()
+                .memory(memory)
+                .fan_in(2)
+                .temp_dir(&dir);
+            let mut output = Vec::new();
+            sort(input.as_bytes(), &mut output, &config).unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), expected);
+        }
+        fs::remove_dir(&dir).unwrap();
+    }
+
+    #[test]
+    #[should_panic]
+    fn fan_in_test() {
+        ExternalConfig::new().fan_in(1);
+    }
+
+    #[test]
+    fn sort_error_test() {
+        let dir = std::env::temp_dir().join("search-sort-test-missing-dir");
+        let config = ExternalConfig::new().memory(0).temp_dir(dir);
+        let mut output = Vec::new();
+        assert!(sort(&b"b\na\n"[..], &mut output, &config).is_err());
+    }
+}
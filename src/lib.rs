@@ -7,8 +7,46 @@
 //! - [jump](search::jump) search
 //! - [exponential](search::exp) search
 //! - [bubble](sort::bubble) sort
+//! - [comb](sort::comb) sort
+//! - [odd-even](sort::odd_even) transposition sort
+//! - [insertion](sort::insertion) sort
+//! - [selection](sort::selection) sort
+//! - [pancake](sort::pancake) sort
 //! - [quick](sort::quick) sort
+//! - [introsort](sort::intro)
+//! - [sample](sort::sample) sort
+//! - [merge-insertion](sort::merge_insertion) sort
 //! - [merge](sort::merge) sort
+//!   - [in-place](sort::merge_in_place) merge sort
+//! - [heap](sort::heap) sort
+//!   - [weak-heap](sort::weak_heap) sort
+//! - [Timsort](sort::tim)
+//! - [Cartesian tree](sort::cartesian_tree) sort
+//! - [bitonic](sort::bitonic) sort
+//! - [sorting networks](sort::network) for small arrays
+//! - [counting](sort::counting) sort
+//! - [pigeonhole](sort::pigeonhole) sort
+//! - [bead](sort::bead) sort
+//! - [LSD radix](sort::radix_lsd) sort
+//! - [MSD radix](sort::radix_bytes) sort
+//! - [American flag](sort::american_flag) sort
+//! - [bucket](sort::bucket) sort
+//! - [flashsort](sort::flash)
+//! - [spreadsort](sort::spread)
+//! - [external](external::sort) sort for data larger than memory
+//!
+//! The [`sorted`] module provides collections built on them, like
+//! [`SortedVec`](sorted::SortedVec), and [`Sorted`](sorted::Sorted) slices
+//! that can be searched without checking the order again.
+//! The [`measure`] module computes how far a slice is from being sorted.
+//!
+//! With the `educational` feature, the crate also provides deliberately slow
+//! algorithms for demonstrations: `bogo`, `stooge` and `slow` sort.
+//! The `rand` feature adds `quick_random` sort, which chooses the pivots at
+//! random.
+//! The `rayon` feature adds parallel sorts, like `par_quick`.
+//! The `serde` feature implements `Serialize` and `Deserialize` for
+//! `SortedVec`.
 //!
 //! # Quick example
 //! ```
@@ -22,5 +60,10 @@
 //! assert_eq!(None, search::binary_first(&slice, &42));
 //! ```
 
+pub mod cmp;
+pub mod external;
+pub mod measure;
 pub mod search;
 pub mod sort;
+pub mod sorted;
+pub mod util;
@@ -0,0 +1,195 @@
+//! Measures of presortedness.
+//!
+//! They tell how far a slice is from being sorted, in different ways. All of
+//! them are `0` for a sorted slice. Adaptive sorts are faster for slices with
+//! small values of some measure: [`tim`](crate::sort::tim) sort for few
+//! [`runs`], [`insertion`](crate::sort::insertion) sort for few inversions,
+//! which are counted by [`count_inversions`](crate::sort::count_inversions).
+
+use crate::sort;
+
+/// Returns the number of descents of a slice: the adjacent pairs, in which the
+/// first element is greater than the second one.
+///
+/// It's the number of ascending runs, minus one.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::measure;
+///
+/// assert_eq!(measure::runs(&[1, 2, 3]), 0);
+/// assert_eq!(measure::runs(&[3, 4, 1, 2, 0]), 2);
+/// ```
+pub fn runs<T: Ord>(slice: &[T]) -> usize {
+    slice.windows(2).filter(|pair| pair[0] > pair[1]).count()
+}
+
+/// Returns the minimal number of exchanges of two elements that sort a
+/// slice.
+///
+/// Every cycle of the permutation that sorts the slice takes one exchange
+/// less than its length. Equal elements keep their relative order, so if
+/// there are any, the result may be larger than the minimum.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::measure;
+///
+/// assert_eq!(measure::exc(&[1, 2, 3]), 0);
+/// assert_eq!(measure::exc(&[3, 2, 1]), 1);
+/// assert_eq!(measure::exc(&[2, 3, 1]), 2);
+/// ```
+pub fn exc<T: Ord>(slice: &[T]) -> usize {
+    let order = sort::argsort(slice);
+
+    let mut visited = vec![false; slice.len()];
+    let mut cycles = 0;
+    for start in 0..slice.len() {
+        if visited[start] {
+            continue;
+        }
+
+        cycles += 1;
+        let mut i = start;
+        while !visited[i] {
+            visited[i] = true;
+            i = order[i];
+        }
+    }
+
+    slice.len() - cycles
+}
+
+/// Returns the largest distance between the elements of an inversion: a pair
+/// of elements, in which the first one is greater than the second one.
+///
+/// Every element is sorted by moving it at most that far. The leftmost
+/// element greater than the current one is found by binary search in the
+/// maxima of the prefixes, so it takes `O(n log n)` time.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::measure;
+///
+/// assert_eq!(measure::dis(&[1, 2, 3]), 0);
+/// assert_eq!(measure::dis(&[2, 1, 3, 5, 4]), 1);
+/// assert_eq!(measure::dis(&[5, 1, 2, 3, 4]), 4);
+/// ```
+pub fn dis<T: Ord>(slice: &[T]) -> usize {
+    let mut prefix_max: Vec<&T> = Vec::with_capacity(slice.len());
+    let mut dis = 0;
+    for (j, v) in slice.iter().enumerate() {
+        let i = prefix_max.partition_point(|&max| max <= v);
+        dis = dis.max(j - i);
+
+        match prefix_max.last() {
+            Some(&max) if max > v => prefix_max.push(max),
+            _ => prefix_max.push(v),
+        }
+    }
+
+    dis
+}
+
+/// Returns the smallest number of elements that have to be removed from a
+/// slice to leave it sorted.
+///
+/// It's the length of the slice minus the length of its longest
+/// non-decreasing subsequence, which is found by patience sorting in
+/// `O(n log n)` time.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::measure;
+///
+/// assert_eq!(measure::rem(&[1, 2, 3]), 0);
+/// assert_eq!(measure::rem(&[1, 9, 2, 3, 0, 4]), 2);
+/// ```
+pub fn rem<T: Ord>(slice: &[T]) -> usize {
+    // the smallest last element of a subsequence of every length
+    let mut tails: Vec<&T> = Vec::new();
+    for v in slice {
+        let len = tails.partition_point(|&tail| tail <= v);
+        if len == tails.len() {
+            tails.push(v);
+        } else {
+            tails[len] = v;
+        }
+    }
+
+    slice.len() - tails.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dis;
+    use super::exc;
+    use super::rem;
+    use super::runs;
+
+    #[test]
+    fn sorted_test() {
+        let sorted: Vec<_> = (0..100).map(|x| x / 3).collect();
+        assert_eq!(runs(&sorted), 0);
+        assert_eq!(exc(&sorted), 0);
+        assert_eq!(dis(&sorted), 0);
+        assert_eq!(rem(&sorted), 0);
+        assert_eq!((runs::<i32>(&[]), exc::<i32>(&[])), (0, 0));
+        assert_eq!((dis::<i32>(&[]), rem::<i32>(&[])), (0, 0));
+    }
+
+    #[test]
+    fn reversed_test() {
+        let reversed: Vec<_> = (0..100).rev().collect();
+        assert_eq!(runs(&reversed), 99);
+        assert_eq!(exc(&reversed), 50);
+        assert_eq!(dis(&reversed), 99);
+        assert_eq!(rem(&reversed), 99);
+    }
+
+    #[test]
+    fn brute_force_test() {
+        for len in 0..40 {
+            let data: Vec<_> = (0..len).map(|x| (x * 37) % 41).collect();
+
+            let mut expected_dis = 0;
+            for i in 0..data.len() {
+                for j in (i + 1)..data.len() {
+                    if data[i] > data[j] {
+                        expected_dis = expected_dis.max(j - i);
+                    }
+                }
+            }
+            assert_eq!(dis(&data), expected_dis);
+
+            // the longest non-decreasing subsequence ending at every element
+            let mut longest = vec![1; data.len()];
+            for j in 0..data.len() {
+                for i in 0..j {
+                    if data[i] <= data[j] {
+                        longest[j] = longest[j].max(longest[i] + 1);
+                    }
+                }
+            }
+            let expected_rem = data.len() - longest.iter().max().unwrap_or(&0);
+            assert_eq!(rem(&data), expected_rem);
+
+            // sorting by selection takes the fewest exchanges of distinct
+            // elements
+            let mut sorted = data.clone();
+            let mut exchanges = 0;
+            for i in 0..sorted.len() {
+                let min = (i..sorted.len()).min_by_key(|&k| sorted[k]).unwrap();
+                if min != i {
+                    sorted.swap(i, min);
+                    exchanges += 1;
+                }
+            }
+            assert_eq!(exc(&data), exchanges);
+        }
+    }
+}
@@ -1,6 +1,9 @@
 //! Implementations of searching algorithms.
 
 use std::cmp::Ordering;
+use std::ops::Range;
+
+use crate::sort;
 
 /// An implementation of linear search.
 ///
@@ -28,6 +31,16 @@ pub fn linear<T: PartialEq>(slice: &[T], value: &T) -> Option<usize> {
     None
 }
 
+/// Linear search for an element equal to the value by `compare`.
+fn linear_by<T, F>(slice: &[T], value: &T, compare: &mut F) -> Option<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    slice
+        .iter()
+        .position(|v| compare(value, v) == Ordering::Equal)
+}
+
 /// An implementation of binary search.
 ///
 /// Recursively searches for the value in a sorted slice. It does the following:
@@ -36,9 +49,9 @@ pub fn linear<T: PartialEq>(slice: &[T], value: &T) -> Option<usize> {
 /// * if it's smaller, invokes itself with the first part of the slice,
 /// * if they are equal, returns the center,
 /// * if it's greater, invokes itself with the second part of the slice and
-///     adds the current center and 1.
+///   adds the current center and 1.
 /// * if didn't find the value (center == 0 || center >= size - 1), returns
-///     [`None`].
+///   [`None`].
 ///
 /// **Note**: the returned value is the position of the first found element,
 /// that may not be the position of the first element in the whole slice. Use
@@ -54,15 +67,60 @@ pub fn linear<T: PartialEq>(slice: &[T], value: &T) -> Option<usize> {
 /// assert_eq!(search::binary(&slice, &3), None);
 /// ```
 pub fn binary<T: Ord>(slice: &[T], value: &T) -> Option<usize> {
-    let mid = slice.len() / 2;
-    match value.cmp(&slice[mid]) {
-        Ordering::Less if mid > 0 => binary(&slice[0..mid], value),
-        Ordering::Equal => Some(mid),
-        Ordering::Greater if mid < slice.len() - 1 => {
-            binary(&slice[(mid + 1)..slice.len()], value).map(|x| x + mid + 1)
+    binary_by(slice, value, T::cmp)
+}
+
+/// An implementation of [`binary`] search in a slice sorted by a comparator
+/// function.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::search;
+///
+/// let slice = ["fig", "kiwi", "apple", "banana"];
+/// let by_len = |a: &&str, b: &&str| a.len().cmp(&b.len());
+/// assert_eq!(search::binary_by(&slice, &"lemon", by_len), Some(2));
+/// ```
+pub fn binary_by<T, F>(slice: &[T], value: &T, mut compare: F) -> Option<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    fn inner<T, F>(slice: &[T], value: &T, compare: &mut F) -> Option<usize>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let mid = slice.len() / 2;
+        match compare(value, &slice[mid]) {
+            Ordering::Less if mid > 0 => inner(&slice[0..mid], value, compare),
+            Ordering::Equal => Some(mid),
+            Ordering::Greater if mid < slice.len() - 1 => {
+                inner(&slice[(mid + 1)..slice.len()], value, compare).map(|x| x + mid + 1)
+            }
+            _ => None,
         }
-        _ => None,
     }
+
+    if slice.is_empty() {
+        return None;
+    }
+    inner(slice, value, &mut compare)
+}
+
+/// An implementation of [`binary`] search in a slice sorted in descending
+/// order.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::search;
+///
+/// let slice = [32, 16, 8, 4, 2, 1];
+/// assert_eq!(search::binary_desc(&slice, &8), Some(2));
+/// assert_eq!(search::binary_desc(&slice, &3), None);
+/// ```
+pub fn binary_desc<T: Ord>(slice: &[T], value: &T) -> Option<usize> {
+    binary_by(slice, value, |a, b| b.cmp(a))
 }
 
 /// An implementation of binary search that finds the very first position of
@@ -84,11 +142,30 @@ pub fn binary<T: Ord>(slice: &[T], value: &T) -> Option<usize> {
 /// assert_eq!(search::binary_first(&fib, &1), Some(0));
 /// ```
 pub fn binary_first<T: Ord>(slice: &[T], value: &T) -> Option<usize> {
-    let pos = binary(slice, value);
+    binary_first_by(slice, value, T::cmp)
+}
+
+/// An implementation of [`binary_first`] search in a slice sorted by a
+/// comparator function.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::search;
+///
+/// let slice = [(0, 'a'), (1, 'b'), (1, 'c'), (2, 'd')];
+/// let by_key = |a: &(i32, char), b: &(i32, char)| a.0.cmp(&b.0);
+/// assert_eq!(search::binary_first_by(&slice, &(1, ' '), by_key), Some(1));
+/// ```
+pub fn binary_first_by<T, F>(slice: &[T], value: &T, mut compare: F) -> Option<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let pos = binary_by(slice, value, &mut compare);
     match pos {
         Some(pos) => {
             for (i, v) in slice[0..pos].iter().enumerate().rev() {
-                if v < value {
+                if compare(v, value) == Ordering::Less {
                     return Some(i + 1);
                 }
             }
@@ -98,6 +175,21 @@ pub fn binary_first<T: Ord>(slice: &[T], value: &T) -> Option<usize> {
     }
 }
 
+/// An implementation of [`binary_first`] search in a slice sorted in
+/// descending order.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::search;
+///
+/// let slice = [3, 2, 1, 1];
+/// assert_eq!(search::binary_first_desc(&slice, &1), Some(2));
+/// ```
+pub fn binary_first_desc<T: Ord>(slice: &[T], value: &T) -> Option<usize> {
+    binary_first_by(slice, value, |a, b| b.cmp(a))
+}
+
 /// An implementation of jump search with custom `step`.
 ///
 /// Jumps over a sorted slice by fixed steps, until it finds the largest
@@ -109,11 +201,20 @@ pub fn binary_first<T: Ord>(slice: &[T], value: &T) -> Option<usize> {
 ///
 /// See also [`jump`] function.
 pub fn jump_step<T: Ord>(slice: &[T], value: &T, step: usize) -> Option<usize> {
+    jump_step_by(slice, value, step, T::cmp)
+}
+
+/// An implementation of [`jump_step`] search in a slice sorted by a comparator
+/// function.
+pub fn jump_step_by<T, F>(slice: &[T], value: &T, step: usize, mut compare: F) -> Option<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
     if step == 1 {
-        return linear(slice, value);
+        return linear_by(slice, value, &mut compare);
     } else if step == 0 {
         // it would be stuck on the first element
-        if &slice[0] == value {
+        if slice.first().map(|v| compare(v, value)) == Some(Ordering::Equal) {
             return Some(0);
         } else {
             return None;
@@ -127,7 +228,7 @@ pub fn jump_step<T: Ord>(slice: &[T], value: &T, step: usize) -> Option<usize> {
     let mut found = false;
 
     for i in 0..(slice.len() / step) {
-        match value.cmp(iter.next().unwrap()) {
+        match compare(value, iter.next().unwrap()) {
             Ordering::Less => {
                 if i == 0 {
                     // smaller than every element
@@ -160,7 +261,7 @@ pub fn jump_step<T: Ord>(slice: &[T], value: &T, step: usize) -> Option<usize> {
     }
 
     // no need to check the element on pos
-    linear(&slice[(pos + 1)..end], value).map(|x| x + pos + 1)
+    linear_by(&slice[(pos + 1)..end], value, &mut compare).map(|x| x + pos + 1)
 }
 
 /// An implementation of jump search with optimal `step`.
@@ -178,7 +279,40 @@ pub fn jump_step<T: Ord>(slice: &[T], value: &T, step: usize) -> Option<usize> {
 /// assert_eq!(search::jump(&slice, &15), Some(3));
 /// ```
 pub fn jump<T: Ord>(slice: &[T], value: &T) -> Option<usize> {
-    jump_step(slice, value, (slice.len() as f64).sqrt() as usize)
+    jump_by(slice, value, T::cmp)
+}
+
+/// An implementation of [`jump`] search in a slice sorted by a comparator
+/// function.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::search;
+///
+/// let slice = [-1, 2, -5, 7, -9, 12];
+/// let by_abs = |a: &i32, b: &i32| a.abs().cmp(&b.abs());
+/// assert_eq!(search::jump_by(&slice, &-7, by_abs), Some(3));
+/// ```
+pub fn jump_by<T, F>(slice: &[T], value: &T, compare: F) -> Option<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    jump_step_by(slice, value, (slice.len() as f64).sqrt() as usize, compare)
+}
+
+/// An implementation of [`jump`] search in a slice sorted in descending order.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::search;
+///
+/// let slice = [45, 32, 31, 15, 7, 5, 1];
+/// assert_eq!(search::jump_desc(&slice, &15), Some(3));
+/// ```
+pub fn jump_desc<T: Ord>(slice: &[T], value: &T) -> Option<usize> {
+    jump_by(slice, value, |a, b| b.cmp(a))
 }
 
 /// An implementation of exponential search.
@@ -195,32 +329,187 @@ pub fn jump<T: Ord>(slice: &[T], value: &T) -> Option<usize> {
 /// assert_eq!(search::exp(&slice, &6), Some(2));
 /// ```
 pub fn exp<T: Ord>(slice: &[T], value: &T) -> Option<usize> {
-    if &slice[0] == value {
-        // the loop doesn't check the first element
-        return Some(0);
+    exp_by(slice, value, T::cmp)
+}
+
+/// An implementation of [`exp`]onential search in a slice sorted by a
+/// comparator function.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::search;
+///
+/// let slice = ["a", "to", "the", "pear", "apple"];
+/// let by_len = |a: &&str, b: &&str| a.len().cmp(&b.len());
+/// assert_eq!(search::exp_by(&slice, &"kiwi", by_len), Some(3));
+/// ```
+pub fn exp_by<T, F>(slice: &[T], value: &T, mut compare: F) -> Option<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // the value is between the halved bound and the bound
+    let mut bound = 1;
+    while bound < slice.len() && compare(&slice[bound], value) == Ordering::Less {
+        bound *= 2;
     }
 
-    let mut exp = 0;
-    let start = loop {
-        let i = usize::pow(2, exp);
-        match value.cmp(&slice[i]) {
-            Ordering::Less if i > 1 => break i / 2,
-            Ordering::Equal => return Some(i),
-            Ordering::Greater if i < slice.len() - 1 => {}
-            _ => return None,
+    let start = bound / 2;
+    let end = (bound + 1).min(slice.len());
+    binary_first_by(&slice[start..end], value, compare).map(|x| x + start)
+}
+
+/// An implementation of [`exp`]onential search in a slice sorted in
+/// descending order.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::search;
+///
+/// let slice = [17, 12, 11, 7, 6, 4, 2];
+/// assert_eq!(search::exp_desc(&slice, &6), Some(4));
+/// ```
+pub fn exp_desc<T: Ord>(slice: &[T], value: &T) -> Option<usize> {
+    exp_by(slice, value, |a, b| b.cmp(a))
+}
+
+/// Finds the end of the run of elements equal to `slice[start]` in a sorted
+/// slice.
+///
+/// Gallops forward by doubling steps until it passes the run, and then binary
+/// searches the last step, so it does `O(log k)` comparisons for a run of
+/// length `k`.
+fn gallop_end<T: Ord>(slice: &[T], start: usize) -> usize {
+    let value = &slice[start];
+
+    // all the elements before lo are known to be equal
+    let mut lo = start + 1;
+    let mut step = 1;
+    loop {
+        let hi = lo.saturating_add(step).min(slice.len());
+        if hi == lo {
+            return lo;
+        } else if &slice[hi - 1] != value {
+            return lo + slice[lo..(hi - 1)].partition_point(|x| x == value);
         }
 
-        exp += 1;
+        lo = hi;
+        step *= 2;
+    }
+}
+
+/// Returns an iterator over the ranges of equal elements in a sorted slice.
+///
+/// The end of every range is found by galloping, which is much faster than
+/// scanning the slice when the ranges are long.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::search;
+///
+/// let slice = [1, 1, 1, 4, 7, 7];
+/// let groups: Vec<_> = search::group_boundaries(&slice).collect();
+/// assert_eq!(groups, [0..3, 3..4, 4..6]);
+/// ```
+pub fn group_boundaries<T: Ord>(slice: &[T]) -> impl Iterator<Item = Range<usize>> + '_ {
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        if start >= slice.len() {
+            return None;
+        }
+
+        let end = gallop_end(slice, start);
+        let range = start..end;
+        start = end;
+        Some(range)
+    })
+}
+
+/// Checks if every element of a sorted slice is in another sorted slice.
+///
+/// Both slices are scanned once from the beginning, so it takes
+/// `O(n + m)` comparisons, which is less than a [`binary`] search for every
+/// needle, unless there are only a few of them. Repeated needles may match
+/// the same element of the haystack.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::search;
+///
+/// let haystack = [1, 3, 4, 7, 9, 12];
+/// assert!(search::is_subset(&[3, 7, 7, 12], &haystack));
+/// assert!(!search::is_subset(&[3, 8], &haystack));
+/// ```
+pub fn is_subset<T: Ord>(needles: &[T], haystack: &[T]) -> bool {
+    let mut i = 0;
+    for needle in needles {
+        while i < haystack.len() && &haystack[i] < needle {
+            i += 1;
+        }
+        if i == haystack.len() || &haystack[i] != needle {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Checks for every needle if it's in a sorted slice.
+///
+/// The needles are visited in ascending order, given by
+/// [`argsort`](crate::sort::argsort) unless they're already sorted, and the
+/// haystack is scanned once along with them. So all the queries take
+/// `O(n + m log m)` comparisons and access the memory sequentially, unlike
+/// separate [`binary`] searches. The answers are in the order of the needles.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::search;
+///
+/// let haystack = [1, 3, 4, 7, 9, 12];
+/// let found = search::contains_many(&haystack, &[7, 2, 12, 1, 7]);
+/// assert_eq!(found, [true, false, true, true, true]);
+/// ```
+pub fn contains_many<T: Ord>(haystack: &[T], needles: &[T]) -> Vec<bool> {
+    let mut found = vec![false; needles.len()];
+    let mut visit = |i: usize, pos: &mut usize| {
+        while *pos < haystack.len() && haystack[*pos] < needles[i] {
+            *pos += 1;
+        }
+        found[i] = *pos < haystack.len() && haystack[*pos] == needles[i];
     };
 
-    binary_first(&slice[start..(start * 2)], value).map(|x| x + start)
+    let mut pos = 0;
+    if sort::test(needles) {
+        for i in 0..needles.len() {
+            visit(i, &mut pos);
+        }
+    } else {
+        for i in sort::argsort(needles) {
+            visit(i, &mut pos);
+        }
+    }
+
+    found
 }
 
 #[cfg(test)]
 mod tests {
     use super::binary;
+    use super::binary_desc;
     use super::binary_first;
+    use super::binary_first_desc;
+    use super::contains_many;
+    use super::exp;
+    use super::exp_desc;
+    use super::group_boundaries;
+    use super::is_subset;
     use super::jump;
+    use super::jump_desc;
     use super::linear;
 
     #[test]
@@ -239,6 +528,7 @@ mod tests {
         assert_eq!(binary(&primes, &8), None);
         assert_eq!(binary(&primes, &0), None);
         assert_eq!(binary(&primes, &18), None);
+        assert_eq!(binary(&[], &1), None);
     }
 
     #[test]
@@ -258,5 +548,74 @@ mod tests {
         let slice = [-2, 0, 3, 6, 7, 12, 23, 25, 31, 41];
         assert_eq!(jump(&slice, &12), Some(5));
         assert_eq!(jump(&slice, &13), None);
+
+        for len in 0..40 {
+            let slice: Vec<_> = (0..len).map(|x| x * 2).collect();
+            for x in -1..(2 * len + 1) {
+                let expected = if x % 2 == 0 && x < 2 * len {
+                    Some(x as usize / 2)
+                } else {
+                    None
+                };
+                assert_eq!(exp(&slice, &x), expected);
+                assert_eq!(jump(&slice, &x), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn desc_test() {
+        let slice = [41, 31, 25, 23, 12, 12, 7, 6, 3, 0, -2];
+        assert_eq!(binary_desc(&slice, &23), Some(3));
+        assert_eq!(binary_first_desc(&slice, &12), Some(4));
+        assert_eq!(jump_desc(&slice, &6), Some(7));
+        assert_eq!(exp_desc(&slice, &3), Some(8));
+
+        assert_eq!(binary_desc(&slice, &13), None);
+        assert_eq!(jump_desc(&slice, &42), None);
+        assert_eq!(exp_desc(&slice, &24), None);
+    }
+
+    #[test]
+    fn group_boundaries_test() {
+        let slice = [0, 2, 2, 2, 2, 2, 2, 2, 2, 2, 5, 9, 9];
+        let groups: Vec<_> = group_boundaries(&slice).collect();
+        assert_eq!(groups, [0..1, 1..10, 10..11, 11..13]);
+
+        for len in 0..40usize {
+            let slice: Vec<_> = (0..len).map(|x| x / 7).collect();
+            let groups: Vec<_> = group_boundaries(&slice).collect();
+            assert_eq!(groups.len(), len.div_ceil(7));
+            for range in groups {
+                assert!(slice[range.clone()]
+                    .iter()
+                    .all(|x| x == &slice[range.start]));
+            }
+        }
+    }
+
+    #[test]
+    fn is_subset_test() {
+        let haystack: Vec<_> = (0..50).map(|x| x * 2).collect();
+        assert!(is_subset(&[0, 10, 10, 98], &haystack));
+        assert!(is_subset(&[], &haystack));
+        assert!(is_subset(&haystack, &haystack));
+        assert!(!is_subset(&[0, 11], &haystack));
+        assert!(!is_subset(&[-1], &haystack));
+        assert!(!is_subset(&[100], &haystack));
+        assert!(!is_subset(&[1], &[]));
+    }
+
+    #[test]
+    fn contains_many_test() {
+        let haystack: Vec<_> = (0..50).map(|x| x * 2).collect();
+        let needles: Vec<_> = (0..300).map(|x| (x * 37) % 101 - 1).collect();
+        let expected: Vec<_> = needles.iter().map(|x| haystack.contains(x)).collect();
+        assert_eq!(contains_many(&haystack, &needles), expected);
+
+        let sorted: Vec<_> = (-1..101).collect();
+        let expected: Vec<_> = sorted.iter().map(|x| haystack.contains(x)).collect();
+        assert_eq!(contains_many(&haystack, &sorted), expected);
+        assert_eq!(contains_many(&[], &[1, 0]), [false, false]);
     }
 }
@@ -1,6 +1,14 @@
 //! Implementations of sorting algorithms.
 
 use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::fmt;
+use std::iter::Peekable;
+use std::mem;
+use std::ops::{Bound, Range, RangeBounds, Sub};
+use std::vec;
+
+use crate::{search, util};
 
 /// Checks if a slice is sorted.
 pub fn test<T: Ord>(slice: &[T]) -> bool {
@@ -12,7 +20,7 @@ pub fn test<T: Ord>(slice: &[T]) -> bool {
     let mut prev = iter.next().unwrap();
     let mut curr = iter.next();
 
-    while curr != None {
+    while curr.is_some() {
         let value = curr.unwrap();
         if prev > value {
             return false;
@@ -30,6 +38,250 @@ pub fn is_sorted<T: Ord>(slice: &[T]) -> bool {
     test(slice)
 }
 
+/// Checks if a slice is sorted by a comparator function, as by the `_by`
+/// sorts, like [`merge_by`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert!(sort::is_sorted_by(&["fig", "kiwi", "apple"], |a, b| a.len().cmp(&b.len())));
+/// assert!(!sort::is_sorted_by(&[1, 2], |a, b| b.cmp(a)));
+/// ```
+pub fn is_sorted_by<T, F>(slice: &[T], mut compare: F) -> bool
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    test_by(slice, &mut compare)
+}
+
+/// Checks if a slice is sorted by the keys extracted by `key_fn`, as by the
+/// `_by_key` sorts, like [`merge_by_key`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert!(sort::is_sorted_by_key(&[-1, 2, -3], |x: &i32| x.abs()));
+/// ```
+pub fn is_sorted_by_key<T, K, F>(slice: &[T], mut key_fn: F) -> bool
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    test_by(slice, &mut |a, b| key_fn(a).cmp(&key_fn(b)))
+}
+
+/// Checks if a slice is sorted and has no equal elements.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert!(sort::is_strictly_sorted(&[1, 2, 4]));
+/// assert!(!sort::is_strictly_sorted(&[1, 2, 2]));
+/// ```
+pub fn is_strictly_sorted<T: Ord>(slice: &[T]) -> bool {
+    slice.windows(2).all(|pair| pair[0] < pair[1])
+}
+
+/// Checks if a slice is sorted in descending order, as by the `_desc` sorts,
+/// like [`merge_desc`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert!(sort::is_sorted_desc(&[5, 3, 3, 1]));
+/// assert!(!sort::is_sorted_desc(&[1, 3]));
+/// ```
+pub fn is_sorted_desc<T: Ord>(slice: &[T]) -> bool {
+    test_by(slice, &mut |a: &T, b: &T| b.cmp(a))
+}
+
+/// Returns the length of the longest sorted prefix of a slice.
+///
+/// It's the length of the slice if it's sorted, and otherwise the position
+/// of the first element that is smaller than the previous one.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert_eq!(sort::is_sorted_until(&[1, 2, 2, 5, 3, 4]), 4);
+/// assert_eq!(sort::is_sorted_until(&[1, 2, 3]), 3);
+/// ```
+pub fn is_sorted_until<T: Ord>(slice: &[T]) -> usize {
+    is_sorted_until_by(slice, T::cmp)
+}
+
+/// Like [`is_sorted_until`], but compares the elements with a comparator
+/// function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert_eq!(sort::is_sorted_until_by(&[3, 2, 5], |a, b| b.cmp(a)), 2);
+/// ```
+pub fn is_sorted_until_by<T, F>(slice: &[T], mut compare: F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    slice
+        .windows(2)
+        .position(|pair| compare(&pair[0], &pair[1]) == Ordering::Greater)
+        .map_or(slice.len(), |i| i + 1)
+}
+
+/// Like [`is_sorted_until`], but compares the keys extracted by `key_fn`.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert_eq!(sort::is_sorted_until_by_key(&[-1, 2, -3, 0], |x: &i32| x.abs()), 3);
+/// ```
+pub fn is_sorted_until_by_key<T, K, F>(slice: &[T], mut key_fn: F) -> usize
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    is_sorted_until_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)))
+}
+
+/// Checks if a slice of floats is sorted up to an error of less than
+/// `epsilon`.
+///
+/// Every element may be smaller than the elements before it, but by less
+/// than `epsilon`. It's compared with the largest of them, so small errors
+/// don't add up into a descending slice. Slices with NaNs are never sorted.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let readings = [0.0, 1.02, 0.99, 2.0, 1.98, 3.1];
+/// assert!(sort::is_sorted_within(&readings, 0.05));
+/// assert!(!sort::is_sorted_within(&readings, 0.02));
+///
+/// // the errors are measured from the largest element
+/// assert!(!sort::is_sorted_within(&[1.0, 0.96, 0.92], 0.05));
+/// ```
+pub fn is_sorted_within<T>(slice: &[T], epsilon: T) -> bool
+where
+    T: Float + Sub<Output = T>,
+{
+    let mut iter = slice.iter();
+    let mut max = match iter.next() {
+        Some(&first) if !first.is_nan() => first,
+        Some(_) => return false,
+        None => return true,
+    };
+
+    for &v in iter {
+        if v.is_nan() {
+            return false;
+        } else if v > max {
+            max = v;
+        } else if v < max && max - v >= epsilon {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Counts the inversions of a slice: the pairs of elements, in which the
+/// first element is greater than the second one.
+///
+/// A copy of the slice is sorted like by [`merge`] sort. Every time an
+/// element is taken from the right half, the remaining elements of the left
+/// half are greater than it, so they're counted at once. It takes
+/// `O(n log n)` time instead of checking all the pairs. It's `0` for a sorted
+/// slice and `n(n - 1)/2` for a slice sorted in the reversed order, without
+/// equal elements.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert_eq!(sort::count_inversions(&[1, 2, 3]), 0);
+/// assert_eq!(sort::count_inversions(&[3, 1, 2, 1]), 4);
+/// assert_eq!(sort::count_inversions(&[4, 3, 2, 1]), 6);
+/// ```
+pub fn count_inversions<T: Ord + Clone>(slice: &[T]) -> u64 {
+    count_inversions_by(slice, T::cmp)
+}
+
+/// Like [`count_inversions`], but compares the elements with a comparator
+/// function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert_eq!(sort::count_inversions_by(&[1, 2, 3], |a, b| b.cmp(a)), 3);
+/// ```
+pub fn count_inversions_by<T, F>(slice: &[T], mut compare: F) -> u64
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    fn inner<T, F>(slice: &mut [T], buf: &mut Vec<T>, compare: &mut F) -> u64
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if slice.len() < 2 {
+            return 0;
+        }
+
+        let mid = slice.len() / 2;
+        let mut count = inner(&mut slice[..mid], buf, compare);
+        count += inner(&mut slice[mid..], buf, compare);
+
+        buf.clear();
+        let (left, right) = slice.split_at(mid);
+        let (mut i, mut j) = (0, 0);
+        while i < left.len() && j < right.len() {
+            if compare(&right[j], &left[i]) == Ordering::Less {
+                // all the remaining elements of the left half are greater
+                count += (left.len() - i) as u64;
+                buf.push(right[j].clone());
+                j += 1;
+            } else {
+                buf.push(left[i].clone());
+                i += 1;
+            }
+        }
+        buf.extend_from_slice(&left[i..]);
+        buf.extend_from_slice(&right[j..]);
+        slice.clone_from_slice(buf);
+
+        count
+    }
+
+    let mut copy = slice.to_vec();
+    let mut buf = Vec::with_capacity(slice.len());
+    inner(&mut copy, &mut buf, &mut compare)
+}
+
+/// Converts any range into bounds that can index a slice.
+fn bounds<R: RangeBounds<usize>>(range: R) -> (Bound<usize>, Bound<usize>) {
+    (range.start_bound().cloned(), range.end_bound().cloned())
+}
+
+/// Checks if a slice is sorted by the `compare` function.
+fn test_by<T, F>(slice: &[T], compare: &mut F) -> bool
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    slice
+        .windows(2)
+        .all(|pair| compare(&pair[0], &pair[1]) != Ordering::Greater)
+}
+
 /// An implementation of bubble sort.
 ///
 /// Checks for every element if the next element is greater than this and swaps
@@ -44,7 +296,24 @@ pub fn is_sorted<T: Ord>(slice: &[T]) -> bool {
 /// assert_eq!(slice, [-44, 1, 2, 3, 6, 11]);
 /// ```
 pub fn bubble<T: Ord>(slice: &mut [T]) {
-    if test(slice) {
+    bubble_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`bubble`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [1, 6, 3, -44, 11, 2];
+/// sort::bubble_by(&mut slice, |a, b| b.cmp(a));
+/// assert_eq!(slice, [11, 6, 3, 2, 1, -44]);
+/// ```
+pub fn bubble_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if test_by(slice, &mut compare) {
         return;
     }
 
@@ -53,7 +322,7 @@ pub fn bubble<T: Ord>(slice: &mut [T]) {
         let mut newn = 0;
 
         for i in 1..n {
-            if slice[i - 1] > slice[i] {
+            if compare(&slice[i - 1], &slice[i]) == Ordering::Greater {
                 slice.swap(i - 1, i);
                 newn = i;
             }
@@ -63,191 +332,10408 @@ pub fn bubble<T: Ord>(slice: &mut [T]) {
     }
 }
 
-/// Part of quick sort algorithm.
+/// Sorts a slice with [`bubble`] sort, using a key extraction function.
 ///
-/// Sets the pivot, places smaller elements before it and greater after it.
-/// Returns the final position of the pivot.
+/// # Examples
+/// ```
+/// use search_sort::sort;
 ///
-/// This function is used in [`quick`] sort.
-pub fn quick_partition<T: Ord>(slice: &mut [T]) -> usize {
-    // 'the pivot' is the last element of the slice
+/// let mut slice = [(2, 'a'), (1, 'b'), (2, 'c'), (0, 'd')];
+/// sort::bubble_by_key(&mut slice, |p| p.0);
+/// assert_eq!(slice, [(0, 'd'), (1, 'b'), (2, 'a'), (2, 'c')]);
+/// ```
+pub fn bubble_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    bubble_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
 
-    let n = slice.len();
-    let mut lo = 0;
-    let mut hi = n - 1;
-    let mut pivot = n - 1;
+/// Sorts a slice in descending order with [`bubble`] sort. Equal elements keep
+/// their original order.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::bubble_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn bubble_desc<T: Ord>(slice: &mut [T]) {
+    bubble_by(slice, |a, b| b.cmp(a));
+}
 
-    let mut equal = false;
-    loop {
-        // always increments the counter if they're equal
-        if equal {
-            lo += 1;
-            equal = false;
-        }
+/// Sorts a range of a slice with [`bubble`] sort, leaving the other elements
+/// untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [9, 8, 3, 1, 2, 0];
+/// sort::bubble_range(&mut slice, 1..5);
+/// assert_eq!(slice, [9, 1, 2, 3, 8, 0]);
+/// ```
+pub fn bubble_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    bubble(&mut slice[bounds(range)]);
+}
 
-        // search for an element greater or equal to the pivot
-        while slice[lo] < slice[pivot] {
-            lo += 1;
-        }
+/// An implementation of comb sort.
+///
+/// Works like [`bubble`] sort, but compares elements that are `gap` positions
+/// apart, shrinking the gap by a factor of 1.3 after every pass. This moves
+/// small elements from the end of the slice quickly. Once the gap reaches 1, it
+/// repeats the passes until nothing is swapped.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [8, 4, -1, 9, 0, 3, 3];
+/// sort::comb(&mut slice);
+/// assert_eq!(slice, [-1, 0, 3, 3, 4, 8, 9]);
+/// ```
+pub fn comb<T: Ord>(slice: &mut [T]) {
+    comb_by(slice, T::cmp);
+}
 
-        // search for an element smaller or equal to the pivot
-        while hi > 0 && slice[hi] > slice[pivot] {
-            hi -= 1;
-        }
+/// Sorts a slice with [`comb`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [8, 4, -1, 9, 0, 3, 3];
+/// sort::comb_by(&mut slice, |a: &i32, b| a.abs().cmp(&b.abs()));
+/// assert_eq!(slice, [0, -1, 3, 3, 4, 8, 9]);
+/// ```
+pub fn comb_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut gap = slice.len();
+    let mut swapped = true;
 
-        if lo >= hi {
-            // the slice is sorted
-            break;
-        } else if slice[lo] == slice[hi] {
-            equal = true;
-        } else {
-            if lo == pivot {
-                pivot = hi;
-            } else if hi == pivot {
-                pivot = lo;
-            }
+    while gap > 1 || swapped {
+        // gap / 1.3
+        gap = (gap * 10 / 13).max(1);
+        swapped = false;
 
-            slice.swap(lo, hi);
+        for i in gap..slice.len() {
+            if compare(&slice[i - gap], &slice[i]) == Ordering::Greater {
+                slice.swap(i - gap, i);
+                swapped = true;
+            }
         }
     }
-
-    slice.swap(lo, pivot);
-    lo
 }
 
-/// An implementation of quick sort.
+/// Sorts a slice with [`comb`] sort, using a key extraction function.
 ///
-/// Partitions the slice into two parts by [`quick_partition`], and invokes
-/// itself until the list is sorted.
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [-5, 3, -1, 4, -2];
+/// sort::comb_by_key(&mut slice, |x: &i32| x.abs());
+/// assert_eq!(slice, [-1, -2, 3, 4, -5]);
+/// ```
+pub fn comb_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    comb_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`comb`] sort.
 ///
 /// # Examples
 /// ```
 /// use search_sort::sort;
 ///
-/// let mut slice = [5, 1, -5, 3, 9, 2, 19];
-/// sort::quick(&mut slice);
-/// assert_eq!(slice, [-5, 1, 2, 3, 5, 9, 19]);
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::comb_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
 /// ```
-pub fn quick<T: Ord>(slice: &mut [T]) {
-    if test(slice) {
-        return;
-    }
-    let partition = quick_partition(slice);
-    quick(&mut slice[..partition]);
-    quick(&mut slice[(partition + 1)..]);
+pub fn comb_desc<T: Ord>(slice: &mut [T]) {
+    comb_by(slice, |a, b| b.cmp(a));
 }
 
-/// An implemetation of top-down (recursive) merge sort that uses only
-/// half of the space.
+/// Sorts a range of a slice with [`comb`] sort, leaving the other elements
+/// untouched.
 ///
-/// Invokes itself on the two halves, copies the first half of the slice and
-/// merges it into the original slice.
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
 ///
 /// # Examples
 /// ```
 /// use search_sort::sort;
 ///
-/// let mut slice = [4, -2, 7, 0, 11, -11, -10];
-/// sort::merge(&mut slice);
-/// assert_eq!(slice, [-11, -10, -2, 0, 4, 7, 11]);
+/// let mut slice = [5, 4, 3, 2, 1];
+/// sort::comb_range(&mut slice, 2..);
+/// assert_eq!(slice, [5, 4, 1, 2, 3]);
 /// ```
-pub fn merge<T: Ord + Clone>(slice: &mut [T]) {
-    if test(slice) {
-        return;
+pub fn comb_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    comb(&mut slice[bounds(range)]);
+}
+
+/// One phase of odd-even transposition sort.
+///
+/// Compares and swaps the disjoint pairs `(start, start + 1)`,
+/// `(start + 2, start + 3)`, ... Returns whether anything was swapped.
+fn odd_even_phase<T, F>(slice: &mut [T], start: usize, compare: &mut F) -> bool
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut swapped = false;
+    for pair in slice[start..].chunks_exact_mut(2) {
+        if compare(&pair[0], &pair[1]) == Ordering::Greater {
+            pair.swap(0, 1);
+            swapped = true;
+        }
     }
 
-    let mid = slice.len() / 2;
+    swapped
+}
 
-    // copy first part to a new slice
-    let mut left = Vec::new();
-    left.extend_from_slice(&slice[..mid]);
-    let mut left = &mut left[..];
+/// An implementation of odd-even transposition sort.
+///
+/// A variant of [`bubble`] sort that alternates two phases: it compares every
+/// even element with the next one, and then every odd element with the next
+/// one. The pairs within a phase are disjoint, so they could be compared in
+/// parallel. The slice is sorted after at most `n` phases.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [7, 0, -3, 7, 2, 5];
+/// sort::odd_even(&mut slice);
+/// assert_eq!(slice, [-3, 0, 2, 5, 7, 7]);
+/// ```
+pub fn odd_even<T: Ord>(slice: &mut [T]) {
+    odd_even_by(slice, T::cmp);
+}
 
-    merge(&mut left);
-    merge(&mut slice[mid..]);
+/// Sorts a slice with [`odd_even`] transposition sort, using a comparator
+/// function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = ["pear", "fig", "banana", "kiwi"];
+/// sort::odd_even_by(&mut slice, |a, b| a.len().cmp(&b.len()));
+/// assert_eq!(slice, ["fig", "pear", "kiwi", "banana"]);
+/// ```
+pub fn odd_even_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if slice.len() < 2 {
+        return;
+    }
 
-    // merge the two parts
-    let mut i = 0;
-    let mut j = 0;
     loop {
-        let midj = mid + j;
-
-        if i == mid {
-            // the slice is sorted, as the first part has been merged
+        let even = odd_even_phase(slice, 0, &mut compare);
+        let odd = odd_even_phase(slice, 1, &mut compare);
+        if !even && !odd {
             break;
-        } else if midj == slice.len() {
-            // only the second half has been merged, so clone the remainging
-            // elements
-            slice[(mid + i)..].clone_from_slice(&left[i..]);
-            break;
-        }
-
-        let ij = i + j;
-
-        match left[i].cmp(&slice[midj]) {
-            Ordering::Less => {
-                slice[ij] = left[i].clone();
-                i += 1;
-            }
-            Ordering::Equal => {
-                // insert the two elements one by one, since they are equal
-
-                let e = left[i].clone();
-
-                slice[ij] = e.clone();
-                slice[ij + 1] = e;
-
-                i += 1;
-                j += 1;
-            }
-            Ordering::Greater => {
-                slice[i + j] = slice[midj].clone();
-                j += 1;
-            }
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::bubble;
+/// Sorts a slice with [`odd_even`] transposition sort, using a key extraction
+/// function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = ["apple", "fig", "banana", "kiwi"];
+/// sort::odd_even_by_key(&mut slice, |s| s.len());
+/// assert_eq!(slice, ["fig", "kiwi", "apple", "banana"]);
+/// ```
+pub fn odd_even_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    odd_even_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`odd_even`] transposition sort.
+/// Equal elements keep their original order.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::odd_even_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn odd_even_desc<T: Ord>(slice: &mut [T]) {
+    odd_even_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`odd_even`] transposition sort, leaving the
+/// other elements untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 2, 1, 0, -1];
+/// sort::odd_even_range(&mut slice, ..3);
+/// assert_eq!(slice, [1, 2, 3, 0, -1]);
+/// ```
+pub fn odd_even_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    odd_even(&mut slice[bounds(range)]);
+}
+
+/// An implementation of insertion sort.
+///
+/// Takes the elements one by one and moves each of them back, until the
+/// previous element is not greater than it. It's stable, and fast on small or
+/// nearly sorted slices.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [4, 1, -3, 9, 0, 2];
+/// sort::insertion(&mut slice);
+/// assert_eq!(slice, [-3, 0, 1, 2, 4, 9]);
+/// ```
+pub fn insertion<T: Ord>(slice: &mut [T]) {
+    insertion_by_less(slice, |a, b| a < b);
+}
+
+/// Sorts a slice with [`insertion`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [(2, 'a'), (1, 'b'), (2, 'c'), (0, 'd')];
+/// sort::insertion_by(&mut slice, |a, b| a.0.cmp(&b.0));
+/// assert_eq!(slice, [(0, 'd'), (1, 'b'), (2, 'a'), (2, 'c')]);
+/// ```
+pub fn insertion_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    insertion_by_less(slice, |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts a slice with [`insertion`] sort, using a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [(2, 'a'), (1, 'b'), (2, 'c'), (0, 'd')];
+/// sort::insertion_by_key(&mut slice, |p| p.0);
+/// assert_eq!(slice, [(0, 'd'), (1, 'b'), (2, 'a'), (2, 'c')]);
+/// ```
+pub fn insertion_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    insertion_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`insertion`] sort. Equal elements
+/// keep their original order.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::insertion_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn insertion_desc<T: Ord>(slice: &mut [T]) {
+    insertion_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`insertion`] sort, leaving the other elements
+/// untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [9, 8, 3, 1, 2, 0];
+/// sort::insertion_range(&mut slice, 1..5);
+/// assert_eq!(slice, [9, 1, 2, 3, 8, 0]);
+/// ```
+pub fn insertion_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    insertion(&mut slice[bounds(range)]);
+}
+
+/// Insertion sort with a custom `is_less` function.
+fn insertion_by_less<T, F>(slice: &mut [T], mut is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    for i in 1..slice.len() {
+        let mut j = i;
+        while j > 0 && is_less(&slice[j], &slice[j - 1]) {
+            slice.swap(j - 1, j);
+            j -= 1;
+        }
+    }
+}
+
+/// An implementation of selection sort.
+///
+/// Finds the smallest element of the unsorted part of the slice and swaps it
+/// with the first element of that part. It does `O(n²)` comparisons, but at
+/// most `n - 1` swaps, which is useful when writes are expensive.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, -7, 12, 0, 5];
+/// sort::selection(&mut slice);
+/// assert_eq!(slice, [-7, 0, 3, 5, 12]);
+/// ```
+pub fn selection<T: Ord>(slice: &mut [T]) {
+    selection_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`selection`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, -7, 12, 0, 5];
+/// sort::selection_by(&mut slice, |a, b| b.cmp(a));
+/// assert_eq!(slice, [12, 5, 3, 0, -7]);
+/// ```
+pub fn selection_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for i in 0..slice.len() {
+        let mut min = i;
+        for j in (i + 1)..slice.len() {
+            if compare(&slice[j], &slice[min]) == Ordering::Less {
+                min = j;
+            }
+        }
+
+        if min != i {
+            slice.swap(i, min);
+        }
+    }
+}
+
+/// Sorts a slice with [`selection`] sort, using a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+/// use std::cmp::Reverse;
+///
+/// let mut slice = [1, 6, 3, 2, 8];
+/// sort::selection_by_key(&mut slice, |&x| Reverse(x));
+/// assert_eq!(slice, [8, 6, 3, 2, 1]);
+/// ```
+pub fn selection_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    selection_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`selection`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::selection_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn selection_desc<T: Ord>(slice: &mut [T]) {
+    selection_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`selection`] sort, leaving the other elements
+/// untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [5, 4, 3, 2, 1];
+/// sort::selection_range(&mut slice, 2..);
+/// assert_eq!(slice, [5, 4, 1, 2, 3]);
+/// ```
+pub fn selection_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    selection(&mut slice[bounds(range)]);
+}
+
+/// Reverses the first `k` elements of a slice (a prefix reversal).
+///
+/// This is the only operation used by [`pancake`] sort.
+///
+/// # Panics
+///
+/// Panics if `k` is greater than the length of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [1, 2, 3, 4, 5];
+/// sort::flip(&mut slice, 3);
+/// assert_eq!(slice, [3, 2, 1, 4, 5]);
+/// ```
+pub fn flip<T>(slice: &mut [T], k: usize) {
+    slice[..k].reverse();
+}
+
+/// An implementation of pancake sort.
+///
+/// Sorts the slice using only prefix reversals ([`flip`]): for every unsorted
+/// prefix it flips the largest element to the front, and then flips the whole
+/// prefix to bring it to the end. It does at most `2n - 3` flips.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 6, -2, 1, 0];
+/// sort::pancake(&mut slice);
+/// assert_eq!(slice, [-2, 0, 1, 3, 6]);
+/// ```
+pub fn pancake<T: Ord>(slice: &mut [T]) {
+    pancake_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`pancake`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 6, -2, 1, 0];
+/// sort::pancake_by(&mut slice, |a, b| b.cmp(a));
+/// assert_eq!(slice, [6, 3, 1, 0, -2]);
+/// ```
+pub fn pancake_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for size in (2..=slice.len()).rev() {
+        let mut max = 0;
+        for i in 1..size {
+            if compare(&slice[i], &slice[max]) == Ordering::Greater {
+                max = i;
+            }
+        }
+
+        if max != size - 1 {
+            if max != 0 {
+                flip(slice, max + 1);
+            }
+            flip(slice, size);
+        }
+    }
+}
+
+/// Sorts a slice with [`pancake`] sort, using a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [-5, 3, -1, 4, -2];
+/// sort::pancake_by_key(&mut slice, |x: &i32| x.abs());
+/// assert_eq!(slice, [-1, -2, 3, 4, -5]);
+/// ```
+pub fn pancake_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    pancake_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`pancake`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::pancake_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn pancake_desc<T: Ord>(slice: &mut [T]) {
+    pancake_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`pancake`] sort, leaving the other elements
+/// untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 2, 1, 0, -1];
+/// sort::pancake_range(&mut slice, ..3);
+/// assert_eq!(slice, [1, 2, 3, 0, -1]);
+/// ```
+pub fn pancake_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    pancake(&mut slice[bounds(range)]);
+}
+
+/// A small xorshift pseudo-random number generator, seeded from the standard
+/// library's random hasher state.
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Self {
+        use std::collections::hash_map::RandomState;
+        use std::hash::{BuildHasher, Hasher};
+
+        // the state must not be zero
+        Rng(RandomState::new().build_hasher().finish() | 1)
+    }
+
+    /// Returns a random number in `0..n`.
+    fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % n as u64) as usize
+    }
+}
+
+/// An implementation of bogosort.
+///
+/// Shuffles the slice randomly until it's sorted. The expected number of
+/// shuffles is `n!`, so it's usable only for a handful of elements; it isn't
+/// even guaranteed to terminate. Available only with the `educational`
+/// feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 2, 0];
+/// sort::bogo(&mut slice);
+/// assert_eq!(slice, [0, 1, 2, 3]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn bogo<T: Ord>(slice: &mut [T]) {
+    bogo_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`bogo`] sort, using a comparator function. Available
+/// only with the `educational` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 2, 0];
+/// sort::bogo_by(&mut slice, |a, b| b.cmp(a));
+/// assert_eq!(slice, [3, 2, 1, 0]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn bogo_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut rng = Rng::new();
+    while !test_by(slice, &mut compare) {
+        // Fisher-Yates shuffle
+        for i in (1..slice.len()).rev() {
+            let j = rng.below(i + 1);
+            slice.swap(i, j);
+        }
+    }
+}
+
+/// Sorts a slice with [`bogo`] sort, using a key extraction function. Available
+/// only with the `educational` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+/// use std::cmp::Reverse;
+///
+/// let mut slice = [1, 6, 3, 2, 8];
+/// sort::bogo_by_key(&mut slice, |&x| Reverse(x));
+/// assert_eq!(slice, [8, 6, 3, 2, 1]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn bogo_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    bogo_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`bogo`] sort. Available only with
+/// the `educational` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::bogo_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn bogo_desc<T: Ord>(slice: &mut [T]) {
+    bogo_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`bogo`] sort, leaving the other elements
+/// untouched. Available only with the `educational` feature.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [9, 8, 3, 1, 2, 0];
+/// sort::bogo_range(&mut slice, 1..5);
+/// assert_eq!(slice, [9, 1, 2, 3, 8, 0]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn bogo_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    bogo(&mut slice[bounds(range)]);
+}
+
+/// An implementation of stooge sort.
+///
+/// Swaps the first and last element if they're out of order, then recursively
+/// sorts the first two thirds, the last two thirds and the first two thirds
+/// again. It runs in `O(n^2.71)` time, slower than [`bubble`] sort. Available
+/// only with the `educational` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [5, -2, 8, 0, 3];
+/// sort::stooge(&mut slice);
+/// assert_eq!(slice, [-2, 0, 3, 5, 8]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn stooge<T: Ord>(slice: &mut [T]) {
+    stooge_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`stooge`] sort, using a comparator function. Available
+/// only with the `educational` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [5, -2, 8, 0, 3];
+/// sort::stooge_by(&mut slice, |a, b| b.cmp(a));
+/// assert_eq!(slice, [8, 5, 3, 0, -2]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn stooge_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    fn inner<T, F>(slice: &mut [T], compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let n = slice.len();
+        if n < 2 {
+            return;
+        }
+
+        if compare(&slice[0], &slice[n - 1]) == Ordering::Greater {
+            slice.swap(0, n - 1);
+        }
+
+        if n > 2 {
+            let third = n / 3;
+            inner(&mut slice[..(n - third)], compare);
+            inner(&mut slice[third..], compare);
+            inner(&mut slice[..(n - third)], compare);
+        }
+    }
+
+    inner(slice, &mut compare);
+}
+
+/// Sorts a slice with [`stooge`] sort, using a key extraction function.
+/// Available only with the `educational` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [-5, 3, -1, 4, -2];
+/// sort::stooge_by_key(&mut slice, |x: &i32| x.abs());
+/// assert_eq!(slice, [-1, -2, 3, 4, -5]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn stooge_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    stooge_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`stooge`] sort. Available only with
+/// the `educational` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::stooge_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn stooge_desc<T: Ord>(slice: &mut [T]) {
+    stooge_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`stooge`] sort, leaving the other elements
+/// untouched. Available only with the `educational` feature.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [5, 4, 3, 2, 1];
+/// sort::stooge_range(&mut slice, 2..);
+/// assert_eq!(slice, [5, 4, 1, 2, 3]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn stooge_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    stooge(&mut slice[bounds(range)]);
+}
+
+/// An implementation of slowsort.
+///
+/// A "multiply and surrender" algorithm: recursively sorts both halves, moves
+/// the maximum of them to the end, and then sorts everything except the last
+/// element again. Its running time isn't even polynomial. Available only with
+/// the `educational` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [4, 1, 3, 9, 7];
+/// sort::slow(&mut slice);
+/// assert_eq!(slice, [1, 3, 4, 7, 9]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn slow<T: Ord>(slice: &mut [T]) {
+    slow_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`slow`] sort, using a comparator function. Available
+/// only with the `educational` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [4, 1, 3, 9, 7];
+/// sort::slow_by(&mut slice, |a, b| b.cmp(a));
+/// assert_eq!(slice, [9, 7, 4, 3, 1]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn slow_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    fn inner<T, F>(slice: &mut [T], compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let n = slice.len();
+        if n < 2 {
+            return;
+        }
+
+        let mid = n / 2;
+        inner(&mut slice[..mid], compare);
+        inner(&mut slice[mid..], compare);
+        if compare(&slice[mid - 1], &slice[n - 1]) == Ordering::Greater {
+            slice.swap(mid - 1, n - 1);
+        }
+        inner(&mut slice[..(n - 1)], compare);
+    }
+
+    inner(slice, &mut compare);
+}
+
+/// Sorts a slice with [`slow`] sort, using a key extraction function. Available
+/// only with the `educational` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = ["apple", "fig", "banana", "kiwi"];
+/// sort::slow_by_key(&mut slice, |s| s.len());
+/// assert_eq!(slice, ["fig", "kiwi", "apple", "banana"]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn slow_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    slow_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`slow`] sort. Available only with
+/// the `educational` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::slow_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn slow_desc<T: Ord>(slice: &mut [T]) {
+    slow_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`slow`] sort, leaving the other elements
+/// untouched. Available only with the `educational` feature.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 2, 1, 0, -1];
+/// sort::slow_range(&mut slice, ..3);
+/// assert_eq!(slice, [1, 2, 3, 0, -1]);
+/// ```
+#[cfg(feature = "educational")]
+pub fn slow_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    slow(&mut slice[bounds(range)]);
+}
+
+/// Part of quick sort algorithm.
+///
+/// Sets the pivot, places smaller elements before it and greater after it.
+/// Returns the final position of the pivot, or [`None`] if the slice is empty.
+///
+/// This function is used in [`quick`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [7, 1, 9, 4];
+/// assert_eq!(sort::quick_partition(&mut slice), Some(1));
+/// assert_eq!(slice[1], 4);
+///
+/// assert_eq!(sort::quick_partition::<i32>(&mut []), None);
+/// ```
+pub fn quick_partition<T: Ord>(slice: &mut [T]) -> Option<usize> {
+    quick_partition_by(slice, T::cmp)
+}
+
+/// Like [`quick_partition`], but compares the elements with a comparator
+/// function.
+///
+/// This function is used in [`quick_by`] sort.
+pub fn quick_partition_by<T, F>(slice: &mut [T], mut compare: F) -> Option<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if slice.is_empty() {
+        None
+    } else {
+        Some(partition_last(slice, &mut compare))
+    }
+}
+
+/// Partitions a non-empty slice around its last element, and returns the
+/// final position of it.
+fn partition_last<T, F>(slice: &mut [T], compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    // 'the pivot' is the last element of the slice
+
+    let n = slice.len();
+    let mut lo = 0;
+    let mut hi = n - 1;
+    let mut pivot = n - 1;
+
+    let mut equal = false;
+    loop {
+        // always increments the counter if they're equal
+        if equal {
+            lo += 1;
+            equal = false;
+        }
+
+        // search for an element greater or equal to the pivot
+        while compare(&slice[lo], &slice[pivot]) == Ordering::Less {
+            lo += 1;
+        }
+
+        // search for an element smaller or equal to the pivot
+        while hi > 0 && compare(&slice[hi], &slice[pivot]) == Ordering::Greater {
+            hi -= 1;
+        }
+
+        if lo >= hi {
+            // the slice is sorted
+            break;
+        } else if compare(&slice[lo], &slice[hi]) == Ordering::Equal {
+            equal = true;
+        } else {
+            if lo == pivot {
+                pivot = hi;
+            } else if hi == pivot {
+                pivot = lo;
+            }
+
+            slice.swap(lo, hi);
+        }
+    }
+
+    slice.swap(lo, pivot);
+    lo
+}
+
+/// The strategy of choosing the pivot in [`quick_partition_with`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pivot {
+    /// The last element. It's the default, but makes quick sort quadratic on
+    /// reversed input.
+    Last,
+    /// The first element.
+    First,
+    /// The middle element.
+    Middle,
+    /// The median of the first, middle and last element.
+    MedianOfThree,
+    /// The median of the medians of three triples spread over the slice
+    /// (Tukey's ninther). Good for large slices.
+    Ninther,
+    /// A pseudo-randomly chosen element, which makes the worst case unlikely
+    /// for any input.
+    Random,
+}
+
+/// Returns the position of the median of three elements.
+fn median3<T, F>(slice: &[T], a: usize, b: usize, c: usize, compare: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut less = |i: usize, j: usize| compare(&slice[i], &slice[j]) == Ordering::Less;
+    if less(a, b) {
+        if less(b, c) {
+            b
+        } else if less(a, c) {
+            c
+        } else {
+            a
+        }
+    } else if less(a, c) {
+        a
+    } else if less(b, c) {
+        c
+    } else {
+        b
+    }
+}
+
+/// Returns a function that returns a random number below its argument. The
+/// random number generator is created when it's needed for the first time.
+fn lazy_random() -> impl FnMut(usize) -> usize {
+    let mut rng = None;
+    move |n| rng.get_or_insert_with(Rng::new).below(n)
+}
+
+/// Returns the position of the pivot chosen by the strategy. `random` returns
+/// a random number below its argument.
+fn choose_pivot<T, F, G>(slice: &[T], pivot: Pivot, compare: &mut F, random: &mut G) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+    G: FnMut(usize) -> usize,
+{
+    let n = slice.len();
+    let mid = n / 2;
+    match pivot {
+        Pivot::Last => n - 1,
+        Pivot::First => 0,
+        Pivot::Middle => mid,
+        Pivot::MedianOfThree | Pivot::Ninther if n < 3 => n - 1,
+        Pivot::Ninther if n >= 9 => {
+            let step = n / 8;
+            let a = median3(slice, 0, step, 2 * step, compare);
+            let b = median3(slice, mid - step, mid, mid + step, compare);
+            let c = median3(slice, n - 1 - 2 * step, n - 1 - step, n - 1, compare);
+            median3(slice, a, b, c, compare)
+        }
+        Pivot::MedianOfThree | Pivot::Ninther => median3(slice, 0, mid, n - 1, compare),
+        Pivot::Random => random(n),
+    }
+}
+
+/// Like [`quick_partition`], but chooses the pivot with the given strategy.
+/// Returns the final position of the pivot, or [`None`] if the slice is empty.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort::{self, Pivot};
+///
+/// let mut slice = [1, 2, 3, 4, 5, 6, 7];
+/// let pos = sort::quick_partition_with(&mut slice, Pivot::MedianOfThree);
+/// assert_eq!(pos, Some(3));
+/// assert!(slice[..3].iter().all(|&x| x < 4) && slice[4..].iter().all(|&x| x > 4));
+/// ```
+pub fn quick_partition_with<T: Ord>(slice: &mut [T], pivot: Pivot) -> Option<usize> {
+    let n = slice.len();
+    if n == 0 {
+        return None;
+    }
+
+    let p = choose_pivot(slice, pivot, &mut T::cmp, &mut lazy_random());
+    slice.swap(p, n - 1);
+    Some(partition_last(slice, &mut T::cmp))
+}
+
+/// An implementation of quick sort.
+///
+/// Partitions the slice into two parts by [`quick_partition`], and invokes
+/// itself until the list is sorted. Parts of at most 24 elements are sorted
+/// with [`insertion`] sort instead; see [`quick_with_cutoff`] to change this
+/// limit.
+///
+/// It only recurses into the smaller part, so the stack depth is `O(log n)`.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [5, 1, -5, 3, 9, 2, 19];
+/// sort::quick(&mut slice);
+/// assert_eq!(slice, [-5, 1, 2, 3, 5, 9, 19]);
+/// ```
+pub fn quick<T: Ord>(slice: &mut [T]) {
+    quick_by(slice, T::cmp);
+}
+
+/// The default length of slices, below which [`quick`] and [`merge`] sort use
+/// [`insertion`] sort.
+const INSERTION_CUTOFF: usize = 24;
+
+/// Sorts a slice with [`quick`] sort, switching to [`insertion`] sort for the
+/// parts of at most `cutoff` elements.
+///
+/// With `cutoff` equal to 0 or 1, insertion sort isn't used at all.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: Vec<_> = (0..100).map(|x| (x * 37) % 100).collect();
+/// sort::quick_with_cutoff(&mut slice, 8);
+/// assert!(slice.iter().copied().eq(0..100));
+/// ```
+pub fn quick_with_cutoff<T: Ord>(slice: &mut [T], cutoff: usize) {
+    quick_with(slice, &SortConfig::new().insertion_cutoff(cutoff));
+}
+
+/// Sorts a slice with [`quick`] sort, choosing the pivots with the given
+/// strategy.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort::{self, Pivot};
+///
+/// // the median of three handles sorted and reversed input well
+/// let mut slice: Vec<_> = (0..10_000).rev().collect();
+/// sort::quick_with_pivot(&mut slice, Pivot::MedianOfThree);
+/// assert!(sort::test(&slice));
+/// ```
+pub fn quick_with_pivot<T: Ord>(slice: &mut [T], pivot: Pivot) {
+    quick_with(slice, &SortConfig::new().pivot(pivot));
+}
+
+/// Sorts a slice with [`quick`] sort, choosing the pivots uniformly at random
+/// with the thread-local random number generator of the `rand` crate.
+///
+/// No input can reliably trigger the quadratic worst case. Available only with
+/// the `rand` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [5, 1, -5, 3, 9, 2, 19];
+/// sort::quick_random(&mut slice);
+/// assert_eq!(slice, [-5, 1, 2, 3, 5, 9, 19]);
+/// ```
+#[cfg(feature = "rand")]
+pub fn quick_random<T: Ord>(slice: &mut [T]) {
+    quick_random_with(slice, &mut rand::thread_rng());
+}
+
+/// Like [`quick_random`], but uses the given random number generator. A seeded
+/// generator makes the sort reproducible. Available only with the `rand`
+/// feature.
+///
+/// # Examples
+/// ```
+/// use rand::rngs::StdRng;
+/// use rand::SeedableRng;
+/// use search_sort::sort;
+///
+/// let mut slice = [5, 1, -5, 3, 9, 2, 19];
+/// sort::quick_random_with(&mut slice, &mut StdRng::seed_from_u64(42));
+/// assert_eq!(slice, [-5, 1, 2, 3, 5, 9, 19]);
+/// ```
+#[cfg(feature = "rand")]
+pub fn quick_random_with<T: Ord, R: rand::Rng>(slice: &mut [T], rng: &mut R) {
+    let config = SortConfig::new().pivot(Pivot::Random);
+    let mut random = |n| rng.gen_range(0..n);
+    quick_inner(slice, &mut T::cmp, &config, usize::MAX, &mut random);
+}
+
+/// The length of parts, at or below which the parallel sorts, like
+/// [`par_quick`], sort them sequentially.
+#[cfg(feature = "rayon")]
+const PAR_THRESHOLD: usize = 4096;
+
+/// Sorts a slice with [`quick`] sort in parallel, on the global thread pool of
+/// the `rayon` crate.
+///
+/// After partitioning, the two parts are sorted in parallel by `rayon::join`.
+/// Parts of at most 4096 elements are sorted sequentially with [`intro`] sort;
+/// see [`par_quick_with`] to change this limit.
+/// The pivots are medians of three, and like in [`intro`] sort, too deep
+/// recursion switches to [`heap`] sort, so it works in `O(n log n)` time. It's
+/// not stable. Available only with the `rayon` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: Vec<_> = (0..100_000).map(|x| (x * 7919) % 100_003).collect();
+/// sort::par_quick(&mut slice);
+/// assert!(sort::test(&slice));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_quick<T: Ord + Send>(slice: &mut [T]) {
+    par_quick_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`par_quick`] sort, using a comparator function.
+/// Available only with the `rayon` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: Vec<_> = (0..10_000).collect();
+/// sort::par_quick_by(&mut slice, |a, b| b.cmp(a));
+/// assert!(slice.iter().copied().eq((0..10_000).rev()));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_quick_by<T, F>(slice: &mut [T], compare: F)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    par_quick_with_by(slice, &compare, &SortConfig::new());
+}
+
+/// Sorts a slice with [`par_quick`] sort, tuned by a [`SortConfig`].
+///
+/// Only the parallelism threshold applies. Available only with the `rayon`
+/// feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort::{self, SortConfig};
+///
+/// let mut slice: Vec<_> = (0..10_000).rev().collect();
+/// sort::par_quick_with(&mut slice, &SortConfig::new().par_threshold(256));
+/// assert!(slice.iter().copied().eq(0..10_000));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_quick_with<T: Ord + Send>(slice: &mut [T], config: &SortConfig) {
+    par_quick_with_by(slice, &T::cmp, config);
+}
+
+/// Sorts a slice with [`par_quick_with`] on the given thread pool, instead of
+/// the global one. Available only with the `rayon` feature.
+///
+/// # Examples
+/// ```
+/// use rayon::ThreadPoolBuilder;
+/// use search_sort::sort::{self, SortConfig};
+///
+/// let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+/// let mut slice: Vec<_> = (0..10_000).rev().collect();
+/// sort::par_quick_in(&mut slice, &pool, &SortConfig::new());
+/// assert!(slice.iter().copied().eq(0..10_000));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_quick_in<T>(slice: &mut [T], pool: &rayon::ThreadPool, config: &SortConfig)
+where
+    T: Ord + Send,
+{
+    pool.install(|| par_quick_with(slice, config));
+}
+
+/// The common part of [`par_quick_by`] and [`par_quick_with`].
+#[cfg(feature = "rayon")]
+fn par_quick_with_by<T, F>(slice: &mut [T], compare: &F, config: &SortConfig)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let depth = match slice.len() {
+        0 => 0,
+        n => 2 * (usize::BITS - 1 - n.leading_zeros()) as usize,
+    };
+    par_quick_inner(slice, compare, depth, config.par_threshold);
+}
+
+/// The recursive part of [`par_quick_by`].
+#[cfg(feature = "rayon")]
+fn par_quick_inner<T, F>(slice: &mut [T], compare: &F, depth: usize, threshold: usize)
+where
+    T: Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if slice.len() <= threshold {
+        intro_by(slice, compare);
+        return;
+    } else if depth == 0 {
+        heap_by_less(slice, |a, b| compare(a, b) == Ordering::Less);
+        return;
+    }
+
+    let n = slice.len();
+    let mut compare_mut = |a: &T, b: &T| compare(a, b);
+    let pivot = choose_pivot(
+        slice,
+        Pivot::MedianOfThree,
+        &mut compare_mut,
+        &mut lazy_random(),
+    );
+    slice.swap(pivot, n - 1);
+    let partition = partition_last(slice, &mut compare_mut);
+    let (left, right) = slice.split_at_mut(partition);
+    let right = &mut right[1..];
+    rayon::join(
+        || par_quick_inner(left, compare, depth - 1, threshold),
+        || par_quick_inner(right, compare, depth - 1, threshold),
+    );
+}
+
+/// Tuning parameters of the sorts, for the `_with` functions like
+/// [`quick_with`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort::{self, SortConfig};
+///
+/// let config = SortConfig::new().insertion_cutoff(8).max_depth(32);
+/// let mut slice = [5, 1, -5, 3, 9, 2, 19];
+/// sort::quick_with(&mut slice, &config);
+/// assert_eq!(slice, [-5, 1, 2, 3, 5, 9, 19]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SortConfig {
+    insertion_cutoff: usize,
+    max_depth: Option<usize>,
+    pivot: Pivot,
+    #[cfg(feature = "rayon")]
+    par_threshold: usize,
+}
+
+impl SortConfig {
+    /// Creates the default configuration, which matches the behavior of the
+    /// plain sorts, like [`quick`].
+    pub fn new() -> Self {
+        SortConfig {
+            insertion_cutoff: INSERTION_CUTOFF,
+            max_depth: None,
+            pivot: Pivot::Last,
+            #[cfg(feature = "rayon")]
+            par_threshold: PAR_THRESHOLD,
+        }
+    }
+
+    /// Sets the length of slices, at or below which [`insertion`] sort is used.
+    /// The default is 24.
+    pub fn insertion_cutoff(mut self, cutoff: usize) -> Self {
+        self.insertion_cutoff = cutoff;
+        self
+    }
+
+    /// Limits the recursion depth of [`quick_with`]: the parts that are still
+    /// unsorted at this depth are sorted with [`heap`] sort, like in [`intro`]
+    /// sort. It's unlimited by default.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Sets the pivot strategy of [`quick_with`]. The default is
+    /// [`Pivot::Last`].
+    pub fn pivot(mut self, pivot: Pivot) -> Self {
+        self.pivot = pivot;
+        self
+    }
+
+    /// Sets the length of parts, at or below which the parallel sorts, like
+    /// [`par_quick_with`], work sequentially. The default is 4096, and values
+    /// below 2 are treated as 2. Available only with the `rayon` feature.
+    #[cfg(feature = "rayon")]
+    pub fn par_threshold(mut self, threshold: usize) -> Self {
+        self.par_threshold = threshold.max(2);
+        self
+    }
+}
+
+impl Default for SortConfig {
+    fn default() -> Self {
+        SortConfig::new()
+    }
+}
+
+/// Sorts a slice with [`quick`] sort, tuned by a [`SortConfig`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort::{self, SortConfig};
+///
+/// // a depth limit guarantees O(n log n) time even for adversarial input
+/// let mut slice: Vec<_> = (0..1000).collect();
+/// sort::quick_with(&mut slice, &SortConfig::new().max_depth(20));
+/// assert!(sort::test(&slice));
+/// ```
+pub fn quick_with<T: Ord>(slice: &mut [T], config: &SortConfig) {
+    let depth = config.max_depth.unwrap_or(usize::MAX);
+    quick_inner(slice, &mut T::cmp, config, depth, &mut lazy_random());
+}
+
+/// Sorts a slice with [`quick`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [5, 1, -5, 3, 9, 2, 19];
+/// sort::quick_by(&mut slice, |a, b| b.cmp(a));
+/// assert_eq!(slice, [19, 9, 5, 3, 2, 1, -5]);
+/// ```
+pub fn quick_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    quick_inner(
+        slice,
+        &mut compare,
+        &SortConfig::new(),
+        usize::MAX,
+        &mut lazy_random(),
+    );
+}
+
+/// The recursive part of [`quick_by`]. Sorts the slice with [`heap`] sort
+/// once `depth` reaches 0.
+///
+/// It recurses only into the smaller part and loops on the larger one, so the
+/// stack depth is `O(log n)` even in the worst case.
+fn quick_inner<T, F, G>(
+    mut slice: &mut [T],
+    compare: &mut F,
+    config: &SortConfig,
+    mut depth: usize,
+    random: &mut G,
+) where
+    F: FnMut(&T, &T) -> Ordering,
+    G: FnMut(usize) -> usize,
+{
+    loop {
+        if slice.len() <= config.insertion_cutoff {
+            insertion_by_less(slice, |a, b| compare(a, b) == Ordering::Less);
+            return;
+        } else if test_by(slice, compare) {
+            return;
+        } else if depth == 0 {
+            heap_by_less(slice, |a, b| compare(a, b) == Ordering::Less);
+            return;
+        }
+        depth -= 1;
+
+        let n = slice.len();
+        let pivot = choose_pivot(slice, config.pivot, compare, random);
+        slice.swap(pivot, n - 1);
+        let partition = partition_last(slice, compare);
+        let (left, right) = slice.split_at_mut(partition);
+        let right = &mut right[1..];
+        if left.len() < right.len() {
+            quick_inner(left, compare, config, depth, random);
+            slice = right;
+        } else {
+            quick_inner(right, compare, config, depth, random);
+            slice = left;
+        }
+    }
+}
+
+/// Sorts a slice with [`quick`] sort, using a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = ["apple", "fig", "banana", "kiwi"];
+/// sort::quick_by_key(&mut slice, |s| s.len());
+/// assert_eq!(slice, ["fig", "kiwi", "apple", "banana"]);
+/// ```
+pub fn quick_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    quick_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`quick`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::quick_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn quick_desc<T: Ord>(slice: &mut [T]) {
+    quick_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`quick`] sort, leaving the other elements
+/// untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [9, 8, 3, 1, 2, 0];
+/// sort::quick_range(&mut slice, 1..5);
+/// assert_eq!(slice, [9, 1, 2, 3, 8, 0]);
+/// ```
+pub fn quick_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    quick(&mut slice[bounds(range)]);
+}
+
+/// Adapts a fallible comparator to the infallible sorts.
+///
+/// The first error is stored in `error`; after that, `compare` isn't called
+/// anymore and all the elements compare as `fallback`, so the sort finishes
+/// quickly without reordering anything else.
+fn fallible<'a, T, E, F>(
+    mut compare: F,
+    error: &'a mut Option<E>,
+    fallback: Ordering,
+) -> impl FnMut(&T, &T) -> Ordering + 'a
+where
+    F: FnMut(&T, &T) -> Result<Ordering, E> + 'a,
+{
+    move |a, b| {
+        if error.is_some() {
+            return fallback;
+        }
+
+        compare(a, b).unwrap_or_else(|e| {
+            *error = Some(e);
+            fallback
+        })
+    }
+}
+
+/// Sorts a slice with [`quick`] sort, using a fallible comparator function.
+///
+/// Stops as soon as `compare` returns an error, and returns it. The slice is
+/// then left in an unspecified order, but still contains all of its elements.
+///
+/// # Errors
+///
+/// Returns the first error returned by `compare`.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// use std::cmp::Ordering;
+/// use std::num::ParseIntError;
+///
+/// fn numerically(a: &&str, b: &&str) -> Result<Ordering, ParseIntError> {
+///     Ok(a.parse::<i32>()?.cmp(&b.parse()?))
+/// }
+///
+/// let mut slice = ["10", "2", "33"];
+/// assert!(sort::try_quick_by(&mut slice, numerically).is_ok());
+/// assert_eq!(slice, ["2", "10", "33"]);
+///
+/// let mut slice = ["10", "x", "33"];
+/// assert!(sort::try_quick_by(&mut slice, numerically).is_err());
+/// ```
+pub fn try_quick_by<T, E, F>(slice: &mut [T], compare: F) -> Result<(), E>
+where
+    F: FnMut(&T, &T) -> Result<Ordering, E>,
+{
+    let mut error = None;
+    // with all the elements equal, partitioning just scans the slice
+    quick_by(slice, fallible(compare, &mut error, Ordering::Equal));
+    error.map_or(Ok(()), Err)
+}
+
+/// The length of slices, below which [`intro`] sort uses [`insertion`] sort.
+const INTRO_INSERTION_LEN: usize = 16;
+
+/// An implementation of introsort.
+///
+/// Works like [`quick`] sort with the median of the first, middle and last
+/// element as the pivot, but tracks the recursion depth. Once it exceeds
+/// `2 * log2(n)`, the current part is sorted with [`heap`] sort instead, so the
+/// worst case is `O(n log n)`. Parts shorter than 16 elements are sorted with
+/// [`insertion`] sort.
+///
+/// It only recurses into the smaller part, so the stack depth is `O(log n)`.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 14, -1, 5, 9, 2, 6, 5, 3];
+/// sort::intro(&mut slice);
+/// assert_eq!(slice, [-1, 2, 3, 3, 5, 5, 6, 9, 14]);
+/// ```
+pub fn intro<T: Ord>(slice: &mut [T]) {
+    intro_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`intro`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 14, -1, 5, 9, 2, 6, 5, 3];
+/// sort::intro_by(&mut slice, |a, b| b.cmp(a));
+/// assert_eq!(slice, [14, 9, 6, 5, 5, 3, 3, 2, -1]);
+/// ```
+pub fn intro_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    fn inner<T, F>(mut slice: &mut [T], mut depth: usize, compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        loop {
+            let n = slice.len();
+            if n <= INTRO_INSERTION_LEN {
+                insertion_by_less(slice, |a, b| compare(a, b) == Ordering::Less);
+                return;
+            } else if depth == 0 {
+                heap_by_less(slice, |a, b| compare(a, b) == Ordering::Less);
+                return;
+            }
+            depth -= 1;
+
+            // move the median of three to the end, where quick_partition
+            // expects the pivot
+            let mid = n / 2;
+            if compare(&slice[mid], &slice[0]) == Ordering::Less {
+                slice.swap(mid, 0);
+            }
+            if compare(&slice[n - 1], &slice[0]) == Ordering::Less {
+                slice.swap(n - 1, 0);
+            }
+            if compare(&slice[mid], &slice[n - 1]) == Ordering::Less {
+                slice.swap(mid, n - 1);
+            }
+
+            let partition = partition_last(slice, compare);
+            let (left, right) = slice.split_at_mut(partition);
+            let right = &mut right[1..];
+            if left.len() < right.len() {
+                inner(left, depth, compare);
+                slice = right;
+            } else {
+                inner(right, depth, compare);
+                slice = left;
+            }
+        }
+    }
+
+    let depth = match slice.len() {
+        0 => 0,
+        n => 2 * (usize::BITS - 1 - n.leading_zeros()) as usize,
+    };
+    inner(slice, depth, &mut compare);
+}
+
+/// Sorts a slice with [`intro`] sort, using a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [-5, 3, -1, 4, -2];
+/// sort::intro_by_key(&mut slice, |x: &i32| x.abs());
+/// assert_eq!(slice, [-1, -2, 3, 4, -5]);
+/// ```
+pub fn intro_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    intro_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`intro`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::intro_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn intro_desc<T: Ord>(slice: &mut [T]) {
+    intro_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`intro`] sort, leaving the other elements
+/// untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [5, 4, 3, 2, 1];
+/// sort::intro_range(&mut slice, 2..);
+/// assert_eq!(slice, [5, 4, 1, 2, 3]);
+/// ```
+pub fn intro_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    intro(&mut slice[bounds(range)]);
+}
+
+/// The length of slices, below which [`sample`] sort uses [`intro`] sort.
+const SAMPLE_MIN_LEN: usize = 1024;
+
+/// The number of sampled elements per bucket in [`sample`] sort.
+const SAMPLE_OVERSAMPLING: usize = 8;
+
+/// An implementation of sample sort.
+///
+/// Takes evenly spaced elements of the slice as a sample, sorts it, and picks
+/// every 8th of them as splitters. The elements are then distributed into the
+/// buckets between the splitters, so that every bucket can be sorted
+/// independently, recursively. Elements equal to a splitter get their own
+/// buckets, which are not sorted at all, so many duplicates don't slow it
+/// down. Slices shorter than 1024 elements are sorted with [`intro`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: Vec<_> = (0..5000).map(|x| (x * 7919) % 5003).collect();
+/// sort::sample(&mut slice);
+/// assert!(sort::test(&slice));
+/// ```
+pub fn sample<T: Ord + Clone>(slice: &mut [T]) {
+    sample_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`sample`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: Vec<_> = (0..5000).map(|x| (x * 7919) % 5003).collect();
+/// sort::sample_by(&mut slice, |a, b| b.cmp(a));
+/// assert!(slice.windows(2).all(|w| w[0] >= w[1]));
+/// ```
+pub fn sample_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    fn inner<T, F>(slice: &mut [T], compare: &mut F)
+    where
+        T: Clone,
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        let n = slice.len();
+        if n < SAMPLE_MIN_LEN {
+            intro_by(slice, &mut *compare);
+            return;
+        }
+
+        let buckets = (n / 64).clamp(2, 256);
+        let step = n / (buckets * SAMPLE_OVERSAMPLING);
+        let mut samples: Vec<T> = slice.iter().step_by(step).cloned().collect();
+        intro_by(&mut samples, &mut *compare);
+        let splitters: Vec<T> = samples
+            .into_iter()
+            .skip(SAMPLE_OVERSAMPLING)
+            .step_by(SAMPLE_OVERSAMPLING)
+            .take(buckets - 1)
+            .collect();
+
+        // the bucket 2 * i contains the elements between the splitters i - 1
+        // and i, and the bucket 2 * i + 1 the elements equal to the splitter i
+        let bucket = |v: &T| {
+            let i = splitters.partition_point(|s| compare(s, v) == Ordering::Less);
+            if i < splitters.len() && compare(&splitters[i], v) == Ordering::Equal {
+                2 * i + 1
+            } else {
+                2 * i
+            }
+        };
+        let counts = distribute(slice, bucket, 2 * splitters.len() + 1);
+
+        let mut start = 0;
+        for (i, count) in counts.into_iter().enumerate() {
+            if i % 2 == 0 && count > 1 {
+                inner(&mut slice[start..(start + count)], compare);
+            }
+            start += count;
+        }
+    }
+
+    inner(slice, &mut compare);
+}
+
+/// Sorts a slice with [`sample`] sort, using a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+/// use std::cmp::Reverse;
+///
+/// let mut slice = [1, 6, 3, 2, 8];
+/// sort::sample_by_key(&mut slice, |&x| Reverse(x));
+/// assert_eq!(slice, [8, 6, 3, 2, 1]);
+/// ```
+pub fn sample_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    T: Clone,
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    sample_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`sample`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::sample_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn sample_desc<T: Ord + Clone>(slice: &mut [T]) {
+    sample_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`sample`] sort, leaving the other elements
+/// untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 2, 1, 0, -1];
+/// sort::sample_range(&mut slice, ..3);
+/// assert_eq!(slice, [1, 2, 3, 0, -1]);
+/// ```
+pub fn sample_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord + Clone,
+    R: RangeBounds<usize>,
+{
+    sample(&mut slice[bounds(range)]);
+}
+
+/// An implementation of merge-insertion (Ford–Johnson) sort.
+///
+/// Compares the elements in pairs and sorts the larger elements of the pairs
+/// recursively. The smaller elements are then inserted into this chain by
+/// binary search, in an order based on the Jacobsthal numbers, so that every
+/// binary search is done on a range of length `2^k - 1`.
+///
+/// It does fewer comparisons than any other algorithm in this crate, close to
+/// the theoretical minimum of `log2(n!)`, so it's the best choice when
+/// comparisons are very expensive. Otherwise it's slow, since it does
+/// `O(n²)` other operations. It's not stable.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [21, 1, 13, 2, 8, 3, 5];
+/// sort::merge_insertion(&mut slice);
+/// assert_eq!(slice, [1, 2, 3, 5, 8, 13, 21]);
+/// ```
+pub fn merge_insertion<T: Ord>(slice: &mut [T]) {
+    merge_insertion_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`merge_insertion`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut comparisons = 0;
+/// let mut slice = [21, 1, 13, 2, 8, 3, 5];
+/// sort::merge_insertion_by(&mut slice, |a, b| {
+///     comparisons += 1;
+///     a.cmp(b)
+/// });
+/// assert_eq!(slice, [1, 2, 3, 5, 8, 13, 21]);
+/// assert!(comparisons <= 13);
+/// ```
+pub fn merge_insertion_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    /// Sorts the positions of `items` in the slice.
+    fn inner<T, F>(slice: &[T], items: Vec<usize>, compare: &mut F) -> Vec<usize>
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if items.len() < 2 {
+            return items;
+        }
+
+        // the larger element of every pair, and the smaller one
+        let mut partner = std::collections::HashMap::new();
+        let mut larger = Vec::with_capacity(items.len() / 2);
+        for pair in items.chunks_exact(2) {
+            let (small, big) = if compare(&slice[pair[1]], &slice[pair[0]]) == Ordering::Less {
+                (pair[1], pair[0])
+            } else {
+                (pair[0], pair[1])
+            };
+            partner.insert(big, small);
+            larger.push(big);
+        }
+        let straggler = if items.len() % 2 == 1 {
+            items.last().copied()
+        } else {
+            None
+        };
+
+        let larger = inner(slice, larger, compare);
+        let mut chain = Vec::with_capacity(items.len());
+        chain.push(partner[&larger[0]]);
+        chain.extend_from_slice(&larger);
+
+        // the elements to insert with the larger elements that bound them
+        let mut pending: Vec<(usize, Option<usize>)> =
+            larger[1..].iter().map(|b| (partner[b], Some(*b))).collect();
+        if let Some(s) = straggler {
+            pending.push((s, None));
+        }
+
+        // insert the groups of pending elements ending at the Jacobsthal
+        // numbers, each group from its end
+        let (mut prev, mut curr) = (1, 1);
+        let mut done = 0;
+        while done < pending.len() {
+            let next = curr + 2 * prev;
+            prev = curr;
+            curr = next;
+
+            let end = (curr - 1).min(pending.len());
+            for &(x, bound) in pending[done..end].iter().rev() {
+                let hi = match bound {
+                    Some(b) => chain.iter().position(|&c| c == b).unwrap(),
+                    None => chain.len(),
+                };
+                let (mut lo, mut hi) = (0, hi);
+                while lo < hi {
+                    let mid = (lo + hi) / 2;
+                    if compare(&slice[x], &slice[chain[mid]]) == Ordering::Less {
+                        hi = mid;
+                    } else {
+                        lo = mid + 1;
+                    }
+                }
+                chain.insert(lo, x);
+            }
+            done = end;
+        }
+
+        chain
+    }
+
+    let order = inner(slice, (0..slice.len()).collect(), &mut compare);
+    let mut dest = vec![0; slice.len()];
+    for (i, &from) in order.iter().enumerate() {
+        dest[from] = i;
+    }
+    move_to(slice, dest);
+}
+
+/// Sorts a slice with [`merge_insertion`] sort, using a key extraction
+/// function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = ["apple", "fig", "banana", "kiwi"];
+/// sort::merge_insertion_by_key(&mut slice, |s| s.len());
+/// assert_eq!(slice, ["fig", "kiwi", "apple", "banana"]);
+/// ```
+pub fn merge_insertion_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    merge_insertion_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`merge_insertion`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::merge_insertion_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn merge_insertion_desc<T: Ord>(slice: &mut [T]) {
+    merge_insertion_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`merge_insertion`] sort, leaving the other
+/// elements untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [9, 8, 3, 1, 2, 0];
+/// sort::merge_insertion_range(&mut slice, 1..5);
+/// assert_eq!(slice, [9, 1, 2, 3, 8, 0]);
+/// ```
+pub fn merge_insertion_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    merge_insertion(&mut slice[bounds(range)]);
+}
+
+/// An implemetation of top-down (recursive) merge sort that uses only
+/// half of the space.
+///
+/// Invokes itself on the two halves, copies the first half of the slice and
+/// merges it into the original slice. When one half wins many consecutive
+/// comparisons, the merge gallops to find the whole block at once, so merging
+/// pre-partitioned data takes only `O(log n)` comparisons per block. Parts of
+/// at most 24 elements are sorted with [`insertion`] sort instead; see
+/// [`merge_with_cutoff`] to change this limit.
+///
+/// The sort is stable: equal elements keep their relative order, so sorting
+/// by the less significant keys first and then by the more significant ones
+/// sorts by all of them. This holds for all the `merge_*` variants.
+///
+/// If the comparison or cloning panics, the slice still contains each of its
+/// original elements exactly once, in an unspecified order.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [4, -2, 7, 0, 11, -11, -10];
+/// sort::merge(&mut slice);
+/// assert_eq!(slice, [-11, -10, -2, 0, 4, 7, 11]);
+/// ```
+pub fn merge<T: Ord + Clone>(slice: &mut [T]) {
+    merge_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`merge`] sort, switching to [`insertion`] sort for the
+/// parts of at most `cutoff` elements.
+///
+/// With `cutoff` equal to 0 or 1, insertion sort isn't used at all.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: Vec<_> = (0..100).map(|x| (x * 37) % 100).collect();
+/// sort::merge_with_cutoff(&mut slice, 64);
+/// assert!(slice.iter().copied().eq(0..100));
+/// ```
+pub fn merge_with_cutoff<T: Ord + Clone>(slice: &mut [T], cutoff: usize) {
+    merge_inner(slice, &mut T::cmp, cutoff, &mut Vec::new());
+}
+
+/// Sorts a slice with [`merge`] sort, tuned by a [`SortConfig`].
+///
+/// Only the insertion cutoff applies; the recursion depth of merge sort is
+/// always `O(log n)`.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort::{self, SortConfig};
+///
+/// let mut slice = [4, -2, 7, 0, 11, -11, -10];
+/// sort::merge_with(&mut slice, &SortConfig::new().insertion_cutoff(0));
+/// assert_eq!(slice, [-11, -10, -2, 0, 4, 7, 11]);
+/// ```
+pub fn merge_with<T: Ord + Clone>(slice: &mut [T], config: &SortConfig) {
+    merge_inner(slice, &mut T::cmp, config.insertion_cutoff, &mut Vec::new());
+}
+
+/// Sorts a slice with [`merge`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [4, -2, 7, 0, 11, -11, -10];
+/// sort::merge_by(&mut slice, |a, b| b.cmp(a));
+/// assert_eq!(slice, [11, 7, 4, 0, -2, -10, -11]);
+/// ```
+pub fn merge_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    merge_inner(slice, &mut compare, INSERTION_CUTOFF, &mut Vec::new());
+}
+
+/// Sorts a slice with [`merge`] sort in parallel, on the global thread pool of
+/// the `rayon` crate.
+///
+/// The two halves are sorted in parallel, and then merged in parallel: the
+/// middle element of the longer run is found in the other one by binary
+/// search, which splits the merge into two independent ones. Parts of at most
+/// 4096 elements are sorted and merged sequentially; see [`par_merge_with`] to
+/// change this limit. Unlike [`par_quick`], it's stable, but it needs a buffer
+/// of the same length as the slice. Available only with the `rayon` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: Vec<_> = (0..100_000).map(|x| (x * 7919) % 100_003).collect();
+/// sort::par_merge(&mut slice);
+/// assert!(sort::test(&slice));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_merge<T: Ord + Clone + Send + Sync>(slice: &mut [T]) {
+    par_merge_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`par_merge`] sort, using a comparator function.
+/// Available only with the `rayon` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: Vec<_> = (0..10_000).map(|x| (x % 10, x)).collect();
+/// sort::par_merge_by(&mut slice, |a, b| a.0.cmp(&b.0));
+/// assert!(slice.windows(2).all(|w| w[0].0 < w[1].0 || w[0].1 < w[1].1));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_merge_by<T, F>(slice: &mut [T], compare: F)
+where
+    T: Clone + Send + Sync,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    par_merge_with_by(slice, &compare, &SortConfig::new());
+}
+
+/// Sorts a slice with [`par_merge`] sort, tuned by a [`SortConfig`].
+///
+/// Only the parallelism threshold applies. Available only with the `rayon`
+/// feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort::{self, SortConfig};
+///
+/// let mut slice: Vec<_> = (0..10_000).rev().collect();
+/// sort::par_merge_with(&mut slice, &SortConfig::new().par_threshold(256));
+/// assert!(slice.iter().copied().eq(0..10_000));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_merge_with<T>(slice: &mut [T], config: &SortConfig)
+where
+    T: Ord + Clone + Send + Sync,
+{
+    par_merge_with_by(slice, &T::cmp, config);
+}
+
+/// Sorts a slice with [`par_merge_with`] on the given thread pool, instead of
+/// the global one. Available only with the `rayon` feature.
+///
+/// # Examples
+/// ```
+/// use rayon::ThreadPoolBuilder;
+/// use search_sort::sort::{self, SortConfig};
+///
+/// let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+/// let mut slice: Vec<_> = (0..10_000).rev().collect();
+/// sort::par_merge_in(&mut slice, &pool, &SortConfig::new());
+/// assert!(slice.iter().copied().eq(0..10_000));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_merge_in<T>(slice: &mut [T], pool: &rayon::ThreadPool, config: &SortConfig)
+where
+    T: Ord + Clone + Send + Sync,
+{
+    pool.install(|| par_merge_with(slice, config));
+}
+
+/// The common part of [`par_merge_by`] and [`par_merge_with`].
+#[cfg(feature = "rayon")]
+fn par_merge_with_by<T, F>(slice: &mut [T], compare: &F, config: &SortConfig)
+where
+    T: Clone + Send + Sync,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    let mut buf = slice.to_vec();
+    par_merge_inner(slice, &mut buf, compare, config.par_threshold);
+}
+
+/// The recursive part of [`par_merge_by`]. `buf` has the same length as
+/// `slice`.
+#[cfg(feature = "rayon")]
+fn par_merge_inner<T, F>(slice: &mut [T], buf: &mut [T], compare: &F, threshold: usize)
+where
+    T: Clone + Send + Sync,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if slice.len() <= threshold {
+        merge_by(slice, compare);
+        return;
+    }
+
+    let mid = slice.len() / 2;
+    let (left, right) = slice.split_at_mut(mid);
+    let (left_buf, right_buf) = buf.split_at_mut(mid);
+    rayon::join(
+        || {
+            par_merge_inner(left, left_buf, compare, threshold);
+            left_buf.clone_from_slice(left);
+        },
+        || {
+            par_merge_inner(right, right_buf, compare, threshold);
+            right_buf.clone_from_slice(right);
+        },
+    );
+
+    let (left, right) = buf.split_at(mid);
+    par_merge_into(left, right, slice, compare, threshold);
+}
+
+/// Merges the sorted `left` and `right` into `dest` in a stable way, in
+/// parallel. `dest` has the length of both of them together.
+#[cfg(feature = "rayon")]
+fn par_merge_into<T, F>(left: &[T], right: &[T], dest: &mut [T], compare: &F, threshold: usize)
+where
+    T: Clone + Send + Sync,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    if dest.len() <= threshold {
+        let (mut i, mut j) = (0, 0);
+        for d in dest.iter_mut() {
+            if j == right.len()
+                || (i < left.len() && compare(&right[j], &left[i]) != Ordering::Less)
+            {
+                *d = left[i].clone();
+                i += 1;
+            } else {
+                *d = right[j].clone();
+                j += 1;
+            }
+        }
+        return;
+    }
+
+    // the elements equal to the splitting one stay on the side of their run,
+    // which keeps the merge stable
+    let (i, j) = if left.len() >= right.len() {
+        let i = left.len() / 2;
+        let j = right.partition_point(|x| compare(x, &left[i]) == Ordering::Less);
+        (i, j)
+    } else {
+        let j = right.len() / 2;
+        let i = left.partition_point(|x| compare(x, &right[j]) != Ordering::Greater);
+        (i, j)
+    };
+
+    let (dest_left, dest_right) = dest.split_at_mut(i + j);
+    rayon::join(
+        || par_merge_into(&left[..i], &right[..j], dest_left, compare, threshold),
+        || par_merge_into(&left[i..], &right[j..], dest_right, compare, threshold),
+    );
+}
+
+/// Sorts a slice with [`merge`] sort, using `buf` as the scratch space.
+///
+/// The buffer is cleared before use, and grows to at most half the length of
+/// the slice. Reusing it across calls avoids allocating on every sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut buf = Vec::new();
+/// for mut slice in vec![vec![3, 1, 2], vec![9, -4, 0, 7]] {
+///     sort::merge_with_buf(&mut slice, &mut buf);
+///     assert!(slice.windows(2).all(|w| w[0] <= w[1]));
+/// }
+/// ```
+pub fn merge_with_buf<T: Ord + Clone>(slice: &mut [T], buf: &mut Vec<T>) {
+    merge_inner(slice, &mut T::cmp, INSERTION_CUTOFF, buf);
+}
+
+/// Merges two sorted slices, appending the result to `out`.
+///
+/// It's stable: equal elements come in their order in `a`, followed by their
+/// order in `b`. If the slices aren't sorted, the result is unspecified.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut out = vec![0];
+/// sort::merge_into(&[1, 3, 5], &[2, 3, 4], &mut out);
+/// assert_eq!(out, [0, 1, 2, 3, 3, 4, 5]);
+/// ```
+pub fn merge_into<T: Ord + Clone>(a: &[T], b: &[T], out: &mut Vec<T>) {
+    merge_into_by(a, b, out, T::cmp);
+}
+
+/// Like [`merge_into`], but compares the elements with a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut out = Vec::new();
+/// sort::merge_into_by(&[5, 3, 1], &[4, 2], &mut out, |a, b| b.cmp(a));
+/// assert_eq!(out, [5, 4, 3, 2, 1]);
+/// ```
+pub fn merge_into_by<T, F>(a: &[T], b: &[T], out: &mut Vec<T>, mut compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    out.reserve(a.len() + b.len());
+
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if compare(&b[j], &a[i]) == Ordering::Less {
+            out.push(b[j].clone());
+            j += 1;
+        } else {
+            out.push(a[i].clone());
+            i += 1;
+        }
+    }
+
+    out.extend_from_slice(&a[i..]);
+    out.extend_from_slice(&b[j..]);
+}
+
+/// Merges two sorted slices into a new vector, like [`merge_into`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert_eq!(sort::merged(&[1, 4], &[2, 3, 5]), [1, 2, 3, 4, 5]);
+/// ```
+pub fn merged<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut out = Vec::new();
+    merge_into(a, b, &mut out);
+    out
+}
+
+/// Returns the sorted union of two sorted slices.
+///
+/// The slices are merged in linear time. Every element of `a` equal to an
+/// element of `b` is paired with it and kept once, so duplicates are treated
+/// like in multisets: an element occurring `m` times in `a` and `n` times in
+/// `b` occurs `max(m, n)` times in the union. The same holds for the other
+/// set operations: [`intersection`], [`difference`] and
+/// [`symmetric_difference`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert_eq!(sort::union(&[1, 3, 5], &[2, 3, 4]), [1, 2, 3, 4, 5]);
+/// assert_eq!(sort::union(&[1, 1], &[1]), [1, 1]);
+/// ```
+pub fn union<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    union_iter(a.iter().cloned(), b.iter().cloned()).collect()
+}
+
+/// Returns the sorted intersection of two sorted slices, like [`union`].
+///
+/// An element occurring `m` times in `a` and `n` times in `b` occurs
+/// `min(m, n)` times in the intersection.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert_eq!(sort::intersection(&[1, 3, 5], &[2, 3, 5]), [3, 5]);
+/// ```
+pub fn intersection<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    intersect_iter(a.iter().cloned(), b.iter().cloned()).collect()
+}
+
+/// Returns the elements of the sorted slice `a` that aren't in the sorted
+/// slice `b`, like [`union`].
+///
+/// An element occurring `m` times in `a` and `n` times in `b` occurs
+/// `m - n` times in the difference, if `m > n`.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert_eq!(sort::difference(&[1, 3, 5], &[2, 3, 4]), [1, 5]);
+/// ```
+pub fn difference<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    difference_iter(a.iter().cloned(), b.iter().cloned()).collect()
+}
+
+/// Returns the elements that are in only one of two sorted slices, sorted,
+/// like [`union`].
+///
+/// An element occurring `m` times in `a` and `n` times in `b` occurs
+/// `|m - n|` times in the symmetric difference.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert_eq!(sort::symmetric_difference(&[1, 3, 5], &[2, 3, 4]), [1, 2, 4, 5]);
+/// ```
+pub fn symmetric_difference<T: Ord + Clone>(a: &[T], b: &[T]) -> Vec<T> {
+    symmetric_difference_iter(a.iter().cloned(), b.iter().cloned()).collect()
+}
+
+/// Returns the positions of the matching elements of two sorted slices, as
+/// pairs of a position in `a` and a position in `b`.
+///
+/// The elements are paired like in [`intersection`], so the pairs are
+/// ordered by both positions, and every position occurs at most once.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let ids = [2, 3, 5, 8];
+/// let other_ids = [1, 3, 4, 5, 9];
+/// assert_eq!(sort::intersect_indices(&ids, &other_ids), [(1, 1), (2, 3)]);
+/// ```
+pub fn intersect_indices<T: Ord>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                pairs.push((i, j));
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+
+    pairs
+}
+
+/// Sorts a slice with [`merge`] sort, using a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [2, -5, 3, -1, 1, -2];
+/// sort::merge_by_key(&mut slice, |x: &i32| x.abs());
+/// assert_eq!(slice, [-1, 1, 2, -2, 3, -5]);
+/// ```
+pub fn merge_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    T: Clone,
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    merge_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`merge`] sort.
+///
+/// Equal elements keep their relative order.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::merge_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn merge_desc<T: Ord + Clone>(slice: &mut [T]) {
+    merge_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`merge`] sort, leaving the other elements
+/// untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [5, 4, 3, 2, 1];
+/// sort::merge_range(&mut slice, 2..);
+/// assert_eq!(slice, [5, 4, 1, 2, 3]);
+/// ```
+pub fn merge_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord + Clone,
+    R: RangeBounds<usize>,
+{
+    merge(&mut slice[bounds(range)]);
+}
+
+/// Sorts a slice with [`merge`] sort, using a fallible comparator function.
+///
+/// Stops as soon as `compare` returns an error, and returns it. The slice is
+/// then left in an unspecified order, but still contains all of its elements.
+///
+/// # Errors
+///
+/// Returns the first error returned by `compare`.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [Some(3), Some(1), None, Some(2)];
+/// let result = sort::try_merge_by(&mut slice, |a, b| match (a, b) {
+///     (Some(a), Some(b)) => Ok(a.cmp(b)),
+///     _ => Err("missing value"),
+/// });
+/// assert_eq!(result, Err("missing value"));
+/// ```
+pub fn try_merge_by<T, E, F>(slice: &mut [T], compare: F) -> Result<(), E>
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Result<Ordering, E>,
+{
+    let mut error = None;
+    // taking every element from the left run keeps the merges from
+    // duplicating anything
+    merge_by(slice, fallible(compare, &mut error, Ordering::Less));
+    error.map_or(Ok(()), Err)
+}
+
+/// The recursive part of [`merge_by`].
+fn merge_inner<T, F>(slice: &mut [T], compare: &mut F, cutoff: usize, buf: &mut Vec<T>)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if slice.len() <= cutoff {
+        insertion_by_less(slice, |a, b| compare(a, b) == Ordering::Less);
+        return;
+    } else if test_by(slice, compare) {
+        return;
+    }
+
+    let mid = slice.len() / 2;
+
+    merge_inner(&mut slice[..mid], compare, cutoff, buf);
+    merge_inner(&mut slice[mid..], compare, cutoff, buf);
+
+    merge_galloping(slice, mid, buf, &mut |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// The state of a merge in [`merge_galloping`].
+///
+/// The elements `slice[out..]` that are not yet merged from the right part
+/// are preceded by exactly `buf.len() - left` moved-out ones. Dropping it,
+/// also during a panic, moves the remaining left elements back from `buf`.
+struct MergeHole<'a, T> {
+    slice: &'a mut [T],
+    buf: &'a mut [T],
+    left: usize,
+    out: usize,
+}
+
+impl<T> Drop for MergeHole<'_, T> {
+    fn drop(&mut self) {
+        let rest = &mut self.buf[self.left..];
+        self.slice[self.out..(self.out + rest.len())].swap_with_slice(rest);
+    }
+}
+
+/// The state of a backward merge in [`merge_galloping`].
+///
+/// The elements `slice[..(out - right)]` that are not yet merged from the
+/// left part are followed by exactly `right` moved-out ones. Dropping it, also
+/// during a panic, moves the remaining right elements back from `buf`.
+struct MergeHoleBack<'a, T> {
+    slice: &'a mut [T],
+    buf: &'a mut [T],
+    right: usize,
+    out: usize,
+}
+
+impl<T> Drop for MergeHoleBack<'_, T> {
+    fn drop(&mut self) {
+        let rest = &mut self.buf[..self.right];
+        self.slice[(self.out - rest.len())..self.out].swap_with_slice(rest);
+    }
+}
+
+/// Moves the element on position `root` down the max-heap `heap`, until both
+/// of its children are not greater than it by `is_less`.
+fn sift_down<T, F>(heap: &mut [T], mut root: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    loop {
+        let mut child = 2 * root + 1;
+        if child >= heap.len() {
+            break;
+        }
+        if child + 1 < heap.len() && is_less(&heap[child], &heap[child + 1]) {
+            child += 1;
+        }
+        if !is_less(&heap[root], &heap[child]) {
+            break;
+        }
+
+        heap.swap(root, child);
+        root = child;
+    }
+}
+
+/// An implementation of heap sort.
+///
+/// Builds a max-heap in the slice by sifting down every parent, then
+/// repeatedly swaps the largest element to the end of the slice and restores
+/// the heap on the rest. It's guaranteed to do `O(n log n)` comparisons and
+/// doesn't allocate.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [6, -1, 14, 3, 3, 0, 8];
+/// sort::heap(&mut slice);
+/// assert_eq!(slice, [-1, 0, 3, 3, 6, 8, 14]);
+/// ```
+pub fn heap<T: Ord>(slice: &mut [T]) {
+    heap_by_less(slice, |a, b| a < b);
+}
+
+/// Sorts a slice with [`heap`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = ["kiwi", "apple", "fig", "banana"];
+/// sort::heap_by(&mut slice, |a, b| a.len().cmp(&b.len()).then(a.cmp(b)));
+/// assert_eq!(slice, ["fig", "kiwi", "apple", "banana"]);
+/// ```
+pub fn heap_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    heap_by_less(slice, |a, b| compare(a, b) == Ordering::Less);
+}
+
+/// Sorts a slice with [`heap`] sort, using a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+/// use std::cmp::Reverse;
+///
+/// let mut slice = [1, 6, 3, 2, 8];
+/// sort::heap_by_key(&mut slice, |&x| Reverse(x));
+/// assert_eq!(slice, [8, 6, 3, 2, 1]);
+/// ```
+pub fn heap_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    heap_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`heap`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::heap_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn heap_desc<T: Ord>(slice: &mut [T]) {
+    heap_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`heap`] sort, leaving the other elements
+/// untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 2, 1, 0, -1];
+/// sort::heap_range(&mut slice, ..3);
+/// assert_eq!(slice, [1, 2, 3, 0, -1]);
+/// ```
+pub fn heap_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    heap(&mut slice[bounds(range)]);
+}
+
+/// An implementation of weak-heap sort.
+///
+/// Builds a weak heap: a binary tree where every element is not smaller than
+/// the elements in the right subtree of its node. It's relaxed enough that the
+/// children of a node can be swapped by flipping a single bit. The largest
+/// element is then repeatedly moved to the end, and the heap is restored along
+/// a single path.
+///
+/// It does at most `n log2 n + 0.1n` comparisons, close to the theoretical
+/// minimum, and uses `n` bytes of extra memory: a flag for every element.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [9, 3, -4, 3, 17, 0, 6];
+/// sort::weak_heap(&mut slice);
+/// assert_eq!(slice, [-4, 0, 3, 3, 6, 9, 17]);
+/// ```
+pub fn weak_heap<T: Ord>(slice: &mut [T]) {
+    weak_heap_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`weak_heap`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [9, 3, -4, 3, 17, 0, 6];
+/// sort::weak_heap_by(&mut slice, |a, b| b.cmp(a));
+/// assert_eq!(slice, [17, 9, 6, 3, 3, 0, -4]);
+/// ```
+pub fn weak_heap_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    /// Restores the weak heap order between `i` and its descendant `j`.
+    fn join<T, F>(slice: &mut [T], reverse: &mut [bool], i: usize, j: usize, compare: &mut F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if compare(&slice[i], &slice[j]) == Ordering::Less {
+            slice.swap(i, j);
+            reverse[j] = !reverse[j];
+        }
+    }
+
+    let n = slice.len();
+    if n < 2 {
+        return;
+    }
+
+    // the reverse bits: whether the children of the node are swapped
+    let mut reverse = vec![false; n];
+
+    for j in (1..n).rev() {
+        // the distinguished ancestor: the parent of the first node on the
+        // path to the root that is a right child
+        let mut i = j;
+        while (i & 1 == 1) == reverse[i >> 1] {
+            i >>= 1;
+        }
+        join(slice, &mut reverse, i >> 1, j, &mut compare);
+    }
+
+    for m in (2..n).rev() {
+        slice.swap(0, m);
+
+        let mut x = 1;
+        while 2 * x + (reverse[x] as usize) < m {
+            x = 2 * x + reverse[x] as usize;
+        }
+        while x > 0 {
+            join(slice, &mut reverse, 0, x, &mut compare);
+            x >>= 1;
+        }
+    }
+    slice.swap(0, 1);
+}
+
+/// Sorts a slice with [`weak_heap`] sort, using a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [-5, 3, -1, 4, -2];
+/// sort::weak_heap_by_key(&mut slice, |x: &i32| x.abs());
+/// assert_eq!(slice, [-1, -2, 3, 4, -5]);
+/// ```
+pub fn weak_heap_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    weak_heap_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`weak_heap`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::weak_heap_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn weak_heap_desc<T: Ord>(slice: &mut [T]) {
+    weak_heap_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`weak_heap`] sort, leaving the other elements
+/// untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [9, 8, 3, 1, 2, 0];
+/// sort::weak_heap_range(&mut slice, 1..5);
+/// assert_eq!(slice, [9, 1, 2, 3, 8, 0]);
+/// ```
+pub fn weak_heap_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    weak_heap(&mut slice[bounds(range)]);
+}
+
+/// An implementation of Cartesian tree sort.
+///
+/// Builds a Cartesian tree of the slice in linear time: a binary tree with the
+/// smallest element in the root, whose in-order traversal gives the original
+/// slice. Then the elements are extracted from a heap that initially contains
+/// only the root; every extracted node adds its children to it.
+///
+/// The heap stays small when the slice is almost sorted, so it's strongly
+/// adaptive: it does `O(n)` comparisons for a sorted slice, and `O(n log k)`
+/// when the slice consists of `k` sorted runs. The sort is stable.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [1, 2, 3, 10, 4, 5, 6, 0];
+/// sort::cartesian_tree(&mut slice);
+/// assert_eq!(slice, [0, 1, 2, 3, 4, 5, 6, 10]);
+/// ```
+pub fn cartesian_tree<T: Ord>(slice: &mut [T]) {
+    cartesian_tree_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`cartesian_tree`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [(3, 'a'), (1, 'b'), (3, 'c'), (2, 'd')];
+/// sort::cartesian_tree_by(&mut slice, |a, b| b.0.cmp(&a.0));
+/// assert_eq!(slice, [(3, 'a'), (3, 'c'), (2, 'd'), (1, 'b')]);
+/// ```
+pub fn cartesian_tree_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let n = slice.len();
+    if n < 2 {
+        return;
+    }
+
+    let mut left = vec![None; n];
+    let mut right = vec![None; n];
+    let mut stack: Vec<usize> = Vec::new();
+    for i in 0..n {
+        let mut last = None;
+        while let Some(&top) = stack.last() {
+            if compare(&slice[top], &slice[i]) == Ordering::Greater {
+                last = stack.pop();
+            } else {
+                break;
+            }
+        }
+
+        left[i] = last;
+        if let Some(&top) = stack.last() {
+            right[top] = Some(i);
+        }
+        stack.push(i);
+    }
+
+    // a heap of positions with the earliest element in the root; equal
+    // elements are ordered by their positions, which keeps the sort stable
+    let mut is_later = |a: &usize, b: &usize| match compare(&slice[*a], &slice[*b]) {
+        Ordering::Equal => a > b,
+        order => order == Ordering::Greater,
+    };
+    let mut heap = vec![stack[0]];
+    let mut dest = vec![0; n];
+    let mut pos = 0;
+    while !heap.is_empty() {
+        let i = heap.swap_remove(0);
+        sift_down(&mut heap, 0, &mut is_later);
+        dest[i] = pos;
+        pos += 1;
+
+        for &child in [left[i], right[i]].iter().flatten() {
+            heap.push(child);
+            let mut k = heap.len() - 1;
+            while k > 0 && is_later(&heap[(k - 1) / 2], &heap[k]) {
+                heap.swap((k - 1) / 2, k);
+                k = (k - 1) / 2;
+            }
+        }
+    }
+
+    move_to(slice, dest);
+}
+
+/// Sorts a slice with [`cartesian_tree`] sort, using a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [(2, 'a'), (1, 'b'), (2, 'c'), (0, 'd')];
+/// sort::cartesian_tree_by_key(&mut slice, |p| p.0);
+/// assert_eq!(slice, [(0, 'd'), (1, 'b'), (2, 'a'), (2, 'c')]);
+/// ```
+pub fn cartesian_tree_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    cartesian_tree_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`cartesian_tree`] sort. Equal
+/// elements keep their original order.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::cartesian_tree_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn cartesian_tree_desc<T: Ord>(slice: &mut [T]) {
+    cartesian_tree_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`cartesian_tree`] sort, leaving the other
+/// elements untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [5, 4, 3, 2, 1];
+/// sort::cartesian_tree_range(&mut slice, 2..);
+/// assert_eq!(slice, [5, 4, 1, 2, 3]);
+/// ```
+pub fn cartesian_tree_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    cartesian_tree(&mut slice[bounds(range)]);
+}
+
+/// Heap sort with a custom `is_less` function.
+fn heap_by_less<T, F>(slice: &mut [T], mut is_less: F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    for root in (0..(slice.len() / 2)).rev() {
+        sift_down(slice, root, &mut is_less);
+    }
+
+    for end in (1..slice.len()).rev() {
+        slice.swap(0, end);
+        sift_down(&mut slice[..end], 0, &mut is_less);
+    }
+}
+
+/// An iterator over the elements in ascending order, created by
+/// [`sorted_iter`].
+#[derive(Debug, Clone)]
+pub struct SortedIter<T> {
+    // a min-heap of the remaining elements
+    heap: Vec<T>,
+}
+
+impl<T: Ord> Iterator for SortedIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        let last = self.heap.len() - 1;
+        self.heap.swap(0, last);
+        let min = self.heap.pop();
+        sift_down(&mut self.heap, 0, &mut |a: &T, b: &T| b < a);
+        min
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+impl<T: Ord> ExactSizeIterator for SortedIter<T> {}
+
+/// Returns an iterator over the items in ascending order, which sorts them
+/// lazily.
+///
+/// The items are collected and turned into a heap in `O(n)` time, and every
+/// call to `next` takes the smallest one out in `O(log n)` time, like in
+/// [`heap`] sort. Taking only the first `k` items takes `O(n + k log n)` time.
+/// It's not stable.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let items = (0..1_000_000).rev();
+/// let smallest: Vec<_> = sort::sorted_iter(items).take(3).collect();
+/// assert_eq!(smallest, [0, 1, 2]);
+/// ```
+pub fn sorted_iter<T, I>(iter: I) -> SortedIter<T>
+where
+    T: Ord,
+    I: IntoIterator<Item = T>,
+{
+    let mut heap: Vec<_> = iter.into_iter().collect();
+    for root in (0..(heap.len() / 2)).rev() {
+        sift_down(&mut heap, root, &mut |a: &T, b: &T| b < a);
+    }
+
+    SortedIter { heap }
+}
+
+/// Sorting adapters for all iterators.
+///
+/// The items are collected into a vector, sorted with this crate's sorts and
+/// returned as an iterator, so the adapters fit in iterator pipelines.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort::IteratorSortExt;
+///
+/// let words = ["pear", "fig", "apple", "kiwi"];
+/// let sorted: Vec<_> = words.iter().sorted_by_key(|w| w.len()).collect();
+/// assert_eq!(sorted, [&"fig", &"pear", &"kiwi", &"apple"]);
+/// ```
+pub trait IteratorSortExt: Iterator + Sized {
+    /// Returns the items in ascending order. It's stable, and uses
+    /// [`cartesian_tree`] sort.
+    ///
+    /// # Examples
+    /// ```
+    /// use search_sort::sort::IteratorSortExt;
+    ///
+    /// let sorted: Vec<_> = [3, 1, 2].iter().sorted().collect();
+    /// assert_eq!(sorted, [&1, &2, &3]);
+    /// ```
+    fn sorted(self) -> vec::IntoIter<Self::Item>
+    where
+        Self::Item: Ord,
+    {
+        self.sorted_by(Self::Item::cmp)
+    }
+
+    /// Returns the items sorted by a comparator function. It's stable.
+    ///
+    /// # Examples
+    /// ```
+    /// use search_sort::sort::IteratorSortExt;
+    ///
+    /// let sorted: Vec<_> = (0..5).sorted_by(|a, b| b.cmp(a)).collect();
+    /// assert_eq!(sorted, [4, 3, 2, 1, 0]);
+    /// ```
+    fn sorted_by<F>(self, compare: F) -> vec::IntoIter<Self::Item>
+    where
+        F: FnMut(&Self::Item, &Self::Item) -> Ordering,
+    {
+        let mut items: Vec<_> = self.collect();
+        cartesian_tree_by(&mut items, compare);
+        items.into_iter()
+    }
+
+    /// Returns the items sorted by a key extraction function. It's stable.
+    ///
+    /// # Examples
+    /// ```
+    /// use search_sort::sort::IteratorSortExt;
+    ///
+    /// let sorted: Vec<_> = [-3, 1, -2_i32].iter().sorted_by_key(|x| x.abs()).collect();
+    /// assert_eq!(sorted, [&1, &-2, &-3]);
+    /// ```
+    fn sorted_by_key<K, F>(self, mut key_fn: F) -> vec::IntoIter<Self::Item>
+    where
+        K: Ord,
+        F: FnMut(&Self::Item) -> K,
+    {
+        self.sorted_by(|a, b| key_fn(a).cmp(&key_fn(b)))
+    }
+
+    /// Returns the `k` smallest items in ascending order, or all of them if
+    /// there are fewer.
+    ///
+    /// Keeps a max-heap of the `k` smallest items seen so far, so it takes
+    /// `O(n log k)` time and `O(k)` memory. It's not stable.
+    ///
+    /// # Examples
+    /// ```
+    /// use search_sort::sort::IteratorSortExt;
+    ///
+    /// let smallest: Vec<_> = (0..1000).rev().k_smallest(3).collect();
+    /// assert_eq!(smallest, [0, 1, 2]);
+    /// ```
+    fn k_smallest(self, k: usize) -> vec::IntoIter<Self::Item>
+    where
+        Self::Item: Ord,
+    {
+        k_greatest(self, k, |a, b| b < a).into_iter()
+    }
+}
+
+impl<I: Iterator> IteratorSortExt for I {}
+
+/// The initial number of consecutive wins after which [`tim`] and [`merge`]
+/// sort start galloping.
+const MIN_GALLOP: usize = 7;
+
+/// Finds the number of elements at the start of a sorted slice, for which
+/// `pred` holds.
+///
+/// Checks the positions 0, 2, 6, 14, ... until `pred` fails, and then binary
+/// searches the last step, so it does `O(log k)` comparisons for the result
+/// `k`.
+fn gallop<T, F>(slice: &[T], mut pred: F) -> usize
+where
+    F: FnMut(&T) -> bool,
+{
+    // pred holds for all the elements before lo
+    let mut lo = 0;
+    let mut hi = 1;
+    while hi <= slice.len() && pred(&slice[hi - 1]) {
+        lo = hi;
+        hi = 2 * hi + 1;
+    }
+
+    let hi = hi.min(slice.len());
+    lo + slice[lo..hi].partition_point(pred)
+}
+
+/// Finds the number of elements at the end of a sorted slice, for which
+/// `pred` holds, like [`gallop`] does from the start.
+fn gallop_back<T, F>(slice: &[T], mut pred: F) -> usize
+where
+    F: FnMut(&T) -> bool,
+{
+    // pred holds for the last lo elements
+    let len = slice.len();
+    let mut lo = 0;
+    let mut hi = 1;
+    while hi <= len && pred(&slice[len - hi]) {
+        lo = hi;
+        hi = 2 * hi + 1;
+    }
+
+    let hi = hi.min(len);
+    let rest = &slice[(len - hi)..(len - lo)];
+    lo + rest.len() - rest.partition_point(|x| !pred(x))
+}
+
+/// An iterator that merges sorted iterators, created by [`kmerge`].
+#[derive(Debug, Clone)]
+pub struct KMerge<I: Iterator> {
+    sources: Vec<I>,
+    // a min-heap of the next item of every source, with the source's index
+    heap: Vec<(I::Item, usize)>,
+}
+
+impl<I> Iterator for KMerge<I>
+where
+    I: Iterator,
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        if self.heap.is_empty() {
+            return None;
+        }
+
+        let i = self.heap[0].1;
+        let (item, _) = match self.sources[i].next() {
+            Some(next) => mem::replace(&mut self.heap[0], (next, i)),
+            None => self.heap.swap_remove(0),
+        };
+        sift_down(&mut self.heap, 0, &mut |a, b| b < a);
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let mut low = self.heap.len();
+        let mut high = Some(self.heap.len());
+        for source in &self.sources {
+            let (l, h) = source.size_hint();
+            low = low.saturating_add(l);
+            high = high.and_then(|high| h.and_then(|h| high.checked_add(h)));
+        }
+
+        (low, high)
+    }
+}
+
+/// Merges sorted iterators into a single sorted iterator.
+///
+/// The next items of all the sources are kept in a heap, so every item takes
+/// `O(log k)` comparisons for `k` sources. It's stable: equal items come in
+/// the order of their sources.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let shards = vec![vec![1, 4, 7], vec![2, 5, 8], vec![0, 3, 6, 9]];
+/// let merged: Vec<_> = sort::kmerge(shards).collect();
+/// assert_eq!(merged, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+/// ```
+pub fn kmerge<S, I>(sources: S) -> KMerge<I::IntoIter>
+where
+    S: IntoIterator<Item = I>,
+    I: IntoIterator,
+    I::Item: Ord,
+{
+    let mut sources: Vec<_> = sources.into_iter().map(I::into_iter).collect();
+    let mut heap: Vec<_> = sources
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(i, source)| source.next().map(|item| (item, i)))
+        .collect();
+    for root in (0..(heap.len() / 2)).rev() {
+        sift_down(&mut heap, root, &mut |a, b| b < a);
+    }
+
+    KMerge { sources, heap }
+}
+
+/// An item of a [`merge_join`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Joined<L, R> {
+    /// Items of both sides with equal keys.
+    Both(L, R),
+    /// An item of the left side, with a key missing on the right side.
+    Left(L),
+    /// An item of the right side, with a key missing on the left side.
+    Right(R),
+}
+
+impl<L, R> Joined<L, R> {
+    /// Returns the pair of an inner join, if there's an item on both sides.
+    pub fn both(self) -> Option<(L, R)> {
+        match self {
+            Joined::Both(l, r) => Some((l, r)),
+            _ => None,
+        }
+    }
+
+    /// Returns the pair of a left join, if there's an item on the left side.
+    pub fn left(self) -> Option<(L, Option<R>)> {
+        match self {
+            Joined::Both(l, r) => Some((l, Some(r))),
+            Joined::Left(l) => Some((l, None)),
+            Joined::Right(_) => None,
+        }
+    }
+}
+
+/// An iterator that joins two sorted iterators, created by [`merge_join`].
+#[derive(Debug, Clone)]
+pub struct MergeJoin<I, J, F, G>
+where
+    I: Iterator,
+    J: Iterator,
+{
+    left: Peekable<I>,
+    right: Peekable<J>,
+    left_key: F,
+    right_key: G,
+    // the pairs of a group of equal keys that are not yielded yet
+    pending: VecDeque<Joined<I::Item, J::Item>>,
+}
+
+/// Pairs `l` with every item of `rights`, and moves it into the last pair.
+fn push_pairs<L, R, I>(pending: &mut VecDeque<Joined<L, R>>, l: L, rights: I)
+where
+    L: Clone,
+    I: DoubleEndedIterator<Item = R>,
+{
+    let mut rights = rights;
+    let last = rights.next_back();
+    for r in rights {
+        pending.push_back(Joined::Both(l.clone(), r));
+    }
+    if let Some(r) = last {
+        pending.push_back(Joined::Both(l, r));
+    }
+}
+
+impl<I, J, K, F, G> Iterator for MergeJoin<I, J, F, G>
+where
+    I: Iterator,
+    I::Item: Clone,
+    J: Iterator,
+    J::Item: Clone,
+    K: Ord,
+    F: FnMut(&I::Item) -> K,
+    G: FnMut(&J::Item) -> K,
+{
+    type Item = Joined<I::Item, J::Item>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(joined) = self.pending.pop_front() {
+            return Some(joined);
+        }
+
+        let (left_key, right_key) = (&mut self.left_key, &mut self.right_key);
+        let order = match (self.left.peek(), self.right.peek()) {
+            (None, None) => return None,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(l), Some(r)) => left_key(l).cmp(&right_key(r)),
+        };
+
+        match order {
+            Ordering::Less => self.left.next().map(Joined::Left),
+            Ordering::Greater => self.right.next().map(Joined::Right),
+            Ordering::Equal => {
+                let first = self.left.next()?;
+                let key = left_key(&first);
+                let mut lefts = vec![first];
+                while let Some(l) = self.left.next_if(|l| left_key(l) == key) {
+                    lefts.push(l);
+                }
+                let mut rights = Vec::new();
+                while let Some(r) = self.right.next_if(|r| right_key(r) == key) {
+                    rights.push(r);
+                }
+
+                // every item is cloned for all of its pairs but the last one,
+                // so a group with a single item on one side only clones that
+                // item
+                let last = lefts.pop()?;
+                for l in lefts {
+                    push_pairs(&mut self.pending, l, rights.iter().cloned());
+                }
+                push_pairs(&mut self.pending, last, rights.into_iter());
+                self.pending.pop_front()
+            }
+        }
+    }
+}
+
+/// Joins two iterators sorted by the keys of `left_key` and `right_key`.
+///
+/// Yields the items with equal keys on both sides as [`Joined::Both`] pairs,
+/// every item of the left side with every item of the right one, and the
+/// other items as [`Joined::Left`] and [`Joined::Right`], in the order of the
+/// keys. That's a full outer join; [`Joined::both`] and [`Joined::left`] turn
+/// it into an inner and a left join. It takes linear time, besides the pairs
+/// of the groups of equal keys. An item is only cloned if it's paired with
+/// more than one item of the other side.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort::{self, Joined};
+///
+/// let prices = [("apple", 3), ("fig", 7), ("kiwi", 2)];
+/// let stock = [(10, "apple"), (4, "banana"), (0, "kiwi")];
+/// let mut joined =
+///     sort::merge_join(prices.iter(), stock.iter(), |p| p.0, |s| s.1);
+/// assert_eq!(joined.next(), Some(Joined::Both(&("apple", 3), &(10, "apple"))));
+/// assert_eq!(joined.next(), Some(Joined::Right(&(4, "banana"))));
+/// assert_eq!(joined.next(), Some(Joined::Left(&("fig", 7))));
+///
+/// let inner: Vec<_> = sort::merge_join(prices, stock, |p| p.0, |s| s.1)
+///     .filter_map(Joined::both)
+///     .map(|(price, stock)| (price.0, price.1 * stock.0))
+///     .collect();
+/// assert_eq!(inner, [("apple", 30), ("kiwi", 0)]);
+/// ```
+pub fn merge_join<I, J, K, F, G>(
+    left: I,
+    right: J,
+    left_key: F,
+    right_key: G,
+) -> MergeJoin<I::IntoIter, J::IntoIter, F, G>
+where
+    I: IntoIterator,
+    I::Item: Clone,
+    J: IntoIterator,
+    J::Item: Clone,
+    K: Ord,
+    F: FnMut(&I::Item) -> K,
+    G: FnMut(&J::Item) -> K,
+{
+    MergeJoin {
+        left: left.into_iter().peekable(),
+        right: right.into_iter().peekable(),
+        left_key,
+        right_key,
+        pending: VecDeque::new(),
+    }
+}
+
+/// The iterator, from which the next item of a [`SetMerge`] comes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Side {
+    A,
+    B,
+    Both,
+}
+
+/// Merges two sorted iterators, and pairs their equal items.
+struct SetMerge<I: Iterator, J: Iterator<Item = I::Item>> {
+    a: Peekable<I>,
+    b: Peekable<J>,
+}
+
+impl<I, J> SetMerge<I, J>
+where
+    I: Iterator,
+    I::Item: Ord,
+    J: Iterator<Item = I::Item>,
+{
+    fn new(a: I, b: J) -> Self {
+        SetMerge {
+            a: a.peekable(),
+            b: b.peekable(),
+        }
+    }
+
+    /// Returns the smallest next item, and where it comes from. Of a pair of
+    /// equal items, the one from `a` is returned.
+    fn next_side(&mut self) -> Option<(I::Item, Side)> {
+        let order = match (self.a.peek(), self.b.peek()) {
+            (None, None) => return None,
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (Some(a), Some(b)) => a.cmp(b),
+        };
+
+        match order {
+            Ordering::Less => self.a.next().map(|a| (a, Side::A)),
+            Ordering::Greater => self.b.next().map(|b| (b, Side::B)),
+            Ordering::Equal => {
+                self.b.next();
+                self.a.next().map(|a| (a, Side::Both))
+            }
+        }
+    }
+
+    /// Returns the next item that comes from one of the sides.
+    fn next_from(&mut self, sides: &[Side]) -> Option<I::Item> {
+        loop {
+            let (item, side) = self.next_side()?;
+            if sides.contains(&side) {
+                return Some(item);
+            }
+        }
+    }
+}
+
+macro_rules! set_iter {
+    ($(#[$attr:meta])* $name:ident, [$($side:ident),*], $size_hint:expr) => {
+        $(#[$attr])*
+        pub struct $name<I: Iterator, J: Iterator<Item = I::Item>> {
+            inner: SetMerge<I, J>,
+        }
+
+        // not derived, since the peeked items need the bounds too
+        impl<I, J> Clone for $name<I, J>
+        where
+            I: Iterator + Clone,
+            I::Item: Clone,
+            J: Iterator<Item = I::Item> + Clone,
+        {
+            fn clone(&self) -> Self {
+                let inner = SetMerge {
+                    a: self.inner.a.clone(),
+                    b: self.inner.b.clone(),
+                };
+                $name { inner }
+            }
+        }
+
+        impl<I, J> fmt::Debug for $name<I, J>
+        where
+            I: Iterator + fmt::Debug,
+            I::Item: fmt::Debug,
+            J: Iterator<Item = I::Item> + fmt::Debug,
+        {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("a", &self.inner.a)
+                    .field("b", &self.inner.b)
+                    .finish()
+            }
+        }
+
+        impl<I, J> Iterator for $name<I, J>
+        where
+            I: Iterator,
+            I::Item: Ord,
+            J: Iterator<Item = I::Item>,
+        {
+            type Item = I::Item;
+
+            fn next(&mut self) -> Option<I::Item> {
+                self.inner.next_from(&[$(Side::$side),*])
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                let size_hint: fn(_, _) -> _ = $size_hint;
+                size_hint(self.inner.a.size_hint(), self.inner.b.size_hint())
+            }
+        }
+    };
+}
+
+set_iter!(
+    /// An iterator over the union of two sorted iterators, created by
+    /// [`union_iter`].
+    UnionIter,
+    [A, B, Both],
+    |(a_low, a_high): (usize, Option<usize>), (b_low, b_high): (usize, Option<usize>)| {
+        let high = a_high.and_then(|a| b_high.and_then(|b| a.checked_add(b)));
+        (a_low.max(b_low), high)
+    }
+);
+
+set_iter!(
+    /// An iterator over the intersection of two sorted iterators, created by
+    /// [`intersect_iter`].
+    IntersectIter,
+    [Both],
+    |(_, a_high): (usize, Option<usize>), (_, b_high): (usize, Option<usize>)| {
+        let high = match (a_high, b_high) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        (0, high)
+    }
+);
+
+set_iter!(
+    /// An iterator over the difference of two sorted iterators, created by
+    /// [`difference_iter`].
+    DifferenceIter,
+    [A],
+    |(a_low, a_high): (usize, Option<usize>), (_, b_high): (usize, Option<usize>)| {
+        let low = b_high.map_or(0, |b| a_low.saturating_sub(b));
+        (low, a_high)
+    }
+);
+
+set_iter!(
+    /// An iterator over the symmetric difference of two sorted iterators,
+    /// created by [`symmetric_difference_iter`].
+    SymmetricDifferenceIter,
+    [A, B],
+    |(_, a_high): (usize, Option<usize>), (_, b_high): (usize, Option<usize>)| {
+        let high = a_high.and_then(|a| b_high.and_then(|b| a.checked_add(b)));
+        (0, high)
+    }
+);
+
+/// Returns a lazy iterator over the union of two sorted iterators.
+///
+/// Works like [`union`], but the items are merged only when they're needed,
+/// so the sources can be streams, like a [`kmerge`]. Of equal items, the one
+/// from `a` is yielded.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let odd = (1..).step_by(2);
+/// let squares = (1..).map(|x| x * x);
+/// let first: Vec<_> = sort::union_iter(odd, squares).take(6).collect();
+/// assert_eq!(first, [1, 3, 4, 5, 7, 9]);
+/// ```
+pub fn union_iter<A, B>(a: A, b: B) -> UnionIter<A::IntoIter, B::IntoIter>
+where
+    A: IntoIterator,
+    A::Item: Ord,
+    B: IntoIterator<Item = A::Item>,
+{
+    UnionIter {
+        inner: SetMerge::new(a.into_iter(), b.into_iter()),
+    }
+}
+
+/// Returns a lazy iterator over the intersection of two sorted iterators,
+/// like [`union_iter`] and [`intersection`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let odd = (1..).step_by(2);
+/// let squares = (1..).map(|x| x * x);
+/// let first: Vec<_> = sort::intersect_iter(odd, squares).take(3).collect();
+/// assert_eq!(first, [1, 9, 25]);
+/// ```
+pub fn intersect_iter<A, B>(a: A, b: B) -> IntersectIter<A::IntoIter, B::IntoIter>
+where
+    A: IntoIterator,
+    A::Item: Ord,
+    B: IntoIterator<Item = A::Item>,
+{
+    IntersectIter {
+        inner: SetMerge::new(a.into_iter(), b.into_iter()),
+    }
+}
+
+/// Returns a lazy iterator over the items of the sorted iterator `a` that
+/// aren't in the sorted iterator `b`, like [`union_iter`] and [`difference`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let odd = (1..).step_by(2);
+/// let squares = (1..).map(|x| x * x);
+/// let first: Vec<_> = sort::difference_iter(odd, squares).take(4).collect();
+/// assert_eq!(first, [3, 5, 7, 11]);
+/// ```
+pub fn difference_iter<A, B>(a: A, b: B) -> DifferenceIter<A::IntoIter, B::IntoIter>
+where
+    A: IntoIterator,
+    A::Item: Ord,
+    B: IntoIterator<Item = A::Item>,
+{
+    DifferenceIter {
+        inner: SetMerge::new(a.into_iter(), b.into_iter()),
+    }
+}
+
+/// Returns a lazy iterator over the items that are in only one of two sorted
+/// iterators, like [`union_iter`] and [`symmetric_difference`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let odd = (1..).step_by(2);
+/// let squares = (1..).map(|x| x * x);
+/// let first: Vec<_> = sort::symmetric_difference_iter(odd, squares).take(5).collect();
+/// assert_eq!(first, [3, 4, 5, 7, 11]);
+/// ```
+pub fn symmetric_difference_iter<A, B>(
+    a: A,
+    b: B,
+) -> SymmetricDifferenceIter<A::IntoIter, B::IntoIter>
+where
+    A: IntoIterator,
+    A::Item: Ord,
+    B: IntoIterator<Item = A::Item>,
+{
+    SymmetricDifferenceIter {
+        inner: SetMerge::new(a.into_iter(), b.into_iter()),
+    }
+}
+
+/// Returns the length of the run at the start of the slice by `is_less`. If
+/// the run is strictly descending, it's reversed.
+fn run_len<T, F>(slice: &mut [T], is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let (end, descending) = run_end(slice, is_less);
+    if descending {
+        slice[..end].reverse();
+    }
+
+    end
+}
+
+/// Returns the length of the run at the start of the slice by `is_less`, and
+/// whether the run is strictly descending.
+fn run_end<T, F>(slice: &[T], is_less: &mut F) -> (usize, bool)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if slice.len() < 2 {
+        return (slice.len(), false);
+    }
+
+    let mut end = 2;
+    let descending = is_less(&slice[1], &slice[0]);
+    if descending {
+        while end < slice.len() && is_less(&slice[end], &slice[end - 1]) {
+            end += 1;
+        }
+    } else {
+        while end < slice.len() && !is_less(&slice[end], &slice[end - 1]) {
+            end += 1;
+        }
+    }
+
+    (end, descending)
+}
+
+/// Splits a slice into maximal runs, as [`tim`] sort does.
+///
+/// Yields the ranges of the consecutive runs, which are either ascending
+/// (with equal elements allowed) or strictly descending. The runs cover the
+/// whole slice, so their number measures how presorted it is.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let slice = [1, 2, 2, 5, 4, 3, 0, 7, 8];
+/// let runs: Vec<_> = sort::runs(&slice).collect();
+/// assert_eq!(runs, [0..4, 4..7, 7..9]);
+/// ```
+pub fn runs<T: Ord>(slice: &[T]) -> impl Iterator<Item = Range<usize>> + '_ {
+    runs_by(slice, T::cmp)
+}
+
+/// Splits a slice into maximal [`runs`] by a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let slice = [5, 3, 1, 2, 4];
+/// let runs: Vec<_> = sort::runs_by(&slice, |a, b| b.cmp(a)).collect();
+/// assert_eq!(runs, [0..3, 3..5]);
+/// ```
+pub fn runs_by<'a, T, F>(slice: &'a [T], mut compare: F) -> impl Iterator<Item = Range<usize>> + 'a
+where
+    F: FnMut(&T, &T) -> Ordering + 'a,
+{
+    let mut start = 0;
+    std::iter::from_fn(move || {
+        if start == slice.len() {
+            return None;
+        }
+
+        let mut is_less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+        let (len, _) = run_end(&slice[start..], &mut is_less);
+        start += len;
+        Some((start - len)..start)
+    })
+}
+
+/// Computes the minimal length of a run for [`tim`] sort, so that the number
+/// of runs is equal to or slightly less than a power of 2.
+fn min_run(mut n: usize) -> usize {
+    let mut r = 0;
+    while n >= 64 {
+        r |= n & 1;
+        n >>= 1;
+    }
+
+    n + r
+}
+
+/// Merges the sorted `slice[..mid]` and `slice[mid..]` in a stable way by
+/// `is_less`, with galloping.
+///
+/// The shorter part is copied to `buf`, so it takes at most half of the
+/// elements; when it's the right one, the merge goes backward from the end.
+/// When one part wins [`MIN_GALLOP`] consecutive comparisons, the merge
+/// switches to galloping, and copies whole blocks found by [`gallop`]; it
+/// switches back once the blocks get short.
+fn merge_galloping<T, F>(slice: &mut [T], mid: usize, buf: &mut Vec<T>, is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    // the elements that are already in place are skipped
+    let start = gallop(&slice[..mid], |x| !is_less(&slice[mid], x));
+    let end = mid + gallop(&slice[mid..], |x| is_less(x, &slice[mid - 1]));
+    let slice = &mut slice[start..end];
+    let mid = mid - start;
+    if mid > slice.len() - mid {
+        merge_galloping_back(slice, mid, buf, is_less);
+        return;
+    }
+
+    buf.clear();
+    buf.extend_from_slice(&slice[..mid]);
+
+    // the elements are swapped into place, so that a panicking `is_less`
+    // can't leave any element duplicated in the slice
+    let mut hole = MergeHole {
+        slice,
+        buf: &mut buf[..],
+        left: 0,
+        out: 0,
+    };
+    let mut min_gallop = MIN_GALLOP;
+    let mut j = mid;
+    'merge: while hole.left < mid && j < hole.slice.len() {
+        let mut left_wins = 0;
+        let mut right_wins = 0;
+        while left_wins < min_gallop && right_wins < min_gallop {
+            if is_less(&hole.slice[j], &hole.buf[hole.left]) {
+                hole.slice.swap(hole.out, j);
+                j += 1;
+                right_wins += 1;
+                left_wins = 0;
+            } else {
+                mem::swap(&mut hole.slice[hole.out], &mut hole.buf[hole.left]);
+                hole.left += 1;
+                left_wins += 1;
+                right_wins = 0;
+            }
+            hole.out += 1;
+
+            if hole.left == mid || j == hole.slice.len() {
+                break 'merge;
+            }
+        }
+
+        loop {
+            let (i, k) = (hole.left, hole.out);
+            let n = gallop(&hole.buf[i..], |x| !is_less(&hole.slice[j], x));
+            hole.slice[k..(k + n)].swap_with_slice(&mut hole.buf[i..(i + n)]);
+            hole.left += n;
+            hole.out += n;
+            if hole.left == mid {
+                break 'merge;
+            }
+
+            let (i, k) = (hole.left, hole.out);
+            let m = gallop(&hole.slice[j..], |x| is_less(x, &hole.buf[i]));
+            for t in 0..m {
+                hole.slice.swap(k + t, j + t);
+            }
+            j += m;
+            hole.out += m;
+            if j == hole.slice.len() {
+                break 'merge;
+            }
+
+            if n < MIN_GALLOP && m < MIN_GALLOP {
+                min_gallop += 1;
+                break;
+            } else if min_gallop > 1 {
+                min_gallop -= 1;
+            }
+        }
+    }
+}
+
+/// Merges the sorted `slice[..mid]` and `slice[mid..]` like
+/// [`merge_galloping`], but from the end, with the right part copied to `buf`.
+fn merge_galloping_back<T, F>(slice: &mut [T], mid: usize, buf: &mut Vec<T>, is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    buf.clear();
+    buf.extend_from_slice(&slice[mid..]);
+
+    let len = slice.len();
+    let mut hole = MergeHoleBack {
+        slice,
+        buf: &mut buf[..],
+        right: len - mid,
+        out: len,
+    };
+    let mut min_gallop = MIN_GALLOP;
+    // the left elements before i are not merged yet
+    let mut i = mid;
+    'merge: while hole.right > 0 && i > 0 {
+        let mut left_wins = 0;
+        let mut right_wins = 0;
+        while left_wins < min_gallop && right_wins < min_gallop {
+            if is_less(&hole.buf[hole.right - 1], &hole.slice[i - 1]) {
+                hole.slice.swap(hole.out - 1, i - 1);
+                i -= 1;
+                left_wins += 1;
+                right_wins = 0;
+            } else {
+                mem::swap(&mut hole.slice[hole.out - 1], &mut hole.buf[hole.right - 1]);
+                hole.right -= 1;
+                right_wins += 1;
+                left_wins = 0;
+            }
+            hole.out -= 1;
+
+            if hole.right == 0 || i == 0 {
+                break 'merge;
+            }
+        }
+
+        loop {
+            let (r, k) = (hole.right, hole.out);
+            let n = gallop_back(&hole.slice[..i], |x| is_less(&hole.buf[r - 1], x));
+            for t in 1..=n {
+                hole.slice.swap(k - t, i - t);
+            }
+            i -= n;
+            hole.out -= n;
+            if i == 0 {
+                break 'merge;
+            }
+
+            let (r, k) = (hole.right, hole.out);
+            let m = gallop_back(&hole.buf[..r], |x| !is_less(x, &hole.slice[i - 1]));
+            hole.slice[(k - m)..k].swap_with_slice(&mut hole.buf[(r - m)..r]);
+            hole.right -= m;
+            hole.out -= m;
+            if hole.right == 0 {
+                break 'merge;
+            }
+
+            if n < MIN_GALLOP && m < MIN_GALLOP {
+                min_gallop += 1;
+                break;
+            } else if min_gallop > 1 {
+                min_gallop -= 1;
+            }
+        }
+    }
+}
+
+/// An implementation of Timsort.
+///
+/// Splits the slice into natural runs, reversing the descending ones and
+/// extending the short ones to a minimal length with [`insertion`] sort. The
+/// runs are kept on a stack and merged with galloping, so that the lengths of
+/// the runs on the stack decrease at least as fast as the Fibonacci numbers.
+///
+/// It's stable, and very fast on partially sorted data. Uses at most `n / 2`
+/// elements of extra space.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [1, 2, 3, 9, 8, 7, -1, 0, 4];
+/// sort::tim(&mut slice);
+/// assert_eq!(slice, [-1, 0, 1, 2, 3, 4, 7, 8, 9]);
+/// ```
+pub fn tim<T: Ord + Clone>(slice: &mut [T]) {
+    tim_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`tim`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [(1, 'a'), (0, 'b'), (1, 'c'), (0, 'd'), (2, 'e')];
+/// sort::tim_by(&mut slice, |a, b| a.0.cmp(&b.0));
+/// assert_eq!(slice, [(0, 'b'), (0, 'd'), (1, 'a'), (1, 'c'), (2, 'e')]);
+/// ```
+pub fn tim_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    fn merge_at<T, F>(
+        slice: &mut [T],
+        runs: &mut Vec<(usize, usize)>,
+        i: usize,
+        buf: &mut Vec<T>,
+        is_less: &mut F,
+    ) where
+        T: Clone,
+        F: FnMut(&T, &T) -> bool,
+    {
+        let (start, a) = runs[i];
+        let (_, b) = runs[i + 1];
+        merge_galloping(&mut slice[start..(start + a + b)], a, buf, is_less);
+        runs[i] = (start, a + b);
+        runs.remove(i + 1);
+    }
+
+    let mut is_less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+    let n = slice.len();
+    let min_run = min_run(n);
+
+    let mut buf = Vec::new();
+    // (start, length) of the runs
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+
+    let mut start = 0;
+    while start < n {
+        let mut len = run_len(&mut slice[start..], &mut is_less);
+        if len < min_run {
+            len = min_run.min(n - start);
+            insertion_by_less(&mut slice[start..(start + len)], &mut is_less);
+        }
+        runs.push((start, len));
+        start += len;
+
+        loop {
+            let k = runs.len();
+            if k < 2 {
+                break;
+            }
+
+            let c = runs[k - 1].1;
+            let b = runs[k - 2].1;
+            if (k >= 3 && runs[k - 3].1 <= b + c) || (k >= 4 && runs[k - 4].1 <= runs[k - 3].1 + b)
+            {
+                if runs[k - 3].1 < c {
+                    merge_at(slice, &mut runs, k - 3, &mut buf, &mut is_less);
+                } else {
+                    merge_at(slice, &mut runs, k - 2, &mut buf, &mut is_less);
+                }
+            } else if b <= c {
+                merge_at(slice, &mut runs, k - 2, &mut buf, &mut is_less);
+            } else {
+                break;
+            }
+        }
+    }
+
+    while runs.len() > 1 {
+        let k = runs.len();
+        if k >= 3 && runs[k - 3].1 < runs[k - 1].1 {
+            merge_at(slice, &mut runs, k - 3, &mut buf, &mut is_less);
+        } else {
+            merge_at(slice, &mut runs, k - 2, &mut buf, &mut is_less);
+        }
+    }
+}
+
+/// Sorts a slice with [`tim`] sort, using a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [(2, 'a'), (1, 'b'), (2, 'c'), (0, 'd')];
+/// sort::tim_by_key(&mut slice, |p| p.0);
+/// assert_eq!(slice, [(0, 'd'), (1, 'b'), (2, 'a'), (2, 'c')]);
+/// ```
+pub fn tim_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    T: Clone,
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    tim_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`tim`] sort. Equal elements keep
+/// their original order.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::tim_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn tim_desc<T: Ord + Clone>(slice: &mut [T]) {
+    tim_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`tim`] sort, leaving the other elements
+/// untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 2, 1, 0, -1];
+/// sort::tim_range(&mut slice, ..3);
+/// assert_eq!(slice, [1, 2, 3, 0, -1]);
+/// ```
+pub fn tim_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord + Clone,
+    R: RangeBounds<usize>,
+{
+    tim(&mut slice[bounds(range)]);
+}
+
+/// Floating-point types, which can be sorted by their total order.
+///
+/// Floats are only [`PartialOrd`], because NaN is not comparable to anything,
+/// so they can't be sorted by the [`Ord`] sorts. The `_total` sorts use
+/// [`total_cmp`](Float::total_cmp) instead.
+pub trait Float: Copy + PartialOrd {
+    /// Compares two values by the IEEE 754 total order: `-0.0` is smaller than
+    /// `0.0`, and NaNs are placed at the ends, depending on their sign.
+    fn total_cmp(&self, other: &Self) -> Ordering;
+
+    /// Returns `true` if the value is NaN.
+    fn is_nan(self) -> bool;
+}
+
+macro_rules! impl_float {
+    ($($t:ty),*) => {
+        $(
+            impl Float for $t {
+                fn total_cmp(&self, other: &Self) -> Ordering {
+                    <$t>::total_cmp(self, other)
+                }
+
+                fn is_nan(self) -> bool {
+                    <$t>::is_nan(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_float!(f32, f64);
+
+/// Sorts a slice of floats by their total order with [`quick`] sort.
+///
+/// See [`Float::total_cmp`] for the order of zeros and NaNs.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [2.5, -1.0, f64::INFINITY, 0.0, -0.0];
+/// sort::quick_total(&mut slice);
+/// assert_eq!(slice, [-1.0, -0.0, 0.0, 2.5, f64::INFINITY]);
+/// ```
+pub fn quick_total<T: Float>(slice: &mut [T]) {
+    quick_by(slice, T::total_cmp);
+}
+
+/// Sorts a slice of floats by their total order with [`intro`] sort.
+///
+/// See [`Float::total_cmp`] for the order of zeros and NaNs.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [0.5f32, f32::NAN, -3.0, 1e10];
+/// sort::intro_total(&mut slice);
+/// assert_eq!(&slice[..3], [-3.0, 0.5, 1e10]);
+/// assert!(slice[3].is_nan());
+/// ```
+pub fn intro_total<T: Float>(slice: &mut [T]) {
+    intro_by(slice, T::total_cmp);
+}
+
+/// Sorts a slice of floats by their total order with [`merge`] sort.
+///
+/// See [`Float::total_cmp`] for the order of zeros and NaNs.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3.25, -7.5, 0.0, 3.0];
+/// sort::merge_total(&mut slice);
+/// assert_eq!(slice, [-7.5, 0.0, 3.0, 3.25]);
+/// ```
+pub fn merge_total<T: Float>(slice: &mut [T]) {
+    merge_by(slice, T::total_cmp);
+}
+
+/// Sorts a slice of floats by their total order with [`tim`] sort.
+///
+/// See [`Float::total_cmp`] for the order of zeros and NaNs.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [1.0, 2.0, 3.0, -f64::NAN, 0.5];
+/// sort::tim_total(&mut slice);
+/// assert!(slice[0].is_nan());
+/// assert_eq!(&slice[1..], [0.5, 1.0, 2.0, 3.0]);
+/// ```
+pub fn tim_total<T: Float>(slice: &mut [T]) {
+    tim_by(slice, T::total_cmp);
+}
+
+/// Where [`floats`] puts NaNs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NanPolicy {
+    /// Moves all NaNs to the start of the slice.
+    First,
+    /// Moves all NaNs to the end of the slice.
+    Last,
+    /// Fails with a [`NanError`] if the slice contains a NaN.
+    Error,
+}
+
+/// The error returned by [`floats`] when a NaN is found with
+/// [`NanPolicy::Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NanError {
+    /// The position of the first NaN in the slice.
+    pub index: usize,
+}
+
+impl std::fmt::Display for NanError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "NaN found at position {}", self.index)
+    }
+}
+
+impl std::error::Error for NanError {}
+
+/// Sorts a slice of floats with the given algorithm, placing NaNs according to
+/// `policy`.
+///
+/// The NaNs are first moved to one end of the slice with [`partition_stable`],
+/// regardless of their sign, and then `sort` is invoked on the rest. With
+/// [`NanPolicy::Error`] the slice is left untouched if it contains a NaN.
+///
+/// # Errors
+///
+/// Returns a [`NanError`] with the position of the first NaN, if the slice
+/// contains any and the policy is [`NanPolicy::Error`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort::{self, NanPolicy};
+///
+/// let mut slice = [2.0, f64::NAN, -1.0, -f64::NAN, 0.5];
+/// sort::floats(&mut slice, NanPolicy::Last, sort::quick_total).unwrap();
+/// assert_eq!(&slice[..3], [-1.0, 0.5, 2.0]);
+/// assert!(slice[3..].iter().all(|x| x.is_nan()));
+///
+/// let err = sort::floats(&mut slice, NanPolicy::Error, sort::quick_total);
+/// assert_eq!(err.unwrap_err().index, 3);
+/// ```
+pub fn floats<T, F>(slice: &mut [T], policy: NanPolicy, sort: F) -> Result<(), NanError>
+where
+    T: Float,
+    F: FnOnce(&mut [T]),
+{
+    match policy {
+        NanPolicy::First => {
+            let nans = partition_stable(slice, |x| x.is_nan());
+            sort(&mut slice[nans..]);
+        }
+        NanPolicy::Last => {
+            let numbers = partition_stable(slice, |x| !x.is_nan());
+            sort(&mut slice[..numbers]);
+        }
+        NanPolicy::Error => {
+            if let Some(index) = slice.iter().position(|x| x.is_nan()) {
+                return Err(NanError { index });
+            }
+            sort(slice);
+        }
+    }
+
+    Ok(())
+}
+
+/// An implementation of tag sort.
+///
+/// Extracts a `(key, index)` tag for every record, sorts only the tags with
+/// [`intro`] sort and returns the resulting permutation: the `i`-th element of
+/// it is the position of the `i`-th smallest record. The records themselves are
+/// never moved, which is useful when they are large and cheap keys are
+/// available.
+///
+/// Records with equal keys keep their relative order, because the indices in
+/// the tags are unique.
+///
+/// See also [`tag_sort_gather`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let records = ["pear", "fig", "banana", "kiwi"];
+/// let perm = sort::tag_sort(&records, |r| r.len());
+/// assert_eq!(perm, [1, 0, 3, 2]);
+/// ```
+pub fn tag_sort<T, K, F>(records: &[T], mut key_fn: F) -> Vec<usize>
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    let mut tags: Vec<(K, usize)> = records
+        .iter()
+        .enumerate()
+        .map(|(i, r)| (key_fn(r), i))
+        .collect();
+
+    intro(&mut tags);
+    tags.into_iter().map(|(_, i)| i).collect()
+}
+
+/// An implementation of tag sort that gathers the records into a new [`Vec`].
+///
+/// Computes the permutation with [`tag_sort`] and clones every record exactly
+/// once into its final position.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let records = ["pear", "fig", "banana", "kiwi"];
+/// let sorted = sort::tag_sort_gather(&records, |r| r.len());
+/// assert_eq!(sorted, ["fig", "pear", "kiwi", "banana"]);
+/// ```
+pub fn tag_sort_gather<T, K, F>(records: &[T], key_fn: F) -> Vec<T>
+where
+    T: Clone,
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    tag_sort(records, key_fn)
+        .into_iter()
+        .map(|i| records[i].clone())
+        .collect()
+}
+
+/// Returns the positions of the elements of a slice in sorted order, without
+/// changing the slice.
+///
+/// The `i`-th element of the result is the position of the `i`-th smallest
+/// element, and equal elements come in their original order. The positions
+/// are sorted with [`merge`] sort, comparing the elements they point to.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let slice = [30, 10, 20, 10];
+/// assert_eq!(sort::argsort(&slice), [1, 3, 2, 0]);
+/// ```
+pub fn argsort<T: Ord>(slice: &[T]) -> Vec<usize> {
+    argsort_by(slice, T::cmp)
+}
+
+/// Like [`argsort`], but compares the elements with a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let slice = [30, 10, 20, 10];
+/// assert_eq!(sort::argsort_by(&slice, |a, b| b.cmp(a)), [0, 2, 1, 3]);
+/// ```
+pub fn argsort_by<T, F>(slice: &[T], mut compare: F) -> Vec<usize>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut indices: Vec<_> = (0..slice.len()).collect();
+    merge_by(&mut indices, |&a, &b| compare(&slice[a], &slice[b]));
+    indices
+}
+
+/// Like [`argsort`], but compares the keys extracted by `key_fn`.
+///
+/// The key is computed on every comparison; see [`tag_sort`] for computing
+/// it only once for every element.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let slice = ["pear", "fig", "banana", "kiwi"];
+/// assert_eq!(sort::argsort_by_key(&slice, |s| s.len()), [1, 0, 3, 2]);
+/// ```
+pub fn argsort_by_key<T, K, F>(slice: &[T], mut key_fn: F) -> Vec<usize>
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    argsort_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)))
+}
+
+/// Reorders a slice by a permutation in place, so that the `i`-th element
+/// becomes the one on position `perm[i]`.
+///
+/// This is the order of the permutations returned by [`argsort`] and
+/// [`tag_sort`], so the same permutation can reorder many associated slices.
+/// The elements are moved by following the cycles of `perm` directly, which
+/// takes `O(n)` time and at most `n - 1` swaps. The visited positions are
+/// marked in a `Vec<bool>`, so it allocates `n` bytes.
+///
+/// # Panics
+///
+/// Panics if `perm` isn't a permutation of the positions of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let ages = [41, 12, 30];
+/// let mut names = ["Ann", "Bob", "Cid"];
+/// sort::apply_permutation(&mut names, &sort::argsort(&ages));
+/// assert_eq!(names, ["Bob", "Cid", "Ann"]);
+/// ```
+pub fn apply_permutation<T>(slice: &mut [T], perm: &[usize]) {
+    assert_eq!(
+        slice.len(),
+        perm.len(),
+        "the permutation has a wrong length"
+    );
+
+    let mut visited = vec![false; perm.len()];
+    for start in 0..slice.len() {
+        if visited[start] {
+            continue;
+        }
+
+        // every position takes the element of the next one in the cycle
+        let mut i = start;
+        loop {
+            visited[i] = true;
+            let next = perm[i];
+            assert!(
+                next < perm.len() && (next == start || !visited[next]),
+                "not a permutation"
+            );
+            if next == start {
+                break;
+            }
+            slice.swap(i, next);
+            i = next;
+        }
+    }
+}
+
+/// Returns the inverse of a permutation: if `perm[i]` is `j`, the `j`-th
+/// element of the result is `i`.
+///
+/// # Panics
+///
+/// Panics if `perm` isn't a permutation of `0..perm.len()`.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// // the ranks of the elements
+/// let slice = [30, 10, 20];
+/// assert_eq!(sort::invert_permutation(&sort::argsort(&slice)), [2, 0, 1]);
+/// ```
+pub fn invert_permutation(perm: &[usize]) -> Vec<usize> {
+    let mut inverse = vec![usize::MAX; perm.len()];
+    for (i, &p) in perm.iter().enumerate() {
+        assert!(
+            p < perm.len() && inverse[p] == usize::MAX,
+            "not a permutation"
+        );
+        inverse[p] = i;
+    }
+
+    inverse
+}
+
+/// Sorts a slice by a key, computing the key only once for every element.
+///
+/// Like [`tag_sort`], the `(key, index)` tags are sorted (with [`intro`] sort,
+/// which is enough since the indices are unique), and then the elements are
+/// moved to their positions by following the cycles of the permutation. It's
+/// useful when the key is expensive to compute, e.g. when it allocates; a
+/// `_by_key` sort would compute it on every comparison.
+///
+/// The sort is stable.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = ["Pear", "apple", "Fig", "banana"];
+/// sort::by_cached_key(&mut slice, |s| s.to_lowercase());
+/// assert_eq!(slice, ["apple", "banana", "Fig", "Pear"]);
+/// ```
+pub fn by_cached_key<T, K, F>(slice: &mut [T], key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    let mut tags: Vec<(K, usize)> = slice
+        .iter()
+        .map(key_fn)
+        .enumerate()
+        .map(|(i, k)| (k, i))
+        .collect();
+    intro(&mut tags);
+
+    let mut dest = vec![0; slice.len()];
+    for (pos, (_, i)) in tags.into_iter().enumerate() {
+        dest[i] = pos;
+    }
+    move_to(slice, dest);
+}
+
+/// Unsigned integer types that can be used as keys in counting sorts.
+///
+/// The values are converted to [`usize`] and used directly as positions in a
+/// histogram, so they should be reasonably small.
+pub trait Unsigned: Copy + Ord {
+    /// Converts the value into a histogram position.
+    fn to_usize(self) -> usize;
+
+    /// Converts a histogram position back into a value.
+    fn from_usize(value: usize) -> Self;
+}
+
+macro_rules! impl_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Unsigned for $t {
+                fn to_usize(self) -> usize {
+                    self as usize
+                }
+
+                fn from_usize(value: usize) -> Self {
+                    value as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned!(u8, u16, u32, u64, usize);
+
+/// Overwrites the slice with the values counted in `histogram`, in order. The
+/// `k`-th element of the histogram is the count of `offset + k`.
+fn fill_from_histogram<T: Unsigned>(slice: &mut [T], histogram: &[usize], offset: usize) {
+    let mut pos = 0;
+    for (k, &count) in histogram.iter().enumerate() {
+        for v in &mut slice[pos..(pos + count)] {
+            *v = T::from_usize(offset + k);
+        }
+        pos += count;
+    }
+}
+
+/// An implementation of counting sort that also returns the histogram.
+///
+/// Counts the occurrences of every value and then overwrites the slice with
+/// the values in order. The returned histogram has `max + 1` entries, and its
+/// `k`-th element is the number of occurrences of `k` in the slice.
+///
+/// It does only two passes over the slice, but allocates memory proportional
+/// to the largest value.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3u8, 1, 0, 3, 1, 3];
+/// let histogram = sort::counting_with_histogram(&mut slice);
+/// assert_eq!(slice, [0, 1, 1, 3, 3, 3]);
+/// assert_eq!(histogram, [1, 2, 0, 3]);
+/// ```
+pub fn counting_with_histogram<T: Unsigned>(slice: &mut [T]) -> Vec<usize> {
+    let n_buckets = match min_max(slice) {
+        Some((_, max)) => max.to_usize() + 1,
+        None => return Vec::new(),
+    };
+    let histogram = histogram(slice, |v| v.to_usize(), n_buckets);
+    fill_from_histogram(slice, &histogram, 0);
+
+    histogram
+}
+
+/// An implementation of counting sort.
+///
+/// Works like [`counting_with_histogram`], but discards the histogram. It's
+/// much faster than comparison sorts when the values are small.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [4u16, 0, 7, 4, 1, 2];
+/// sort::counting(&mut slice);
+/// assert_eq!(slice, [0, 1, 2, 4, 4, 7]);
+/// ```
+pub fn counting<T: Unsigned>(slice: &mut [T]) {
+    counting_with_histogram(slice);
+}
+
+/// Moves the elements of a slice to buckets given by `key_fn`, in a stable way.
+/// Returns the number of elements in every bucket.
+fn distribute<T, F>(slice: &mut [T], key_fn: F, n_buckets: usize) -> Vec<usize>
+where
+    F: FnMut(&T) -> usize,
+{
+    let keys: Vec<usize> = slice.iter().map(key_fn).collect();
+    let histogram = histogram(&keys, |&k| k, n_buckets);
+
+    // the first position of every key
+    let mut next = Vec::with_capacity(histogram.len());
+    let mut pos = 0;
+    for &count in &histogram {
+        next.push(pos);
+        pos += count;
+    }
+
+    let dest: Vec<usize> = keys
+        .iter()
+        .map(|&k| {
+            next[k] += 1;
+            next[k] - 1
+        })
+        .collect();
+    move_to(slice, dest);
+
+    histogram
+}
+
+/// Moves every element `slice[i]` to position `dest[i]`, by following the
+/// cycles of the permutation. It takes at most `n - 1` swaps.
+fn move_to<T>(slice: &mut [T], mut dest: Vec<usize>) {
+    for i in 0..slice.len() {
+        while dest[i] != i {
+            let d = dest[i];
+            slice.swap(i, d);
+            dest.swap(i, d);
+        }
+    }
+}
+
+/// An implementation of counting sort by a key.
+///
+/// Computes every key once with `key_fn`, counts the occurrences of every key
+/// and computes the final position of every element from the counts. The
+/// elements are then moved to their positions by following the cycles of the
+/// permutation, which takes at most `n - 1` swaps.
+///
+/// The sort is stable. It allocates memory proportional to `max_key` and the
+/// length of the slice.
+///
+/// # Panics
+///
+/// Panics if `key_fn` returns a key greater than `max_key`.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [("c", 2), ("a", 0), ("b", 1), ("d", 0)];
+/// sort::counting_by_key(&mut slice, |p| p.1, 2);
+/// assert_eq!(slice, [("a", 0), ("d", 0), ("b", 1), ("c", 2)]);
+/// ```
+pub fn counting_by_key<T, F>(slice: &mut [T], key_fn: F, max_key: usize)
+where
+    F: FnMut(&T) -> usize,
+{
+    distribute(slice, key_fn, max_key + 1);
+}
+
+/// An implementation of pigeonhole sort.
+///
+/// Finds the smallest and the largest value with [`min_max`], and counts every
+/// value in a hole relative to the smallest one. Then writes the values back
+/// in order. Unlike [`counting`] sort, the memory used is proportional to the
+/// range of the values, not to the largest value.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [1003u32, 1000, 1007, 1003, 1001];
+/// sort::pigeonhole(&mut slice);
+/// assert_eq!(slice, [1000, 1001, 1003, 1003, 1007]);
+/// ```
+pub fn pigeonhole<T: Unsigned>(slice: &mut [T]) {
+    let (min, max) = match min_max(slice) {
+        Some((min, max)) => (min.to_usize(), max.to_usize()),
+        None => return,
+    };
+
+    let holes = histogram(slice, |v| v.to_usize() - min, max - min + 1);
+    fill_from_histogram(slice, &holes, min);
+}
+
+/// An implementation of bead (gravity) sort.
+///
+/// Every value is a row of beads on vertical poles, one bead per unit. The
+/// beads fall down, so the number of beads on every pole is counted, and then
+/// every row is rebuilt from the poles that still have a bead on its height.
+///
+/// Uses one counter per pole, so the memory is `O(m)` and the time is `O(n·m)`,
+/// where `m` is the largest value in the slice. It's only practical for small
+/// values.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3u8, 0, 4, 1, 3];
+/// sort::bead(&mut slice);
+/// assert_eq!(slice, [0, 1, 3, 3, 4]);
+/// ```
+pub fn bead<T: Unsigned>(slice: &mut [T]) {
+    let max = match slice.iter().max() {
+        Some(max) => max.to_usize(),
+        None => return,
+    };
+
+    let mut poles = vec![0; max];
+    for v in slice.iter() {
+        for beads in &mut poles[..v.to_usize()] {
+            *beads += 1;
+        }
+    }
+
+    // the poles are sorted by height, and after the fall the bottom row is
+    // the last element of the slice
+    for (row, v) in slice.iter_mut().rev().enumerate() {
+        let len = poles.iter().take_while(|&&beads| beads > row).count();
+        *v = T::from_usize(len);
+    }
+}
+
+/// Types that can be sorted by radix sorts.
+///
+/// Every value is mapped to a key of [`BYTES`](Radix::BYTES) bytes, so that
+/// comparing the keys as unsigned integers gives the order of the values.
+///
+/// Floats are ordered like by [`f64::total_cmp`]: `-0.0` is smaller than `0.0`,
+/// and NaNs are placed at the ends, depending on their sign.
+pub trait Radix: Copy {
+    /// The number of bytes of the key.
+    const BYTES: usize;
+
+    /// Returns the `i`-th least significant byte of the key.
+    fn radix_byte(self, i: usize) -> u8;
+}
+
+macro_rules! impl_radix_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Radix for $t {
+                const BYTES: usize = std::mem::size_of::<$t>();
+
+                fn radix_byte(self, i: usize) -> u8 {
+                    (self >> (8 * i)) as u8
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_unsigned!(u8, u16, u32, u64, usize);
+
+macro_rules! impl_radix_signed {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            impl Radix for $t {
+                const BYTES: usize = std::mem::size_of::<$t>();
+
+                fn radix_byte(self, i: usize) -> u8 {
+                    // flipping the sign bit moves the negative values below
+                    // the positive ones
+                    let key = (self as $u) ^ (1 << (<$u>::BITS - 1));
+                    (key >> (8 * i)) as u8
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize);
+
+macro_rules! impl_radix_float {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            impl Radix for $t {
+                const BYTES: usize = std::mem::size_of::<$t>();
+
+                fn radix_byte(self, i: usize) -> u8 {
+                    // the total order of floats: the sign bit of positive
+                    // values is flipped, and all the bits of negative ones
+                    let bits = self.to_bits();
+                    let sign = 1 << (<$u>::BITS - 1);
+                    let key = if bits & sign == 0 { bits ^ sign } else { !bits };
+                    (key >> (8 * i)) as u8
+                }
+            }
+        )*
+    };
+}
+
+impl_radix_float!(f32 => u32, f64 => u64);
+
+/// Distributes the elements of `src` into `dst` by their `byte`-th key byte, in
+/// a stable way. Returns `false` and does nothing if all the bytes are equal.
+fn radix_pass<T: Radix>(src: &[T], dst: &mut [T], byte: usize) -> bool {
+    let counts = histogram(src, |v| v.radix_byte(byte) as usize, 256);
+    if counts.contains(&src.len()) {
+        return false;
+    }
+
+    let mut next = [0; 256];
+    let mut pos = 0;
+    for (b, count) in counts.into_iter().enumerate() {
+        next[b] = pos;
+        pos += count;
+    }
+
+    for &v in src {
+        let b = v.radix_byte(byte) as usize;
+        dst[next[b]] = v;
+        next[b] += 1;
+    }
+
+    true
+}
+
+/// An implementation of LSD (least significant digit) radix sort.
+///
+/// Sorts the slice by every byte of the keys, starting from the least
+/// significant one, with stable counting passes into a buffer of the same
+/// length. Passes in which all the elements have the same byte are skipped.
+///
+/// It does `O(n * BYTES)` work and no comparisons at all.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [70000u32, 3, 256, 255, 0, 70000];
+/// sort::radix_lsd(&mut slice);
+/// assert_eq!(slice, [0, 3, 255, 256, 70000, 70000]);
+///
+/// let mut slice = [5i16, -300, 0, i16::MIN, -1];
+/// sort::radix_lsd(&mut slice);
+/// assert_eq!(slice, [i16::MIN, -300, -1, 0, 5]);
+///
+/// let mut slice = [2.5, -0.5, f64::INFINITY, 0.0, -3.0];
+/// sort::radix_lsd(&mut slice);
+/// assert_eq!(slice, [-3.0, -0.5, 0.0, 2.5, f64::INFINITY]);
+/// ```
+pub fn radix_lsd<T: Radix>(slice: &mut [T]) {
+    let mut buf = slice.to_vec();
+    let mut in_buf = false;
+
+    for byte in 0..T::BYTES {
+        let moved = if in_buf {
+            radix_pass(&buf, slice, byte)
+        } else {
+            radix_pass(slice, &mut buf, byte)
+        };
+
+        if moved {
+            in_buf = !in_buf;
+        }
+    }
+
+    if in_buf {
+        slice.copy_from_slice(&buf);
+    }
+}
+
+/// Like [`radix_pass`], but counts and distributes the chunks of `chunk_len`
+/// elements of `src` in parallel.
+#[cfg(feature = "rayon")]
+fn par_radix_pass<T>(src: &[T], dst: &mut [T], byte: usize, chunk_len: usize) -> bool
+where
+    T: Radix + Send + Sync,
+{
+    use rayon::prelude::*;
+
+    let key = |v: &T| v.radix_byte(byte) as usize;
+    let counts: Vec<_> = src
+        .par_chunks(chunk_len)
+        .map(|chunk| histogram(chunk, key, 256))
+        .collect();
+
+    let mut totals = [0; 256];
+    for chunk_counts in &counts {
+        for (total, count) in totals.iter_mut().zip(chunk_counts) {
+            *total += count;
+        }
+    }
+    if totals.contains(&src.len()) {
+        return false;
+    }
+
+    // every chunk gets its own part of every bucket, in the order of the
+    // chunks, which keeps the pass stable
+    let mut parts: Vec<Vec<&mut [T]>> = counts.iter().map(|_| Vec::with_capacity(256)).collect();
+    let mut rest = dst;
+    for b in 0..256 {
+        for (chunk_parts, chunk_counts) in parts.iter_mut().zip(&counts) {
+            let (part, tail) = mem::take(&mut rest).split_at_mut(chunk_counts[b]);
+            chunk_parts.push(part);
+            rest = tail;
+        }
+    }
+
+    src.par_chunks(chunk_len)
+        .zip(parts)
+        .for_each(|(chunk, mut chunk_parts)| {
+            let mut next = [0; 256];
+            for &v in chunk {
+                let b = key(&v);
+                chunk_parts[b][next[b]] = v;
+                next[b] += 1;
+            }
+        });
+
+    true
+}
+
+/// Sorts a slice with [`radix_lsd`] sort in parallel, on the global thread
+/// pool of the `rayon` crate.
+///
+/// Every pass splits the slice into a chunk per thread, counts the bytes of
+/// the chunks in parallel, and then moves the elements of every chunk to its
+/// own part of every bucket in parallel. Chunks have at least 4096 elements;
+/// see [`par_radix_lsd_with`] to change this limit.
+/// Available only with the `rayon` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: Vec<u64> = (0..100_000).map(|x| (x * 7919) % 100_003).collect();
+/// sort::par_radix_lsd(&mut slice);
+/// assert!(sort::test(&slice));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_radix_lsd<T: Radix + Send + Sync>(slice: &mut [T]) {
+    par_radix_lsd_with(slice, &SortConfig::new());
+}
+
+/// Sorts a slice with [`par_radix_lsd`] sort, tuned by a [`SortConfig`].
+///
+/// Only the parallelism threshold applies, as the minimal length of a chunk.
+/// Available only with the `rayon` feature.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort::{self, SortConfig};
+///
+/// let mut slice: Vec<u32> = (0..10_000).rev().collect();
+/// sort::par_radix_lsd_with(&mut slice, &SortConfig::new().par_threshold(256));
+/// assert!(slice.iter().copied().eq(0..10_000));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_radix_lsd_with<T: Radix + Send + Sync>(slice: &mut [T], config: &SortConfig) {
+    let chunk_len = config
+        .par_threshold
+        .max(slice.len() / rayon::current_num_threads() + 1);
+    let mut buf = slice.to_vec();
+    let mut in_buf = false;
+
+    for byte in 0..T::BYTES {
+        let moved = if in_buf {
+            par_radix_pass(&buf, slice, byte, chunk_len)
+        } else {
+            par_radix_pass(slice, &mut buf, byte, chunk_len)
+        };
+
+        if moved {
+            in_buf = !in_buf;
+        }
+    }
+
+    if in_buf {
+        slice.copy_from_slice(&buf);
+    }
+}
+
+/// Sorts a slice with [`par_radix_lsd_with`] on the given thread pool, instead
+/// of the global one. Available only with the `rayon` feature.
+///
+/// # Examples
+/// ```
+/// use rayon::ThreadPoolBuilder;
+/// use search_sort::sort::{self, SortConfig};
+///
+/// let pool = ThreadPoolBuilder::new().num_threads(2).build().unwrap();
+/// let mut slice: Vec<u32> = (0..10_000).rev().collect();
+/// sort::par_radix_lsd_in(&mut slice, &pool, &SortConfig::new());
+/// assert!(slice.iter().copied().eq(0..10_000));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_radix_lsd_in<T>(slice: &mut [T], pool: &rayon::ThreadPool, config: &SortConfig)
+where
+    T: Radix + Send + Sync,
+{
+    pool.install(|| par_radix_lsd_with(slice, config));
+}
+
+/// The length of buckets, below which [`radix_bytes`] sort uses [`insertion`]
+/// sort.
+const MSD_INSERTION_LEN: usize = 32;
+
+/// The number of nested buckets, after which MSD radix sorts fall back to
+/// [`intro`] sort, so that the recursion can't overflow the stack.
+const MSD_MAX_LEVELS: usize = 64;
+
+/// Sorts byte strings that share the first `depth` bytes by MSD radix sort,
+/// using `buf` as scratch space. `level` is the number of buckets, in which
+/// the slice is nested.
+fn radix_msd<T>(slice: &mut [T], buf: &mut [T], mut depth: usize, level: usize)
+where
+    T: AsRef<[u8]> + Copy + Ord,
+{
+    if slice.len() <= MSD_INSERTION_LEN {
+        insertion(slice);
+        return;
+    } else if level >= MSD_MAX_LEVELS {
+        intro(slice);
+        return;
+    }
+
+    // the strings that end at this depth go first
+    let counts = loop {
+        let key = |s: &T| s.as_ref().get(depth).map_or(0, |&b| b as usize + 1);
+        let counts = histogram(slice, key, 257);
+        if counts[0] == slice.len() {
+            // all the strings are equal
+            return;
+        } else if !counts.contains(&slice.len()) {
+            break counts;
+        }
+
+        // skip the byte shared by all the strings without recursion
+        depth += 1;
+    };
+    let key = |s: &T| s.as_ref().get(depth).map_or(0, |&b| b as usize + 1);
+
+    let mut next = [0; 257];
+    let mut pos = 0;
+    for (b, &count) in counts.iter().enumerate() {
+        next[b] = pos;
+        pos += count;
+    }
+
+    let buf = &mut buf[..slice.len()];
+    for &s in slice.iter() {
+        let b = key(&s);
+        buf[next[b]] = s;
+        next[b] += 1;
+    }
+    slice.copy_from_slice(buf);
+
+    let mut start = counts[0];
+    for &count in &counts[1..] {
+        if count > 1 {
+            let bucket = &mut slice[start..(start + count)];
+            radix_msd(bucket, buf, depth + 1, level + 1);
+        }
+        start += count;
+    }
+}
+
+/// An implementation of MSD (most significant digit) radix sort for byte
+/// strings.
+///
+/// Distributes the strings into buckets by their first byte, and then sorts
+/// every bucket recursively by the next byte. The shared prefixes are never
+/// compared again, which makes it faster than comparison sorts for many
+/// strings with common prefixes. Small buckets are sorted with [`insertion`]
+/// sort.
+///
+/// The bytes shared by all the strings of a bucket are skipped without
+/// recursion, and buckets nested too deeply are sorted with [`intro`] sort, so
+/// long common prefixes can't overflow the stack.
+///
+/// See also [`radix_str`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: [&[u8]; 4] = [b"abc", b"ab", b"b", b"abd"];
+/// sort::radix_bytes(&mut slice);
+/// assert_eq!(slice, [&b"ab"[..], b"abc", b"abd", b"b"]);
+/// ```
+pub fn radix_bytes(slice: &mut [&[u8]]) {
+    let mut buf = slice.to_vec();
+    radix_msd(slice, &mut buf, 0, 0);
+}
+
+/// An implementation of MSD radix sort for strings.
+///
+/// Works like [`radix_bytes`]; the strings are ordered by their UTF-8 bytes,
+/// which is the same as the order of [`str`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = ["pear", "peach", "apple", "pea"];
+/// sort::radix_str(&mut slice);
+/// assert_eq!(slice, ["apple", "pea", "peach", "pear"]);
+/// ```
+pub fn radix_str(slice: &mut [&str]) {
+    let mut buf = slice.to_vec();
+    radix_msd(slice, &mut buf, 0, 0);
+}
+
+/// Returns the whole radix key of a value, for types of at most 8 bytes.
+fn radix_key<T: Radix>(v: T) -> u64 {
+    (0..T::BYTES).fold(0, |key, i| key | (v.radix_byte(i) as u64) << (8 * i))
+}
+
+/// Compares two values by their radix keys.
+fn radix_cmp<T: Radix>(a: T, b: T) -> Ordering {
+    for i in (0..T::BYTES).rev() {
+        match a.radix_byte(i).cmp(&b.radix_byte(i)) {
+            Ordering::Equal => {}
+            ord => return ord,
+        }
+    }
+
+    Ordering::Equal
+}
+
+/// Moves the elements of a slice to buckets given by `key_fn` in place, by
+/// following the cycles of swaps. `counts` is the number of elements in every
+/// bucket. The order within the buckets is not preserved.
+fn permute_to_buckets<T, F>(slice: &mut [T], counts: &[usize], mut key_fn: F)
+where
+    F: FnMut(&T) -> usize,
+{
+    let mut heads = Vec::with_capacity(counts.len());
+    let mut tails = Vec::with_capacity(counts.len());
+    let mut pos = 0;
+    for &count in counts {
+        heads.push(pos);
+        pos += count;
+        tails.push(pos);
+    }
+
+    for b in 0..counts.len() {
+        while heads[b] < tails[b] {
+            let target = key_fn(&slice[heads[b]]);
+            if target == b {
+                heads[b] += 1;
+            } else {
+                slice.swap(heads[b], heads[target]);
+                heads[target] += 1;
+            }
+        }
+    }
+}
+
+/// An implementation of American flag sort.
+///
+/// An in-place variant of MSD radix sort: counts the elements with every value
+/// of the most significant byte, and then moves them directly to their buckets
+/// by following the cycles of swaps. Every bucket is then sorted recursively by
+/// the next byte. Buckets of at most 32 elements are sorted by insertion.
+///
+/// Unlike [`radix_lsd`], it doesn't allocate a buffer, but it's not stable.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [513u16, 2, 65535, 512, 0, 2];
+/// sort::american_flag(&mut slice);
+/// assert_eq!(slice, [0, 2, 2, 512, 513, 65535]);
+/// ```
+pub fn american_flag<T: Radix>(slice: &mut [T]) {
+    fn inner<T: Radix>(slice: &mut [T], byte: usize) {
+        if slice.len() <= MSD_INSERTION_LEN {
+            insertion_by_less(slice, |&a, &b| radix_cmp(a, b) == Ordering::Less);
+            return;
+        }
+
+        let key = |v: &T| v.radix_byte(byte) as usize;
+        let counts = histogram(slice, key, 256);
+        permute_to_buckets(slice, &counts, key);
+
+        if byte > 0 {
+            let mut start = 0;
+            for count in counts {
+                if count > 1 {
+                    inner(&mut slice[start..(start + count)], byte - 1);
+                }
+                start += count;
+            }
+        }
+    }
+
+    if T::BYTES > 0 {
+        inner(slice, T::BYTES - 1);
+    }
+}
+
+/// An implementation of bucket sort.
+///
+/// Moves every element to one of `n_buckets` buckets given by `to_bucket`, and
+/// then sorts every bucket by insertion. The buckets must follow the order of
+/// the elements: a smaller element can't be put in a later bucket. If the
+/// elements are spread evenly between the buckets, it's done in `O(n)` time.
+/// The sort is stable.
+///
+/// The elements only have to be [`PartialOrd`], but all of them have to be
+/// comparable, e.g. there can't be any NaNs.
+///
+/// See also [`bucket_uniform`].
+///
+/// # Panics
+///
+/// Panics if `to_bucket` returns a bucket greater or equal to `n_buckets`.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [42, 7, 99, 13, 3, 58];
+/// sort::bucket(&mut slice, 10, |&x| x / 10);
+/// assert_eq!(slice, [3, 7, 13, 42, 58, 99]);
+/// ```
+pub fn bucket<T, F>(slice: &mut [T], n_buckets: usize, to_bucket: F)
+where
+    T: PartialOrd,
+    F: FnMut(&T) -> usize,
+{
+    let counts = distribute(slice, to_bucket, n_buckets);
+
+    let mut start = 0;
+    for count in counts {
+        insertion_by_less(&mut slice[start..(start + count)], |a, b| a < b);
+        start += count;
+    }
+}
+
+/// An implementation of bucket sort for floats uniformly distributed in
+/// `[0, 1)`.
+///
+/// Uses as many buckets as there are elements with [`bucket`] sort. Values
+/// outside of the range are put in the first or the last bucket, so they are
+/// still sorted, but slower. There can't be any NaNs.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [0.42, 0.07, 0.99, 0.13, 0.5];
+/// sort::bucket_uniform(&mut slice);
+/// assert_eq!(slice, [0.07, 0.13, 0.42, 0.5, 0.99]);
+/// ```
+pub fn bucket_uniform(slice: &mut [f64]) {
+    let n = slice.len();
+    bucket(slice, n, |&x| ((x * n as f64) as usize).min(n - 1));
+}
+
+/// An implementation of flashsort.
+///
+/// Finds the smallest and the largest key, and classifies every element into
+/// one of `0.43 * n` classes by linear interpolation between them. The
+/// elements are moved to their classes in place by cycles of swaps, and then
+/// the almost sorted slice is finished by insertion sort.
+///
+/// It works with the same types as radix sorts (see [`Radix`]), and it's fast
+/// when the values are distributed roughly uniformly. It's not stable.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [0.7, -1.5, 3.25, 0.0, 2.0, -0.5];
+/// sort::flash(&mut slice);
+/// assert_eq!(slice, [-1.5, -0.5, 0.0, 0.7, 2.0, 3.25]);
+/// ```
+pub fn flash<T: Radix>(slice: &mut [T]) {
+    let mut keys = slice.iter().map(|&v| radix_key(v));
+    let first = match keys.next() {
+        Some(key) => key,
+        None => return,
+    };
+    let (min, max) = keys.fold((first, first), |(min, max), k| (min.min(k), max.max(k)));
+    if min == max {
+        return;
+    }
+
+    let classes = (slice.len() * 43 / 100).max(2);
+    let class = |v: &T| {
+        let offset = (radix_key(*v) - min) as u128;
+        (offset * (classes - 1) as u128 / (max - min) as u128) as usize
+    };
+
+    let counts = histogram(slice, class, classes);
+    permute_to_buckets(slice, &counts, class);
+    insertion_by_less(slice, |&a, &b| radix_cmp(a, b) == Ordering::Less);
+}
+
+/// The length of buckets, below which [`spread`] sort switches to comparison
+/// sorting.
+const SPREAD_COMPARISON_LEN: usize = 256;
+
+/// The maximal number of bits used for bucketing in one pass of [`spread`]
+/// sort.
+const SPREAD_MAX_BITS: u32 = 11;
+
+/// An implementation of spreadsort.
+///
+/// A hybrid of radix and comparison sorting. It finds the range of the keys
+/// of the slice (see [`Radix`]) and distributes the elements in place into
+/// buckets by the most significant bits of their offsets in this range. The
+/// number of buckets depends on the length of the slice. Large buckets are
+/// sorted recursively the same way, with their own, narrower ranges, and
+/// buckets shorter than 256 elements are sorted by comparisons with heap sort
+/// (or insertion sort, if they are tiny).
+///
+/// Clustered data is handled well, because every bucket is split by its own
+/// range. It's not stable.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [1_000_000i64, -5, 12, 999_999, 0, -5, 3];
+/// sort::spread(&mut slice);
+/// assert_eq!(slice, [-5, -5, 0, 3, 12, 999_999, 1_000_000]);
+/// ```
+pub fn spread<T: Radix>(slice: &mut [T]) {
+    let is_less = |a: &T, b: &T| radix_cmp(*a, *b) == Ordering::Less;
+
+    if slice.len() <= MSD_INSERTION_LEN {
+        insertion_by_less(slice, is_less);
+        return;
+    } else if slice.len() <= SPREAD_COMPARISON_LEN {
+        heap_by_less(slice, is_less);
+        return;
+    }
+
+    let mut keys = slice.iter().map(|&v| radix_key(v));
+    let first = keys.next().unwrap();
+    let (min, max) = keys.fold((first, first), |(min, max), k| (min.min(k), max.max(k)));
+    if min == max {
+        return;
+    }
+
+    // the number of bits of the range, and of the buckets
+    let range_bits = u64::BITS - (max - min).leading_zeros();
+    let log_len = usize::BITS - 1 - slice.len().leading_zeros();
+    let bits = range_bits
+        .min(log_len.saturating_sub(2).max(1))
+        .min(SPREAD_MAX_BITS);
+
+    let shift = range_bits - bits;
+    let key = |v: &T| ((radix_key(*v) - min) >> shift) as usize;
+    let counts = histogram(slice, key, 1 << bits);
+    permute_to_buckets(slice, &counts, key);
+
+    let mut start = 0;
+    for count in counts {
+        if count > 1 {
+            spread(&mut slice[start..(start + count)]);
+        }
+        start += count;
+    }
+}
+
+/// Finds the smallest and the largest element of a slice.
+///
+/// Returns [`None`] if the slice is empty. Like [`Iterator::min`] and
+/// [`Iterator::max`], of several equal smallest elements the first one is
+/// returned, and of several equal largest elements the last one.
+///
+/// The elements are taken in pairs. The smaller element of a pair is only
+/// compared with the minimum and the larger one only with the maximum, so it
+/// takes about `3n/2` comparisons instead of `2n`.
+///
+/// This is the pre-pass of distribution sorts like [`counting_with_histogram`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// assert_eq!(sort::min_max(&[4, -2, 9, 0]), Some((&-2, &9)));
+/// assert_eq!(sort::min_max::<i32>(&[]), None);
+/// ```
+pub fn min_max<T: Ord>(slice: &[T]) -> Option<(&T, &T)> {
+    min_max_by(slice, T::cmp)
+}
+
+/// Like [`min_max`], but compares the elements with a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let words = ["fig", "banana", "kiwi", "apple"];
+/// let shortest_longest = sort::min_max_by(&words, |a, b| a.len().cmp(&b.len()));
+/// assert_eq!(shortest_longest, Some((&"fig", &"banana")));
+/// ```
+pub fn min_max_by<T, F>(slice: &[T], mut compare: F) -> Option<(&T, &T)>
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let (first, rest) = slice.split_first()?;
+    let (mut min, mut max, pairs) = if rest.len() % 2 == 0 {
+        (first, first, rest)
+    } else if compare(&rest[0], first) == Ordering::Less {
+        (&rest[0], first, &rest[1..])
+    } else {
+        (first, &rest[0], &rest[1..])
+    };
+
+    for pair in pairs.chunks_exact(2) {
+        let (smaller, larger) = if compare(&pair[1], &pair[0]) == Ordering::Less {
+            (&pair[1], &pair[0])
+        } else {
+            (&pair[0], &pair[1])
+        };
+        if compare(smaller, min) == Ordering::Less {
+            min = smaller;
+        }
+        if compare(larger, max) != Ordering::Less {
+            max = larger;
+        }
+    }
+
+    Some((min, max))
+}
+
+/// Counts the elements of a slice that fall into each bucket.
+///
+/// `key_fn` maps every element to its bucket, which must be smaller than
+/// `n_buckets`. Returns a [`Vec`] of length `n_buckets` with the number of
+/// elements in each bucket.
+///
+/// This is the pre-pass of distribution sorts like [`counting_with_histogram`].
+///
+/// # Panics
+///
+/// Panics if `key_fn` returns a bucket greater or equal to `n_buckets`.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let slice = [12, 7, 25, 3, 18, 11];
+/// assert_eq!(sort::histogram(&slice, |&v| v / 10, 3), [2, 3, 1]);
+/// ```
+pub fn histogram<T, F>(slice: &[T], mut key_fn: F, n_buckets: usize) -> Vec<usize>
+where
+    F: FnMut(&T) -> usize,
+{
+    let mut histogram = vec![0; n_buckets];
+    for v in slice {
+        histogram[key_fn(v)] += 1;
+    }
+
+    histogram
+}
+
+/// A direction in which a part of a slice is sorted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From the smallest to the largest element.
+    Ascending,
+    /// From the largest to the smallest element.
+    Descending,
+}
+
+impl Direction {
+    fn reverse(self) -> Self {
+        match self {
+            Direction::Ascending => Direction::Descending,
+            Direction::Descending => Direction::Ascending,
+        }
+    }
+}
+
+/// Returns the compare-exchange network of [`bitonic`] sort for `n` elements.
+///
+/// Every comparator `(i, j, direction)` has `i < j`, and after applying it the
+/// elements on positions `i` and `j` are in the given direction. Executing the
+/// comparators in the returned order sorts the slice ascending.
+///
+/// The network works for any `n`, not only powers of 2.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort::{self, Direction};
+///
+/// let schedule = sort::bitonic_schedule(4);
+/// assert_eq!(schedule[0], (0, 1, Direction::Descending));
+/// assert_eq!(schedule.len(), 6);
+/// ```
+pub fn bitonic_schedule(n: usize) -> Vec<(usize, usize, Direction)> {
+    fn sort(lo: usize, n: usize, dir: Direction, out: &mut Vec<(usize, usize, Direction)>) {
+        if n > 1 {
+            let m = n / 2;
+            sort(lo, m, dir.reverse(), out);
+            sort(lo + m, n - m, dir, out);
+            merge(lo, n, dir, out);
+        }
+    }
+
+    fn merge(lo: usize, n: usize, dir: Direction, out: &mut Vec<(usize, usize, Direction)>) {
+        if n > 1 {
+            // the greatest power of 2 smaller than n
+            let m = n.next_power_of_two() / 2;
+            for i in lo..(lo + n - m) {
+                out.push((i, i + m, dir));
+            }
+            merge(lo, m, dir, out);
+            merge(lo + m, n - m, dir, out);
+        }
+    }
+
+    let mut schedule = Vec::new();
+    sort(0, n, Direction::Ascending, &mut schedule);
+    schedule
+}
+
+/// An implementation of bitonic sort.
+///
+/// Applies the compare-exchange network returned by [`bitonic_schedule`]. The
+/// sequence of comparisons does not depend on the data, which makes the
+/// algorithm suitable for SIMD or hardware implementations; on a CPU it does
+/// `O(n log² n)` comparisons.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [8, -3, 5, 0, 12, 7, 1];
+/// sort::bitonic(&mut slice);
+/// assert_eq!(slice, [-3, 0, 1, 5, 7, 8, 12]);
+/// ```
+pub fn bitonic<T: Ord>(slice: &mut [T]) {
+    bitonic_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`bitonic`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [8, -3, 5, 0, 12, 7, 1];
+/// sort::bitonic_by(&mut slice, |a, b| b.cmp(a));
+/// assert_eq!(slice, [12, 8, 7, 5, 1, 0, -3]);
+/// ```
+pub fn bitonic_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    for (i, j, dir) in bitonic_schedule(slice.len()) {
+        let swap = match dir {
+            Direction::Ascending => compare(&slice[i], &slice[j]) == Ordering::Greater,
+            Direction::Descending => compare(&slice[i], &slice[j]) == Ordering::Less,
+        };
+        if swap {
+            slice.swap(i, j);
+        }
+    }
+}
+
+/// Sorts a slice with [`bitonic`] sort, using a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = ["apple", "fig", "banana", "kiwi"];
+/// sort::bitonic_by_key(&mut slice, |s| s.len());
+/// assert_eq!(slice, ["fig", "kiwi", "apple", "banana"]);
+/// ```
+pub fn bitonic_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    bitonic_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`bitonic`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::bitonic_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn bitonic_desc<T: Ord>(slice: &mut [T]) {
+    bitonic_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`bitonic`] sort, leaving the other elements
+/// untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [9, 8, 3, 1, 2, 0];
+/// sort::bitonic_range(&mut slice, 1..5);
+/// assert_eq!(slice, [9, 1, 2, 3, 8, 0]);
+/// ```
+pub fn bitonic_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    bitonic(&mut slice[bounds(range)]);
+}
+
+/// Size-optimal (or the best known) sorting networks for up to 16 elements, as
+/// lists of compare-exchange pairs.
+#[rustfmt::skip]
+const NETWORKS: [&[(usize, usize)]; 17] = [
+    &[],
+    &[],
+    // 2 elements, 1 comparator
+    &[(0, 1)],
+    // 3 elements, 3 comparators
+    &[(0, 2), (0, 1), (1, 2)],
+    // 4 elements, 5 comparators
+    &[(0, 1), (2, 3), (0, 2), (1, 3), (1, 2)],
+    // 5 elements, 9 comparators
+    &[(0, 3), (1, 4), (0, 2), (1, 3), (0, 1), (2, 4), (1, 2), (3, 4), (2, 3)],
+    // 6 elements, 12 comparators
+    &[(0, 5), (1, 3), (2, 4), (1, 2), (3, 4), (0, 3), (2, 5), (0, 1), (2, 3),
+        (4, 5), (1, 2), (3, 4)],
+    // 7 elements, 16 comparators
+    &[(0, 6), (2, 3), (4, 5), (0, 2), (1, 4), (3, 6), (0, 1), (2, 5), (3, 4),
+        (1, 2), (4, 6), (2, 3), (4, 5), (1, 2), (3, 4), (5, 6)],
+    // 8 elements, 19 comparators
+    &[(0, 2), (1, 3), (4, 6), (5, 7), (0, 4), (1, 5), (2, 6), (3, 7), (0, 1),
+        (2, 3), (4, 5), (6, 7), (2, 4), (3, 5), (1, 4), (3, 6), (1, 2), (3, 4),
+        (5, 6)],
+    // 9 elements, 25 comparators
+    &[(0, 3), (1, 7), (2, 5), (4, 8), (0, 7), (2, 4), (3, 8), (5, 6), (0, 2),
+        (1, 3), (4, 5), (7, 8), (1, 4), (3, 6), (5, 7), (0, 1), (2, 4), (3, 5),
+        (6, 8), (2, 3), (4, 5), (6, 7), (1, 2), (3, 4), (5, 6)],
+    // 10 elements, 29 comparators
+    &[(0, 8), (1, 9), (2, 7), (3, 5), (4, 6), (0, 2), (1, 4), (5, 8), (7, 9),
+        (0, 3), (2, 4), (5, 7), (6, 9), (0, 1), (3, 6), (8, 9), (1, 5), (2, 3),
+        (4, 8), (6, 7), (1, 2), (3, 5), (4, 6), (7, 8), (2, 3), (4, 5), (6, 7),
+        (3, 4), (5, 6)],
+    // 11 elements, 35 comparators
+    &[(0, 9), (1, 6), (2, 4), (3, 7), (5, 8), (0, 1), (3, 5), (4, 10), (6, 9),
+        (7, 8), (1, 3), (2, 5), (4, 7), (8, 10), (0, 4), (1, 2), (3, 7), (5, 9),
+        (6, 8), (0, 1), (2, 6), (4, 5), (7, 8), (9, 10), (2, 4), (3, 6), (5, 7),
+        (8, 9), (1, 2), (3, 4), (5, 6), (7, 8), (2, 3), (4, 5), (6, 7)],
+    // 12 elements, 39 comparators
+    &[(0, 8), (1, 7), (2, 6), (3, 11), (4, 10), (5, 9), (0, 1), (2, 5), (3, 4),
+        (6, 9), (7, 8), (10, 11), (0, 2), (1, 6), (5, 10), (9, 11), (0, 3),
+        (1, 2), (4, 6), (5, 7), (8, 11), (9, 10), (1, 4), (3, 5), (6, 8),
+        (7, 10), (1, 3), (2, 5), (6, 9), (8, 10), (2, 3), (4, 5), (6, 7),
+        (8, 9), (4, 6), (5, 7), (3, 4), (5, 6), (7, 8)],
+    // 13 elements, 45 comparators
+    &[(0, 12), (1, 10), (2, 9), (3, 7), (5, 11), (6, 8), (1, 6), (2, 3),
+        (4, 11), (7, 9), (8, 10), (0, 4), (1, 2), (3, 6), (7, 8), (9, 10),
+        (11, 12), (4, 6), (5, 9), (8, 11), (10, 12), (0, 5), (3, 8), (4, 7),
+        (6, 11), (9, 10), (0, 1), (2, 5), (6, 9), (7, 8), (10, 11), (1, 3),
+        (2, 4), (5, 6), (9, 10), (1, 2), (3, 4), (5, 7), (6, 8), (2, 3), (4, 5),
+        (6, 7), (8, 9), (3, 4), (5, 6)],
+    // 14 elements, 51 comparators
+    &[(0, 1), (2, 3), (4, 5), (6, 7), (8, 9), (10, 11), (12, 13), (0, 2),
+        (1, 3), (4, 8), (5, 9), (10, 12), (11, 13), (0, 4), (1, 2), (3, 7),
+        (5, 8), (6, 10), (9, 13), (11, 12), (0, 6), (1, 5), (3, 9), (4, 10),
+        (7, 13), (8, 12), (2, 10), (3, 11), (4, 6), (7, 9), (1, 3), (2, 8),
+        (5, 11), (6, 7), (10, 12), (1, 4), (2, 6), (3, 5), (7, 11), (8, 10),
+        (9, 12), (2, 4), (3, 6), (5, 8), (7, 10), (9, 11), (3, 4), (5, 6),
+        (7, 8), (9, 10), (6, 7)],
+    // 15 elements, 56 comparators
+    &[(0, 13), (1, 12), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10), (0, 5),
+        (1, 7), (2, 9), (3, 4), (6, 13), (8, 14), (11, 12), (0, 1), (2, 3),
+        (4, 5), (6, 8), (7, 9), (10, 11), (12, 13), (0, 2), (1, 3), (4, 10),
+        (5, 11), (6, 7), (8, 9), (12, 14), (1, 2), (3, 12), (4, 6), (5, 7),
+        (8, 10), (9, 11), (13, 14), (1, 4), (2, 6), (5, 8), (7, 10), (9, 13),
+        (11, 14), (2, 4), (3, 6), (9, 12), (11, 13), (3, 5), (6, 8), (7, 9),
+        (10, 12), (3, 4), (5, 6), (7, 8), (9, 10), (11, 12), (6, 7), (8, 9)],
+    // 16 elements, 60 comparators
+    &[(0, 13), (1, 12), (2, 15), (3, 14), (4, 8), (5, 6), (7, 11), (9, 10),
+        (0, 5), (1, 7), (2, 9), (3, 4), (6, 13), (8, 14), (10, 15), (11, 12),
+        (0, 1), (2, 3), (4, 5), (6, 8), (7, 9), (10, 11), (12, 13), (14, 15),
+        (0, 2), (1, 3), (4, 10), (5, 11), (6, 7), (8, 9), (12, 14), (13, 15),
+        (1, 2), (3, 12), (4, 6), (5, 7), (8, 10), (9, 11), (13, 14), (1, 4),
+        (2, 6), (5, 8), (7, 10), (9, 13), (11, 14), (2, 4), (3, 6), (9, 12),
+        (11, 13), (3, 5), (6, 8), (7, 9), (10, 12), (3, 4), (5, 6), (7, 8),
+        (9, 10), (11, 12), (6, 7), (8, 9)],
+];
+
+/// Sorts a small array with a sorting network.
+///
+/// For `N <= 16` it applies the best known sorting network with the least
+/// number of comparators (e.g. 19 for 8 elements, 60 for 16). The network is
+/// chosen at compile time and doesn't depend on the data, so there are no
+/// recursive calls or loops over the slice length. Larger arrays are sorted
+/// with [`intro`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut array = [5, -2, 9, 0, 3, 3, 1, 8];
+/// sort::network(&mut array);
+/// assert_eq!(array, [-2, 0, 1, 3, 3, 5, 8, 9]);
+/// ```
+pub fn network<T: Ord, const N: usize>(array: &mut [T; N]) {
+    network_by(array, T::cmp);
+}
+
+/// Sorts a small array with a [`network`], using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut array = [5, -2, 9, 0];
+/// sort::network_by(&mut array, |a, b| b.cmp(a));
+/// assert_eq!(array, [9, 5, 0, -2]);
+/// ```
+pub fn network_by<T, F, const N: usize>(array: &mut [T; N], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if N < NETWORKS.len() {
+        for &(i, j) in NETWORKS[N] {
+            if compare(&array[j], &array[i]) == Ordering::Less {
+                array.swap(i, j);
+            }
+        }
+    } else {
+        intro_by(array, compare);
+    }
+}
+
+/// Sorts a small array with a [`network`], using a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut array = [-5, 3, -1, 4, -2];
+/// sort::network_by_key(&mut array, |x: &i32| x.abs());
+/// assert_eq!(array, [-1, -2, 3, 4, -5]);
+/// ```
+pub fn network_by_key<T, K, F, const N: usize>(array: &mut [T; N], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    network_by(array, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a small array in descending order with a [`network`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut array = [3, 1, 4, 1, 5];
+/// sort::network_desc(&mut array);
+/// assert_eq!(array, [5, 4, 3, 1, 1]);
+/// ```
+pub fn network_desc<T: Ord, const N: usize>(array: &mut [T; N]) {
+    network_by(array, |a, b| b.cmp(a));
+}
+
+/// Splits the merge of the sorted (by `is_less`) `slice[..mid]` and
+/// `slice[mid..]` into two smaller, independent merges.
+///
+/// Cuts the longer part in half, finds the matching cut in the other part by
+/// binary search, and rotates the elements between the cuts. Returns
+/// `(cut1, new_mid, cut2)`: `slice[..new_mid]` has to be merged at `cut1`, and
+/// `slice[new_mid..]` at `cut2 - new_mid`.
+fn split_rotate<T, F>(slice: &mut [T], mid: usize, is_less: &mut F) -> (usize, usize, usize)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = slice.len();
+    let (cut1, cut2) = if mid >= len - mid {
+        let cut1 = mid / 2;
+        let cut2 = mid + slice[mid..].partition_point(|x| is_less(x, &slice[cut1]));
+        (cut1, cut2)
+    } else {
+        let cut2 = mid + (len - mid) / 2;
+        let cut1 = slice[..mid].partition_point(|x| !is_less(&slice[cut2], x));
+        (cut1, cut2)
+    };
+
+    util::rotate_left(&mut slice[cut1..cut2], mid - cut1);
+    (cut1, cut1 + cut2 - mid, cut2)
+}
+
+/// Merges the sorted (by `is_less`) `slice[..mid]` and `slice[mid..]` in
+/// place, using at most `cap` elements of `buf` as scratch space.
+///
+/// If neither part fits in the buffer, the parts are split by binary search,
+/// the middle is rotated and both halves are merged recursively. The merge is
+/// stable.
+fn merge_bounded<T, F>(slice: &mut [T], mid: usize, buf: &mut Vec<T>, cap: usize, is_less: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> bool,
+{
+    let len = slice.len();
+    if mid == 0 || mid == len {
+        return;
+    }
+
+    if mid <= cap {
+        // copy the left part to the buffer and merge forward
+        buf.clear();
+        buf.extend_from_slice(&slice[..mid]);
+
+        let (mut i, mut j, mut k) = (0, mid, 0);
+        while i < buf.len() && j < len {
+            if is_less(&slice[j], &buf[i]) {
+                slice[k] = slice[j].clone();
+                j += 1;
+            } else {
+                slice[k] = buf[i].clone();
+                i += 1;
+            }
+            k += 1;
+        }
+        slice[k..(k + buf.len() - i)].clone_from_slice(&buf[i..]);
+    } else if len - mid <= cap {
+        // copy the right part to the buffer and merge backward
+        buf.clear();
+        buf.extend_from_slice(&slice[mid..]);
+
+        let (mut i, mut j, mut k) = (mid, buf.len(), len);
+        while i > 0 && j > 0 {
+            if is_less(&buf[j - 1], &slice[i - 1]) {
+                slice[k - 1] = slice[i - 1].clone();
+                i -= 1;
+            } else {
+                slice[k - 1] = buf[j - 1].clone();
+                j -= 1;
+            }
+            k -= 1;
+        }
+        slice[..j].clone_from_slice(&buf[..j]);
+    } else {
+        let (cut1, new_mid, cut2) = split_rotate(slice, mid, is_less);
+        merge_bounded(&mut slice[..new_mid], cut1, buf, cap, is_less);
+        merge_bounded(&mut slice[new_mid..], cut2 - new_mid, buf, cap, is_less);
+    }
+}
+
+/// A chunked merge sort with bounded scratch memory.
+///
+/// First every chunk of `chunk_size` elements is sorted on its own with
+/// [`merge`] sort, which takes half a chunk of scratch space; [`par_chunked`]
+/// sorts the chunks in parallel. Then the sorted chunks are merged pairwise,
+/// bottom-up, until the whole slice is sorted. It's not a k-way merge: every
+/// element takes part in `log2(n / chunk_size)` merges.
+///
+/// The scratch space never exceeds `chunk_size` elements. When both merged
+/// parts are longer than that, the merge is split by binary search and
+/// rotations until a part fits, so a merge of `m` elements does
+/// `O(m log(m / chunk_size))` moves, and the whole sort does up to
+/// `O(n log² n)` moves when the chunks are small.
+///
+/// The sort is stable.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is 0.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [7, 3, 9, -1, 0, 4, 12, 5, 2];
+/// sort::chunked(&mut slice, 2);
+/// assert_eq!(slice, [-1, 0, 2, 3, 4, 5, 7, 9, 12]);
+/// ```
+pub fn chunked<T: Ord + Clone>(slice: &mut [T], chunk_size: usize) {
+    chunked_by(slice, chunk_size, T::cmp);
+}
+
+/// Sorts a slice with [`chunked`] merge sort, using a comparator function.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is 0.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [7, 3, 9, -1, 0, 4, 12, 5, 2];
+/// sort::chunked_by(&mut slice, 2, |a, b| b.cmp(a));
+/// assert_eq!(slice, [12, 9, 7, 5, 4, 3, 2, 0, -1]);
+/// ```
+pub fn chunked_by<T, F>(slice: &mut [T], chunk_size: usize, mut compare: F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    assert!(chunk_size != 0, "chunk size must be non-zero");
+
+    let mut buf = Vec::with_capacity(chunk_size);
+    for chunk in slice.chunks_mut(chunk_size) {
+        merge_inner(chunk, &mut compare, INSERTION_CUTOFF, &mut buf);
+    }
+    merge_chunks(slice, chunk_size, &mut buf, &mut compare);
+}
+
+/// Merges the sorted chunks of `chunk_size` elements of a slice pairwise,
+/// bottom-up, with [`merge_bounded`].
+fn merge_chunks<T, F>(slice: &mut [T], chunk_size: usize, buf: &mut Vec<T>, compare: &mut F)
+where
+    T: Clone,
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut is_less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+    let mut width = chunk_size;
+    while width < slice.len() {
+        for pair in slice.chunks_mut(2 * width) {
+            if pair.len() > width {
+                merge_bounded(pair, width, buf, chunk_size, &mut is_less);
+            }
+        }
+        width = width.saturating_mul(2);
+    }
+}
+
+/// Sorts a slice with [`chunked`] merge sort, using a key extraction function.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is 0.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+/// use std::cmp::Reverse;
+///
+/// let mut slice = [1, 6, 3, 2, 8];
+/// sort::chunked_by_key(&mut slice, 2, |&x| Reverse(x));
+/// assert_eq!(slice, [8, 6, 3, 2, 1]);
+/// ```
+pub fn chunked_by_key<T, K, F>(slice: &mut [T], chunk_size: usize, mut key_fn: F)
+where
+    T: Clone,
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    chunked_by(slice, chunk_size, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`chunked`] merge sort. Equal
+/// elements keep their original order.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is 0.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::chunked_desc(&mut slice, 2);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn chunked_desc<T: Ord + Clone>(slice: &mut [T], chunk_size: usize) {
+    chunked_by(slice, chunk_size, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`chunked`] merge sort, leaving the other
+/// elements untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice, or if `chunk_size` is
+/// 0.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [5, 4, 3, 2, 1];
+/// sort::chunked_range(&mut slice, 2.., 2);
+/// assert_eq!(slice, [5, 4, 1, 2, 3]);
+/// ```
+pub fn chunked_range<T, R>(slice: &mut [T], range: R, chunk_size: usize)
+where
+    T: Ord + Clone,
+    R: RangeBounds<usize>,
+{
+    chunked(&mut slice[bounds(range)], chunk_size);
+}
+
+/// Sorts a slice with [`chunked`] merge sort, sorting the chunks in parallel
+/// on the global thread pool of the `rayon` crate.
+///
+/// Every thread takes at most half a chunk of scratch space while sorting the
+/// chunks, and the merges, which are sequential, take one chunk. Available
+/// only with the `rayon` feature.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is 0.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: Vec<_> = (0..100_000).map(|x| (x * 7919) % 100_003).collect();
+/// sort::par_chunked(&mut slice, 4096);
+/// assert!(sort::test(&slice));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_chunked<T: Ord + Clone + Send>(slice: &mut [T], chunk_size: usize) {
+    par_chunked_by(slice, chunk_size, T::cmp);
+}
+
+/// Sorts a slice with [`par_chunked`] merge sort, using a comparator
+/// function. Available only with the `rayon` feature.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is 0.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: Vec<_> = (0..10_000).map(|x| (x % 10, x)).collect();
+/// sort::par_chunked_by(&mut slice, 1000, |a, b| a.0.cmp(&b.0));
+/// assert!(slice.windows(2).all(|w| w[0].0 < w[1].0 || w[0].1 < w[1].1));
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_chunked_by<T, F>(slice: &mut [T], chunk_size: usize, compare: F)
+where
+    T: Clone + Send,
+    F: Fn(&T, &T) -> Ordering + Sync,
+{
+    use rayon::prelude::*;
+
+    assert!(chunk_size != 0, "chunk size must be non-zero");
+
+    slice.par_chunks_mut(chunk_size).for_each(|chunk| {
+        merge_inner(chunk, &mut &compare, INSERTION_CUTOFF, &mut Vec::new());
+    });
+    merge_chunks(
+        slice,
+        chunk_size,
+        &mut Vec::with_capacity(chunk_size),
+        &mut &compare,
+    );
+}
+
+/// The length of runs that [`merge_in_place`] sorts with [`insertion`] sort.
+/// It's a power of two, so are the lengths of all the merged runs.
+const IN_PLACE_RUN_LEN: usize = 16;
+
+/// Moves `slice[gap..]` to the front, swapping it through the buffer of the
+/// first `gap` elements, which ends up at the back in any order.
+fn shift_left<T>(slice: &mut [T], gap: usize) {
+    for i in gap..slice.len() {
+        slice.swap(i - gap, i);
+    }
+}
+
+/// Moves `slice[..len - gap]` to the back, swapping it through the buffer of
+/// the last `gap` elements, which ends up at the front in any order.
+fn shift_right<T>(slice: &mut [T], gap: usize) {
+    for i in (0..(slice.len() - gap)).rev() {
+        slice.swap(i, i + gap);
+    }
+}
+
+/// Moves the first occurrences of at most `wanted` distinct elements of a
+/// non-empty slice to its front, sorted, and returns their number. The other
+/// elements keep their order.
+fn collect_keys<T, F>(slice: &mut [T], wanted: usize, is_less: &mut F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // the keys found so far are slice[start..(start + count)]
+    let (mut start, mut count) = (0, 1);
+    for i in 1..slice.len() {
+        if count == wanted {
+            break;
+        }
+
+        let keys = &slice[start..(start + count)];
+        let pos = keys.partition_point(|key| is_less(key, &slice[i]));
+        if pos < count && !is_less(&slice[i], &keys[pos]) {
+            continue;
+        }
+
+        // the keys are moved next to the new one, past the other elements
+        util::rotate_left(&mut slice[start..i], count);
+        start = i - count;
+        util::rotate_right(&mut slice[(start + pos)..=i], 1);
+        count += 1;
+    }
+
+    util::rotate_right(&mut slice[..(start + count)], count);
+    count
+}
+
+/// Returns whether an element of the left part of a merge goes before one of
+/// the right part. If `right_first`, equal elements of the right part go first.
+fn goes_before<T, F>(l: &T, r: &T, is_less: &mut F, right_first: bool) -> bool
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if right_first {
+        is_less(l, r)
+    } else {
+        !is_less(r, l)
+    }
+}
+
+/// Merges the sorted (by `is_less`) `slice[..mid]` and `slice[mid..]` in
+/// place by rotations. If `right_first`, equal elements of the right part go
+/// first.
+///
+/// Every rotation moves the remaining shorter part past a group of elements
+/// of the longer one, so it takes `O(s · g + l)` moves, where `s` and `l` are
+/// the lengths of the parts and `g` is the number of distinct elements of the
+/// shorter one.
+fn merge_lazy<T, F>(slice: &mut [T], mid: usize, is_less: &mut F, right_first: bool)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut before = |l: &T, r: &T| goes_before(l, r, is_less, right_first);
+    let (mut lo, mut mid, mut hi) = (0, mid, slice.len());
+    if mid - lo <= hi - mid {
+        while lo < mid && mid < hi {
+            // the right elements that go before the first left one
+            let h = slice[mid..hi].partition_point(|r| !before(&slice[lo], r));
+            util::rotate_left(&mut slice[lo..(mid + h)], mid - lo);
+            lo += h;
+            mid += h;
+            if mid == hi {
+                break;
+            }
+
+            // the left elements that go before the next right one
+            lo += slice[lo..mid].partition_point(|l| before(l, &slice[mid]));
+        }
+    } else {
+        while lo < mid && mid < hi {
+            // the left elements that go after the last right one
+            let h = (mid - lo) - slice[lo..mid].partition_point(|l| before(l, &slice[hi - 1]));
+            util::rotate_right(&mut slice[(mid - h)..hi], hi - mid);
+            mid -= h;
+            hi -= h;
+            if lo == mid {
+                break;
+            }
+
+            // the right elements that go after the last left one
+            hi = mid + slice[mid..hi].partition_point(|r| !before(&slice[mid - 1], r));
+        }
+    }
+}
+
+/// Merges the sorted (by `is_less`) parts of `slice`, which is made of a
+/// buffer of `buf` elements, a left part of `left_len` elements and a right
+/// part of at most `buf` elements, by swapping the smaller elements into the
+/// buffer. If `right_first`, equal elements of the right part go first.
+///
+/// Stops when one of the parts runs out, and returns the number of the
+/// remaining elements of the other part and whether it's the left one. The
+/// merged elements are at the front, followed by the buffer and the remaining
+/// right elements, or by the remaining left elements and the buffer.
+fn merge_buffered<T, F>(
+    slice: &mut [T],
+    buf: usize,
+    left_len: usize,
+    is_less: &mut F,
+    right_first: bool,
+) -> (usize, bool)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let (left_end, right_end) = (buf + left_len, slice.len());
+    let (mut out, mut l, mut r) = (0, buf, left_end);
+    while l < left_end && r < right_end {
+        if goes_before(&slice[l], &slice[r], is_less, right_first) {
+            slice.swap(out, l);
+            l += 1;
+        } else {
+            slice.swap(out, r);
+            r += 1;
+        }
+        out += 1;
+    }
+
+    if l == left_end {
+        (right_end - r, false)
+    } else {
+        // the buffer is split around the remaining left elements
+        shift_left(&mut slice[out..left_end], l - out);
+        (left_end - l, true)
+    }
+}
+
+/// Merges the sorted (by `is_less`) parts of `slice`, which is made of a
+/// buffer of `buf` elements, a left part of `left_len` elements and a right
+/// part, by blocks of `block` elements. The buffer ends up at the back.
+///
+/// `left_len` is a multiple of `block`, which is at most `buf`, unless `buf`
+/// is 0. The blocks are tagged with the distinct, sorted `tags`, and sorted
+/// by selection, by their first elements and then by their tags, which tells
+/// the left blocks from the right ones. Then every block only has to be
+/// merged with the rest of the ones before it: with the buffer, or by
+/// rotations if there's none. The last incomplete block of the right part is
+/// merged by rotations at the end, and the tags are sorted back.
+fn merge_blocks<T, F>(
+    slice: &mut [T],
+    tags: &mut [T],
+    buf: usize,
+    left_len: usize,
+    block: usize,
+    is_less: &mut F,
+) where
+    F: FnMut(&T, &T) -> bool,
+{
+    let right_len = slice.len() - buf - left_len;
+    let left_blocks = left_len / block;
+    let blocks = left_blocks + right_len / block;
+    let full_end = buf + blocks * block;
+
+    // the tag of the first right block, unless there are only left ones
+    let mut mid = left_blocks;
+    for i in 0..blocks {
+        let mut min = i;
+        for j in (i + 1)..blocks {
+            let (x, y) = (&slice[buf + j * block], &slice[buf + min * block]);
+            if is_less(x, y) || (!is_less(y, x) && is_less(&tags[j], &tags[min])) {
+                min = j;
+            }
+        }
+
+        if min != i {
+            let (head, tail) = slice.split_at_mut(buf + min * block);
+            head[(buf + i * block)..(buf + (i + 1) * block)].swap_with_slice(&mut tail[..block]);
+            tags.swap(i, min);
+            if mid == i {
+                mid = min;
+            } else if mid == min {
+                mid = i;
+            }
+        }
+    }
+
+    // the merged elements are followed by the buffer, and the pending ones,
+    // which come from one part and end where the next block begins
+    let (mut pending, mut pending_left) = (block, mid == blocks || is_less(&tags[0], &tags[mid]));
+    for i in 1..blocks {
+        let start = buf + i * block;
+        let is_left = mid == blocks || is_less(&tags[i], &tags[mid]);
+        if is_left == pending_left {
+            shift_left(&mut slice[(start - pending - buf)..start], buf);
+            pending = block;
+            continue;
+        }
+
+        let right_first = !pending_left;
+        let part = &mut slice[(start - pending - buf)..(start + block)];
+        if buf > 0 {
+            let (rest, left) = merge_buffered(part, buf, pending, is_less, right_first);
+            if left {
+                let len = part.len();
+                shift_right(&mut part[(len - rest - buf)..], buf);
+            }
+            pending = rest;
+            pending_left = if left { pending_left } else { is_left };
+        } else {
+            // the part with the last element has elements left
+            let last = part.len() - 1;
+            let (rest, left) = if goes_before(&part[pending - 1], &part[last], is_less, right_first)
+            {
+                let merged = part[pending..]
+                    .partition_point(|r| !goes_before(&part[pending - 1], r, is_less, right_first));
+                (block - merged, false)
+            } else {
+                let merged = part[..pending]
+                    .partition_point(|l| goes_before(l, &part[last], is_less, right_first));
+                (pending - merged, true)
+            };
+            merge_lazy(part, pending, is_less, right_first);
+            pending = rest;
+            pending_left = if left { pending_left } else { is_left };
+        }
+    }
+    shift_left(&mut slice[(full_end - pending - buf)..full_end], buf);
+
+    let len = slice.len();
+    shift_left(&mut slice[(full_end - buf)..], buf);
+    merge_lazy(&mut slice[..(len - buf)], full_end - buf, is_less, false);
+
+    insertion_by_less(&mut tags[..blocks], &mut *is_less);
+}
+
+/// Sorts a slice stably in place by block merges, as [`merge_in_place`]
+/// describes.
+fn block_merge_sort<T, F>(slice: &mut [T], is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    // the length of the blocks of a merge of two runs of `width` elements,
+    // and the number of keys it takes for the buffer and the tags
+    let block_len = |width: usize| -> usize { 1 << width.trailing_zeros().div_ceil(2) };
+    let keys_for = |width: usize| block_len(width) + 2 * width / block_len(width);
+
+    let len = slice.len();
+    if len <= 2 * IN_PLACE_RUN_LEN {
+        insertion_by_less(slice, is_less);
+        return;
+    }
+
+    let keys = collect_keys(slice, keys_for(len.next_power_of_two()), is_less);
+    if keys == 1 {
+        // all the elements are equal
+        return;
+    }
+    for run in slice[keys..].chunks_mut(IN_PLACE_RUN_LEN) {
+        insertion_by_less(run, &mut *is_less);
+    }
+
+    let data_len = len - keys;
+    let mut width = IN_PLACE_RUN_LEN;
+    while width < data_len {
+        // if there are fewer keys than a merge with a buffer takes, they're
+        // all the distinct elements, and the blocks are merged by rotations
+        let (buf, block) = if keys >= width {
+            (width, width)
+        } else if keys >= keys_for(width) {
+            (block_len(width), block_len(width))
+        } else {
+            let mut block = 1;
+            while 2 * (width / block) > keys {
+                block *= 2;
+            }
+            (0, block)
+        };
+
+        if buf != width {
+            // the tags have to be sorted, after a buffer took some of them
+            insertion_by_less(&mut slice[..keys], &mut *is_less);
+        }
+        let (tags, rest) = slice.split_at_mut(keys - buf);
+        let mut start = 0;
+        while start < data_len {
+            let pair_len = (data_len - start).min(2 * width);
+            let pair = &mut rest[start..(start + buf + pair_len)];
+            if pair_len <= width {
+                shift_left(pair, buf);
+            } else if buf == width {
+                let (rest, left) = merge_buffered(pair, buf, width, is_less, false);
+                if !left {
+                    let len = pair.len();
+                    shift_left(&mut pair[(len - rest - buf)..], buf);
+                }
+            } else {
+                merge_blocks(pair, tags, buf, width, block, is_less);
+            }
+            start += pair_len;
+        }
+        util::rotate_right(rest, buf);
+
+        width *= 2;
+    }
+
+    insertion_by_less(&mut slice[..keys], &mut *is_less);
+    merge_lazy(slice, keys, is_less, false);
+}
+
+/// An implementation of in-place stable merge sort.
+///
+/// A block merge sort, in the style of grailsort: first it moves about `3√n`
+/// distinct elements to the front, to use them as a buffer and to tag blocks
+/// of about `√n` elements. Then it sorts runs of 16 elements with
+/// [`insertion`] sort, and merges them bottom-up. Two runs are merged by
+/// sorting their blocks by the first elements, and merging every block with
+/// the rest of the ones before it, by swapping the elements through the
+/// buffer. At the end, the distinct elements are sorted and merged back.
+///
+/// If there are fewer distinct elements, the blocks are merged by rotations,
+/// which takes few moves, because equal elements are moved together.
+///
+/// It's stable, needs only `O(1)` extra memory and doesn't require
+/// [`Clone`]. It does `O(n log n)` comparisons and moves.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [5, 2, 8, -3, 2, 0, 7];
+/// sort::merge_in_place(&mut slice);
+/// assert_eq!(slice, [-3, 0, 2, 2, 5, 7, 8]);
+/// ```
+pub fn merge_in_place<T: Ord>(slice: &mut [T]) {
+    merge_in_place_by(slice, T::cmp);
+}
+
+/// Sorts a slice with [`merge_in_place`] sort, using a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [(2, 'a'), (1, 'b'), (2, 'c'), (0, 'd')];
+/// sort::merge_in_place_by(&mut slice, |a, b| b.0.cmp(&a.0));
+/// assert_eq!(slice, [(2, 'a'), (2, 'c'), (1, 'b'), (0, 'd')]);
+/// ```
+pub fn merge_in_place_by<T, F>(slice: &mut [T], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let mut is_less = |a: &T, b: &T| compare(a, b) == Ordering::Less;
+    block_merge_sort(slice, &mut is_less);
+}
+
+/// Sorts a slice with [`merge_in_place`] sort, using a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [(2, 'a'), (1, 'b'), (2, 'c'), (0, 'd')];
+/// sort::merge_in_place_by_key(&mut slice, |p| p.0);
+/// assert_eq!(slice, [(0, 'd'), (1, 'b'), (2, 'a'), (2, 'c')]);
+/// ```
+pub fn merge_in_place_by_key<T, K, F>(slice: &mut [T], mut key_fn: F)
+where
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    merge_in_place_by(slice, |a, b| key_fn(a).cmp(&key_fn(b)));
+}
+
+/// Sorts a slice in descending order with [`merge_in_place`] sort. Equal
+/// elements keep their original order.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 4, 1, 5];
+/// sort::merge_in_place_desc(&mut slice);
+/// assert_eq!(slice, [5, 4, 3, 1, 1]);
+/// ```
+pub fn merge_in_place_desc<T: Ord>(slice: &mut [T]) {
+    merge_in_place_by(slice, |a, b| b.cmp(a));
+}
+
+/// Sorts a range of a slice with [`merge_in_place`] sort, leaving the other
+/// elements untouched.
+///
+/// # Panics
+///
+/// Panics if the range is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 2, 1, 0, -1];
+/// sort::merge_in_place_range(&mut slice, ..3);
+/// assert_eq!(slice, [1, 2, 3, 0, -1]);
+/// ```
+pub fn merge_in_place_range<T, R>(slice: &mut [T], range: R)
+where
+    T: Ord,
+    R: RangeBounds<usize>,
+{
+    merge_in_place(&mut slice[bounds(range)]);
+}
+
+/// Sorts several disjoint subranges of a slice with the given algorithm.
+///
+/// The ranges are validated first, and then `sort` is invoked on every one of
+/// them. They may be given in any order.
+///
+/// # Panics
+///
+/// Panics if any range is out of bounds of the slice, if its start is greater
+/// than its end, or if any two ranges overlap.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 2, 0, 9, 8, 7];
+/// sort::ranges(&mut slice, &[4..7, 0..3], sort::quick);
+/// assert_eq!(slice, [1, 2, 3, 0, 7, 8, 9]);
+/// ```
+pub fn ranges<T, F>(slice: &mut [T], ranges: &[Range<usize>], mut sort: F)
+where
+    F: FnMut(&mut [T]),
+{
+    for part in split_ranges(slice, ranges) {
+        sort(part);
+    }
+}
+
+/// Sorts several disjoint subranges of a slice with the given algorithm, in
+/// parallel.
+///
+/// Works like [`ranges`], but every range is sorted in its own task of a
+/// [`rayon::scope`]. Available only with the `rayon` feature.
+///
+/// # Panics
+///
+/// Panics if any range is out of bounds of the slice, if its start is greater
+/// than its end, or if any two ranges overlap.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 2, 0, 9, 8, 7];
+/// sort::par_ranges(&mut slice, &[4..7, 0..3], sort::quick);
+/// assert_eq!(slice, [1, 2, 3, 0, 7, 8, 9]);
+/// ```
+#[cfg(feature = "rayon")]
+pub fn par_ranges<T, F>(slice: &mut [T], ranges: &[Range<usize>], sort: F)
+where
+    T: Send,
+    F: Fn(&mut [T]) + Sync,
+{
+    let parts = split_ranges(slice, ranges);
+    let sort = &sort;
+    rayon::scope(|s| {
+        for part in parts {
+            s.spawn(move |_| sort(part));
+        }
+    });
+}
+
+/// Validates the disjoint subranges of a slice for [`ranges`], and splits
+/// them off, ordered by their starts.
+fn split_ranges<'a, T>(slice: &'a mut [T], ranges: &[Range<usize>]) -> Vec<&'a mut [T]> {
+    let order = tag_sort(ranges, |r| r.start);
+
+    let mut end = 0;
+    for &i in &order {
+        let range = &ranges[i];
+        assert!(range.start <= range.end, "range {:?} is reversed", range);
+        assert!(
+            range.end <= slice.len(),
+            "range {:?} is out of bounds of a slice of length {}",
+            range,
+            slice.len()
+        );
+        assert!(range.start >= end, "range {:?} overlaps another", range);
+        end = range.end;
+    }
+
+    let mut parts = Vec::with_capacity(ranges.len());
+    let mut rest = slice;
+    let mut offset = 0;
+    for &i in &order {
+        let range = &ranges[i];
+        let (_, tail) = rest.split_at_mut(range.start - offset);
+        let (part, tail) = tail.split_at_mut(range.end - range.start);
+        parts.push(part);
+
+        rest = tail;
+        offset = range.end;
+    }
+
+    parts
+}
+
+/// Returns the ranges of consecutive elements for which `eq` holds pairwise.
+fn equal_ranges<T, F>(slice: &[T], mut eq: F) -> Vec<Range<usize>>
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    let mut groups = Vec::new();
+    if slice.is_empty() {
+        return groups;
+    }
+
+    let mut start = 0;
+    for i in 1..slice.len() {
+        if !eq(&slice[i - 1], &slice[i]) {
+            groups.push(start..i);
+            start = i;
+        }
+    }
+    groups.push(start..slice.len());
+
+    groups
+}
+
+/// Sorts a slice and returns the ranges of equal elements.
+///
+/// The slice is sorted with [`quick`] sort, so the ranges are in ascending
+/// order and cover the whole slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [3, 1, 3, 2, 1, 3];
+/// let groups = sort::group(&mut slice);
+/// assert_eq!(slice, [1, 1, 2, 3, 3, 3]);
+/// assert_eq!(groups, [0..2, 2..3, 3..6]);
+/// ```
+pub fn group<T: Ord>(slice: &mut [T]) -> Vec<Range<usize>> {
+    quick(slice);
+    equal_ranges(slice, |a, b| a == b)
+}
+
+/// Sorts a slice by the given key and returns the ranges of elements with
+/// equal keys.
+///
+/// The slice is sorted with [`tag_sort_gather`], so elements with equal keys
+/// keep their relative order.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = ["bb", "a", "cc", "d"];
+/// let groups = sort::group_by_key(&mut slice, |s| s.len());
+/// assert_eq!(slice, ["a", "d", "bb", "cc"]);
+/// assert_eq!(groups, [0..2, 2..4]);
+/// ```
+pub fn group_by_key<T, K, F>(slice: &mut [T], mut key_fn: F) -> Vec<Range<usize>>
+where
+    T: Clone,
+    K: Ord,
+    F: FnMut(&T) -> K,
+{
+    let sorted = tag_sort_gather(slice, &mut key_fn);
+    slice.clone_from_slice(&sorted);
+    equal_ranges(slice, |a, b| key_fn(a) == key_fn(b))
+}
+
+/// Run-length encodes a sorted slice.
+///
+/// Returns every distinct value with the number of its consecutive
+/// occurrences. The slice doesn't have to be sorted, but then equal values
+/// that are not adjacent are encoded separately.
+///
+/// See also [`rle_ref`] and [`rle_expand`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let slice = [1, 1, 2, 5, 5, 5];
+/// assert_eq!(sort::rle(&slice), [(1, 2), (2, 1), (5, 3)]);
+/// ```
+pub fn rle<T: PartialEq + Clone>(slice: &[T]) -> Vec<(T, usize)> {
+    equal_ranges(slice, |a, b| a == b)
+        .into_iter()
+        .map(|r| (slice[r.start].clone(), r.len()))
+        .collect()
+}
+
+/// Run-length encodes a sorted slice, without cloning the values.
+///
+/// Works like [`rle`], but returns references to the first element of every
+/// run.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let slice = ["a", "a", "b"];
+/// assert_eq!(sort::rle_ref(&slice), [(&"a", 2), (&"b", 1)]);
+/// ```
+pub fn rle_ref<T: PartialEq>(slice: &[T]) -> Vec<(&T, usize)> {
+    equal_ranges(slice, |a, b| a == b)
+        .into_iter()
+        .map(|r| (&slice[r.start], r.len()))
+        .collect()
+}
+
+/// Expands run-length encoded values; the inverse of [`rle`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let runs = [(1, 2), (2, 1), (5, 3)];
+/// assert_eq!(sort::rle_expand(&runs), [1, 1, 2, 5, 5, 5]);
+/// ```
+pub fn rle_expand<T: Clone>(runs: &[(T, usize)]) -> Vec<T> {
+    let mut expanded = Vec::with_capacity(runs.iter().map(|r| r.1).sum());
+    for (value, count) in runs {
+        expanded.extend(std::iter::repeat_n(value, *count).cloned());
+    }
+
+    expanded
+}
+
+/// A stable partition of a slice by a predicate.
+///
+/// Moves all the elements for which `pred` returns `true` to the front of the
+/// slice, preserving the relative order within both groups, and returns the
+/// number of such elements.
+///
+/// Partitions both halves recursively and rotates the middle of the slice, so
+/// it does `O(n log n)` moves without allocating. `pred` is invoked exactly
+/// once for every element.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [1, 2, 3, 4, 5, 6, 7];
+/// let split = sort::partition_stable(&mut slice, |x| x % 3 == 0);
+/// assert_eq!(split, 2);
+/// assert_eq!(slice, [3, 6, 1, 2, 4, 5, 7]);
+/// ```
+pub fn partition_stable<T, F>(slice: &mut [T], mut pred: F) -> usize
+where
+    F: FnMut(&T) -> bool,
+{
+    fn inner<T, F: FnMut(&T) -> bool>(slice: &mut [T], pred: &mut F) -> usize {
+        match slice.len() {
+            0 => 0,
+            1 => pred(&slice[0]) as usize,
+            len => {
+                let mid = len / 2;
+                let left = inner(&mut slice[..mid], pred);
+                let right = inner(&mut slice[mid..], pred);
+
+                // [true, false | true, false] -> [true, true | false, false]
+                util::rotate_left(&mut slice[left..(mid + right)], mid - left);
+                left + right
+            }
+        }
+    }
+
+    inner(slice, &mut pred)
+}
+
+/// An unstable in-place partition of a slice by a predicate.
+///
+/// Moves all the elements for which `pred` returns `true` to the front of the
+/// slice and returns the number of such elements. Scans the slice from both
+/// ends, and swaps the first mismatched pair it finds; the order within both
+/// groups is not preserved.
+///
+/// See also [`partition_stable`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [7, -2, 4, -9, 0, 3];
+/// let split = sort::partition(&mut slice, |&x| x < 0);
+/// assert_eq!(split, 2);
+/// assert!(slice[..split].iter().all(|&x| x < 0));
+/// assert!(slice[split..].iter().all(|&x| x >= 0));
+/// ```
+pub fn partition<T, F>(slice: &mut [T], mut pred: F) -> usize
+where
+    F: FnMut(&T) -> bool,
+{
+    let mut lo = 0;
+    let mut hi = slice.len();
+    loop {
+        while lo < hi && pred(&slice[lo]) {
+            lo += 1;
+        }
+        while lo < hi && !pred(&slice[hi - 1]) {
+            hi -= 1;
+        }
+
+        if lo >= hi {
+            return lo;
+        }
+
+        slice.swap(lo, hi - 1);
+        lo += 1;
+        hi -= 1;
+    }
+}
+
+/// An unstable in-place partition of a slice by a predicate on a key.
+///
+/// Works like [`partition`], but `pred` is applied to the key extracted from
+/// every element by `key_fn`.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [("a", 3), ("b", 12), ("c", 8), ("d", 1)];
+/// let split = sort::partition_by_key(&mut slice, |p| p.1, |&k| k > 5);
+/// assert_eq!(split, 2);
+/// assert!(slice[..split].iter().all(|p| p.1 > 5));
+/// ```
+pub fn partition_by_key<T, K, F, P>(slice: &mut [T], mut key_fn: F, mut pred: P) -> usize
+where
+    F: FnMut(&T) -> K,
+    P: FnMut(&K) -> bool,
+{
+    partition(slice, |v| pred(&key_fn(v)))
+}
+
+/// A three-way partition of a slice around a value.
+///
+/// Rearranges the slice into three regions: elements smaller than `pivot`,
+/// equal to it and greater than it (the Dutch national flag problem). Returns
+/// `(lt, gt)`, so that `slice[..lt]` is smaller, `slice[lt..gt]` is equal and
+/// `slice[gt..]` is greater than the pivot.
+///
+/// The pivot doesn't have to be an element of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [5, 1, 7, 5, 3, 9, 5];
+/// let (lt, gt) = sort::partition3(&mut slice, &5);
+/// assert_eq!((lt, gt), (2, 5));
+/// assert_eq!(slice[lt..gt], [5, 5, 5]);
+/// ```
+pub fn partition3<T: Ord>(slice: &mut [T], pivot: &T) -> (usize, usize) {
+    let mut lt = 0;
+    let mut i = 0;
+    let mut gt = slice.len();
+
+    while i < gt {
+        match slice[i].cmp(pivot) {
+            Ordering::Less => {
+                slice.swap(lt, i);
+                lt += 1;
+                i += 1;
+            }
+            Ordering::Equal => i += 1,
+            Ordering::Greater => {
+                gt -= 1;
+                slice.swap(i, gt);
+            }
+        }
+    }
+
+    (lt, gt)
+}
+
+/// Swaps two `size`-byte elements of a byte slice.
+fn swap_bytes(data: &mut [u8], size: usize, i: usize, j: usize) {
+    if i == j {
+        return;
+    }
+
+    let (i, j) = if i < j { (i, j) } else { (j, i) };
+    let (head, tail) = data.split_at_mut(j * size);
+    head[(i * size)..((i + 1) * size)].swap_with_slice(&mut tail[..size]);
+}
+
+/// An implementation of quick sort over type-erased elements.
+///
+/// Sorts a byte slice made of consecutive `elem_size`-byte elements, comparing
+/// them with `cmp`. Unlike the generic sorts, this function is compiled only
+/// once no matter how many element types it is used with, which matters when
+/// the size of the binary is limited.
+///
+/// Uses the middle element as the pivot, which is swapped to the front while
+/// partitioning, and partitions the elements into three parts, so equal
+/// elements don't degrade it. It recurses into the smaller part only, so the
+/// stack depth is `O(log n)`.
+///
+/// # Panics
+///
+/// Panics if `elem_size` is 0 or the length of the slice is not a multiple of
+/// it.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// // pairs of (key, value) bytes
+/// let mut data = [3, 30, 1, 10, 2, 20];
+/// sort::quick_dyn(&mut data, 2, &mut |a, b| a[0].cmp(&b[0]));
+/// assert_eq!(data, [1, 10, 2, 20, 3, 30]);
+/// ```
+pub fn quick_dyn(data: &mut [u8], elem_size: usize, cmp: &mut dyn FnMut(&[u8], &[u8]) -> Ordering) {
+    assert!(elem_size != 0, "element size must be non-zero");
+    assert!(
+        data.len().is_multiple_of(elem_size),
+        "length of the data must be a multiple of the element size"
+    );
+
+    let size = elem_size;
+    let mut data = data;
+    let mut n = data.len() / size;
+    while n > 1 {
+        // the middle element is the pivot; it's kept on position 0 during the
+        // partitioning
+        swap_bytes(data, size, 0, n / 2);
+
+        let mut lt = 1;
+        let mut i = 1;
+        let mut gt = n;
+        while i < gt {
+            let ord = cmp(&data[(i * size)..((i + 1) * size)], &data[..size]);
+            match ord {
+                Ordering::Less => {
+                    swap_bytes(data, size, lt, i);
+                    lt += 1;
+                    i += 1;
+                }
+                Ordering::Equal => i += 1,
+                Ordering::Greater => {
+                    gt -= 1;
+                    swap_bytes(data, size, i, gt);
+                }
+            }
+        }
+        swap_bytes(data, size, 0, lt - 1);
+
+        let (left, rest) = data.split_at_mut((lt - 1) * size);
+        let right = &mut rest[((gt - lt + 1) * size)..];
+        if left.len() < right.len() {
+            quick_dyn(left, size, cmp);
+            data = right;
+        } else {
+            quick_dyn(right, size, cmp);
+            data = left;
+        }
+        n = data.len() / size;
+    }
+}
+
+/// A random-access storage of elements, which can be sorted in place.
+///
+/// It lets the `_storage` sorts, like [`quick_storage`], sort data that isn't
+/// a contiguous slice, like the columns of a struct of arrays, by comparing
+/// and swapping the elements by their positions.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort::{self, SortableStorage};
+/// use std::cmp::Ordering;
+///
+/// // a table stored as columns, sorted by the ages
+/// struct People {
+///     names: Vec<&'static str>,
+///     ages: Vec<u32>,
+/// }
+///
+/// impl SortableStorage for People {
+///     fn len(&self) -> usize {
+///         self.ages.len()
+///     }
+///
+///     fn compare(&self, i: usize, j: usize) -> Ordering {
+///         self.ages[i].cmp(&self.ages[j])
+///     }
+///
+///     fn swap(&mut self, i: usize, j: usize) {
+///         self.names.swap(i, j);
+///         self.ages.swap(i, j);
+///     }
+/// }
+///
+/// let mut people = People {
+///     names: vec!["Ann", "Bob", "Cid"],
+///     ages: vec![41, 12, 30],
+/// };
+/// sort::quick_storage(&mut people);
+/// assert_eq!(people.names, ["Bob", "Cid", "Ann"]);
+/// assert_eq!(people.ages, [12, 30, 41]);
+/// ```
+pub trait SortableStorage {
+    /// Returns the number of elements.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if there are no elements.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Compares the elements on positions `i` and `j`.
+    fn compare(&self, i: usize, j: usize) -> Ordering;
+
+    /// Swaps the elements on positions `i` and `j`.
+    fn swap(&mut self, i: usize, j: usize);
+}
+
+impl<T: Ord> SortableStorage for [T] {
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    fn compare(&self, i: usize, j: usize) -> Ordering {
+        self[i].cmp(&self[j])
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        <[T]>::swap(self, i, j);
+    }
+}
+
+impl<T: Ord> SortableStorage for VecDeque<T> {
+    fn len(&self) -> usize {
+        VecDeque::len(self)
+    }
+
+    fn compare(&self, i: usize, j: usize) -> Ordering {
+        self[i].cmp(&self[j])
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        VecDeque::swap(self, i, j);
+    }
+}
+
+/// Sorts a [`SortableStorage`] with [`bubble`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [1, 6, 3, -44, 11, 2];
+/// sort::bubble_storage(&mut slice[..]);
+/// assert_eq!(slice, [-44, 1, 2, 3, 6, 11]);
+/// ```
+pub fn bubble_storage<S: SortableStorage + ?Sized>(storage: &mut S) {
+    let mut n = storage.len();
+    while n > 1 {
+        let mut newn = 0;
+
+        for i in 1..n {
+            if storage.compare(i - 1, i) == Ordering::Greater {
+                storage.swap(i - 1, i);
+                newn = i;
+            }
+        }
+
+        n = newn;
+    }
+}
+
+/// Sorts a [`SortableStorage`] with [`quick`] sort.
+///
+/// The pivots are medians of three, and the shorter part is sorted first, so
+/// the recursion depth is `O(log n)`. It's not stable.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [5, 1, -5, 3, 9, 2, 19];
+/// sort::quick_storage(&mut slice[..]);
+/// assert_eq!(slice, [-5, 1, 2, 3, 5, 9, 19]);
+/// ```
+pub fn quick_storage<S: SortableStorage + ?Sized>(storage: &mut S) {
+    fn inner<S: SortableStorage + ?Sized>(storage: &mut S, mut range: Range<usize>) {
+        while range.len() > 1 {
+            let pivot = partition_storage(storage, range.clone());
+            let left = range.start..pivot;
+            let right = (pivot + 1)..range.end;
+            if left.len() < right.len() {
+                inner(storage, left);
+                range = right;
+            } else {
+                inner(storage, right);
+                range = left;
+            }
+        }
+    }
+
+    let n = storage.len();
+    inner(storage, 0..n);
+}
+
+/// Partitions a range of a storage, which has at least 2 elements, around the
+/// median of three. Returns the final position of the pivot.
+fn partition_storage<S: SortableStorage + ?Sized>(storage: &mut S, range: Range<usize>) -> usize {
+    let (lo, p) = (range.start, range.end - 1);
+    let mid = lo + (p - lo) / 2;
+    if storage.compare(mid, lo) == Ordering::Less {
+        storage.swap(mid, lo);
+    }
+    if storage.compare(p, lo) == Ordering::Less {
+        storage.swap(p, lo);
+    }
+    if storage.compare(mid, p) == Ordering::Less {
+        storage.swap(mid, p);
+    }
+
+    // the elements before i are not greater than the pivot, and the ones
+    // from j are not smaller
+    let (mut i, mut j) = (lo, p);
+    while i < j {
+        if storage.compare(i, p) == Ordering::Less {
+            i += 1;
+        } else if storage.compare(j - 1, p) == Ordering::Greater {
+            j -= 1;
+        } else {
+            // moving equal elements to both sides keeps the parts balanced
+            j -= 1;
+            storage.swap(i, j);
+            i += 1;
+        }
+    }
+
+    storage.swap(j, p);
+    j
+}
+
+/// Sorts a [`SortableStorage`] with [`heap`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [6, -1, 14, 3, 3, 0, 8];
+/// sort::heap_storage(&mut slice[..]);
+/// assert_eq!(slice, [-1, 0, 3, 3, 6, 8, 14]);
+/// ```
+pub fn heap_storage<S: SortableStorage + ?Sized>(storage: &mut S) {
+    fn sift_down<S: SortableStorage + ?Sized>(storage: &mut S, mut root: usize, len: usize) {
+        loop {
+            let mut child = 2 * root + 1;
+            if child >= len {
+                break;
+            }
+            if child + 1 < len && storage.compare(child, child + 1) == Ordering::Less {
+                child += 1;
+            }
+            if storage.compare(root, child) != Ordering::Less {
+                break;
+            }
+
+            storage.swap(root, child);
+            root = child;
+        }
+    }
+
+    let n = storage.len();
+    for root in (0..(n / 2)).rev() {
+        sift_down(storage, root, n);
+    }
+
+    for end in (1..n).rev() {
+        storage.swap(0, end);
+        sift_down(storage, 0, end);
+    }
+}
+
+/// Parallel columns sorted by their first column, for [`co_sort`].
+struct CoSorted<'a, K, S> {
+    keys: &'a mut [K],
+    swap: S,
+}
+
+impl<K, S> SortableStorage for CoSorted<'_, K, S>
+where
+    K: Ord,
+    S: FnMut(usize, usize),
+{
+    fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    fn compare(&self, i: usize, j: usize) -> Ordering {
+        self.keys[i].cmp(&self.keys[j])
+    }
+
+    fn swap(&mut self, i: usize, j: usize) {
+        self.keys.swap(i, j);
+        (self.swap)(i, j);
+    }
+}
+
+/// Sorts the keys with [`quick_storage`] sort, and reorders the values the
+/// same way, so that the parallel slices stay aligned.
+///
+/// Nothing is allocated, and it's not stable. See the
+/// [`co_sort!`](crate::co_sort!) macro for more columns.
+///
+/// # Panics
+///
+/// Panics if the slices have different lengths.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut ages = [41, 12, 30];
+/// let mut names = ["Ann", "Bob", "Cid"];
+/// sort::co_sort(&mut ages, &mut names);
+/// assert_eq!(ages, [12, 30, 41]);
+/// assert_eq!(names, ["Bob", "Cid", "Ann"]);
+/// ```
+pub fn co_sort<K: Ord, V>(keys: &mut [K], values: &mut [V]) {
+    assert_eq!(
+        keys.len(),
+        values.len(),
+        "the slices have different lengths"
+    );
+    quick_storage(&mut CoSorted {
+        keys,
+        swap: |i, j| values.swap(i, j),
+    });
+}
+
+/// Sorts the keys like [`co_sort`], calling every function of `columns` to
+/// swap the elements of the other columns. It's used by the
+/// [`co_sort!`](crate::co_sort!) macro.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut keys = [2, 0, 1];
+/// let mut values = vec!['c', 'a', 'b'];
+/// sort::co_sort_columns(&mut keys, &mut [&mut |i, j| values.swap(i, j)]);
+/// assert_eq!(values, ['a', 'b', 'c']);
+/// ```
+pub fn co_sort_columns<K: Ord>(keys: &mut [K], columns: &mut [&mut dyn FnMut(usize, usize)]) {
+    quick_storage(&mut CoSorted {
+        keys,
+        swap: |i, j| {
+            for swap in columns.iter_mut() {
+                swap(i, j);
+            }
+        },
+    });
+}
+
+/// Sorts the first slice, and reorders all the other slices the same way.
+///
+/// It's [`co_sort`](crate::sort::co_sort()) for any number of columns. The
+/// arguments can be anything with `len` and `swap` methods, like vectors and
+/// mutable slices.
+///
+/// # Panics
+///
+/// Panics if the columns have different lengths.
+///
+/// # Examples
+/// ```
+/// use search_sort::co_sort;
+///
+/// let mut ages = vec![41, 12, 30];
+/// let mut names = vec!["Ann", "Bob", "Cid"];
+/// let mut cities = vec!["Oslo", "Rome", "Lima"];
+/// co_sort!(ages, names, cities);
+/// assert_eq!(names, ["Bob", "Cid", "Ann"]);
+/// assert_eq!(cities, ["Rome", "Lima", "Oslo"]);
+/// ```
+#[macro_export]
+macro_rules! co_sort {
+    ($keys:expr $(, $column:expr)* $(,)?) => {{
+        let keys = &mut $keys[..];
+        $(
+            assert_eq!(
+                keys.len(),
+                $column.len(),
+                "the columns have different lengths"
+            );
+        )*
+        $crate::sort::co_sort_columns(
+            keys,
+            &mut [$(&mut |i: usize, j: usize| $column.swap(i, j)),*],
+        );
+    }};
+}
+
+/// Sorts a [`VecDeque`] with [`bubble`] sort, without making it contiguous.
+///
+/// The elements are accessed by their positions in the deque, so the
+/// elements are never moved to join its two internal slices.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+/// use std::collections::VecDeque;
+///
+/// let mut deque: VecDeque<_> = [3, 1, 2].iter().copied().collect();
+/// deque.push_front(4);
+/// sort::bubble_deque(&mut deque);
+/// assert_eq!(deque, [1, 2, 3, 4]);
+/// ```
+pub fn bubble_deque<T: Ord>(deque: &mut VecDeque<T>) {
+    bubble_storage(deque);
+}
+
+/// Sorts a [`VecDeque`] with [`quick`] sort, without making it contiguous.
+///
+/// The parts that span both internal slices of the deque are partitioned by
+/// the positions of the elements, around the median of three. The other parts
+/// lie in one of the slices, and are sorted with [`quick`] sort directly. Only
+/// one part on every level spans both slices, so the rotation of
+/// [`VecDeque::make_contiguous`] is avoided at the cost of `O(n)` extra
+/// indexing per level.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+/// use std::collections::VecDeque;
+///
+/// let mut deque: VecDeque<_> = (0..100).collect();
+/// deque.rotate_left(30);
+/// sort::quick_deque(&mut deque);
+/// assert!(deque.iter().copied().eq(0..100));
+/// ```
+pub fn quick_deque<T: Ord>(deque: &mut VecDeque<T>) {
+    let mut range = 0..deque.len();
+    loop {
+        let split = deque.as_slices().0.len();
+        if range.end <= split {
+            quick(&mut deque.as_mut_slices().0[range]);
+            return;
+        } else if range.start >= split {
+            quick(&mut deque.as_mut_slices().1[(range.start - split)..(range.end - split)]);
+            return;
+        }
+
+        let pivot = partition_storage(deque, range.clone());
+        if pivot < split {
+            quick(&mut deque.as_mut_slices().0[range.start..pivot]);
+            range = (pivot + 1)..range.end;
+        } else {
+            let (_, back) = deque.as_mut_slices();
+            quick(&mut back[(pivot + 1 - split)..(range.end - split)]);
+            range = range.start..pivot;
+        }
+    }
+}
+
+/// Sorts a [`VecDeque`] with [`merge`] sort, without making it contiguous.
+///
+/// Both internal slices of the deque are sorted with [`merge`] sort, and then
+/// merged by the positions of the elements, with the first slice copied to a
+/// buffer. It's stable.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+/// use std::collections::VecDeque;
+///
+/// let mut deque: VecDeque<_> = (0..100).rev().collect();
+/// deque.rotate_left(30);
+/// sort::merge_deque(&mut deque);
+/// assert!(deque.iter().copied().eq(0..100));
+/// ```
+pub fn merge_deque<T: Ord + Clone>(deque: &mut VecDeque<T>) {
+    let (front, back) = deque.as_mut_slices();
+    merge(front);
+    merge(back);
+    if front.is_empty() || back.is_empty() || front[front.len() - 1] <= back[0] {
+        return;
+    }
+
+    let mid = front.len();
+    let mut buf = front.to_vec();
+    let (mut i, mut j) = (0, mid);
+    while i < mid && j < deque.len() {
+        if deque[j] < buf[i] {
+            deque.swap(i + j - mid, j);
+            j += 1;
+        } else {
+            mem::swap(&mut deque[i + j - mid], &mut buf[i]);
+            i += 1;
+        }
+    }
+
+    for (k, x) in buf.into_iter().enumerate().skip(i) {
+        deque[k + j - mid] = x;
+    }
+}
+
+/// Reorders a slice so that the element on position `n` is the one that would
+/// be there after sorting, and returns it.
+///
+/// The elements before it are not greater than it, and the ones after it are
+/// not smaller. The pivots are chosen by the median-of-medians algorithm: the
+/// medians of groups of five elements are found, and their median is selected
+/// recursively. Such a pivot has at least 30% of the elements on each side,
+/// so it takes `O(n)` comparisons in the worst case, unlike quickselect. The
+/// elements equal to the pivot are gathered around it, so duplicates don't
+/// slow it down.
+///
+/// # Panics
+///
+/// Panics if `n` is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [9, 1, 8, 2, 7, 3, 6, 4, 5];
+/// assert_eq!(*sort::select_nth_deterministic(&mut slice, 2), 3);
+/// assert!(slice[..2].iter().all(|&x| x < 3));
+/// assert!(slice[3..].iter().all(|&x| x > 3));
+/// ```
+pub fn select_nth_deterministic<T: Ord>(slice: &mut [T], n: usize) -> &mut T {
+    select_nth_deterministic_by(slice, n, T::cmp)
+}
+
+/// Like [`select_nth_deterministic`], but compares the elements with a
+/// comparator function.
+///
+/// # Panics
+///
+/// Panics if `n` is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [9, 1, 8, 2, 7, 3, 6, 4, 5];
+/// let second_largest = sort::select_nth_deterministic_by(&mut slice, 1, |a, b| b.cmp(a));
+/// assert_eq!(*second_largest, 8);
+/// ```
+pub fn select_nth_deterministic_by<T, F>(slice: &mut [T], n: usize, mut compare: F) -> &mut T
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    assert!(n < slice.len(), "the position is out of bounds");
+    select_mom(slice, n, &mut |a, b| compare(a, b) == Ordering::Less);
+    &mut slice[n]
+}
+
+/// The median-of-medians selection of [`select_nth_deterministic_by`].
+fn select_mom<T, F>(mut slice: &mut [T], mut n: usize, is_less: &mut F)
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    loop {
+        let len = slice.len();
+        if len <= 10 {
+            insertion_by_less(slice, &mut *is_less);
+            return;
+        }
+
+        // the medians of the groups of five are moved to the front
+        let groups = len / 5;
+        for g in 0..groups {
+            insertion_by_less(&mut slice[(5 * g)..(5 * g + 5)], &mut *is_less);
+            slice.swap(g, 5 * g + 2);
+        }
+        select_mom(&mut slice[..groups], groups / 2, is_less);
+        slice.swap(0, groups / 2);
+
+        // three-way partition of the rest around the pivot in slice[0]: the
+        // smaller elements end in rest[..lt], the equal ones in rest[lt..gt]
+        // and the greater ones in rest[gt..]
+        let (pivot, rest) = slice.split_at_mut(1);
+        let pivot = &pivot[0];
+        let (mut lt, mut i, mut gt) = (0, 0, rest.len());
+        while i < gt {
+            if is_less(&rest[i], pivot) {
+                rest.swap(lt, i);
+                lt += 1;
+                i += 1;
+            } else if is_less(pivot, &rest[i]) {
+                gt -= 1;
+                rest.swap(i, gt);
+            } else {
+                i += 1;
+            }
+        }
+        slice.swap(0, lt);
+
+        // in the whole slice the equal elements are in lt..(gt + 1)
+        let s = slice;
+        if n < lt {
+            slice = &mut s[..lt];
+        } else if n > gt {
+            slice = &mut s[(gt + 1)..];
+            n -= gt + 1;
+        } else {
+            return;
+        }
+    }
+}
+
+/// Reorders a slice so that every position in `positions` has the element
+/// that would be there after sorting, like [`select_nth_deterministic`] for
+/// all of them at once.
+///
+/// The positions are sorted first. Then the middle one is selected, which
+/// splits the slice into two parts, and the positions in every part are
+/// selected recursively in it. Every level of the recursion takes linear
+/// time, so `k` positions take `O(n log k)` comparisons, instead of `O(nk)`
+/// for separate selections.
+///
+/// # Panics
+///
+/// Panics if any of the positions is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut latencies: Vec<u32> = (1..=100).rev().collect();
+/// let mut positions = [98, 49, 89];
+/// sort::multi_select(&mut latencies, &mut positions);
+/// assert_eq!(positions, [49, 89, 98]);
+/// assert_eq!([latencies[49], latencies[89], latencies[98]], [50, 90, 99]);
+/// ```
+pub fn multi_select<T: Ord>(slice: &mut [T], positions: &mut [usize]) {
+    multi_select_by(slice, positions, T::cmp);
+}
+
+/// Like [`multi_select`], but compares the elements with a comparator function.
+///
+/// # Panics
+///
+/// Panics if any of the positions is out of bounds of the slice.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: Vec<_> = (0..10).collect();
+/// sort::multi_select_by(&mut slice, &mut [0, 1], |a, b| b.cmp(a));
+/// assert_eq!(slice[..2], [9, 8]);
+/// ```
+pub fn multi_select_by<T, F>(slice: &mut [T], positions: &mut [usize], mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    fn inner<T, F>(slice: &mut [T], offset: usize, positions: &[usize], is_less: &mut F)
+    where
+        F: FnMut(&T, &T) -> bool,
+    {
+        if positions.is_empty() {
+            return;
+        }
+
+        let mid = positions.len() / 2;
+        let p = positions[mid] - offset;
+        select_mom(slice, p, is_less);
+
+        // the same position can be requested more than once
+        let before = positions[..mid].partition_point(|&q| q < positions[mid]);
+        let after = mid + positions[mid..].partition_point(|&q| q == positions[mid]);
+        let (left, right) = slice.split_at_mut(p);
+        inner(left, offset, &positions[..before], is_less);
+        inner(
+            &mut right[1..],
+            offset + p + 1,
+            &positions[after..],
+            is_less,
+        );
+    }
+
+    intro(positions);
+    if let Some(&last) = positions.last() {
+        assert!(last < slice.len(), "the position is out of bounds");
+    }
+    inner(slice, 0, positions, &mut |a, b| {
+        compare(a, b) == Ordering::Less
+    });
+}
+
+/// Partially sorts a slice, so that its `k` smallest elements are at the
+/// front in ascending order. The order of the other elements is unspecified.
+///
+/// The `k`-th smallest element is found by [`select_nth_deterministic`], and
+/// only the elements before it are sorted with [`intro`] sort, so it takes
+/// `O(n + k log k)` time. If `k` is at least the length of the slice, the whole
+/// slice is sorted. It's not stable.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: Vec<_> = (0..1000).map(|x| (x * 7919) % 1009).collect();
+/// sort::partial(&mut slice, 3);
+/// assert_eq!(slice[..3], [0, 1, 2]);
+/// ```
+pub fn partial<T: Ord>(slice: &mut [T], k: usize) {
+    partial_by(slice, k, T::cmp);
+}
+
+/// Like [`partial`], but compares the elements with a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice: Vec<_> = (0..1000).collect();
+/// sort::partial_by(&mut slice, 3, |a, b| b.cmp(a));
+/// assert_eq!(slice[..3], [999, 998, 997]);
+/// ```
+pub fn partial_by<T, F>(slice: &mut [T], k: usize, mut compare: F)
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    if k == 0 {
+        return;
+    } else if k < slice.len() {
+        select_mom(slice, k - 1, &mut |a, b| compare(a, b) == Ordering::Less);
+    }
+
+    let k = k.min(slice.len());
+    intro_by(&mut slice[..k], compare);
+}
+
+/// Returns references to the `k` largest elements of a slice, from the
+/// largest one, without changing the slice.
+///
+/// The elements are kept in a min-heap of at most `k` elements, so it takes
+/// `O(n log k)` time and `O(k)` additional memory. If `k` is at least the
+/// length of the slice, all the elements are returned. Of equal elements,
+/// the ones that come first in the slice are preferred, but their order in
+/// the result is unspecified.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let slice = [4, 9, 1, 7, 3];
+/// assert_eq!(sort::top_k(&slice, 2), [&9, &7]);
+/// assert_eq!(slice, [4, 9, 1, 7, 3]);
+/// ```
+pub fn top_k<T: Ord>(slice: &[T], k: usize) -> Vec<&T> {
+    top_k_iter(slice, k)
+}
+
+/// Like [`top_k`], but returns clones of the elements.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let words = vec![String::from("pear"), String::from("fig"), String::from("apple")];
+/// let top: Vec<String> = sort::top_k_cloned(&words, 2);
+/// assert_eq!(top, ["pear", "fig"]);
+/// ```
+pub fn top_k_cloned<T: Ord + Clone>(slice: &[T], k: usize) -> Vec<T> {
+    top_k(slice, k).into_iter().cloned().collect()
+}
+
+/// Returns the `k` largest items of an iterator, from the largest one.
+///
+/// Only a min-heap of at most `k` items is kept while the iterator is
+/// consumed, so it works for streams of any length in `O(k)` memory and
+/// `O(n log k)` time. If there are fewer than `k` items, all of them are
+/// returned.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let top = sort::top_k_iter((0..1_000_000).map(|x| x % 1000), 3);
+/// assert_eq!(top, [999, 999, 999]);
+///
+/// let words = "the quick brown fox jumps over the lazy dog".split(' ');
+/// assert_eq!(sort::top_k_iter(words, 2), ["the", "the"]);
+/// ```
+pub fn top_k_iter<I>(iter: I, k: usize) -> Vec<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Ord,
+{
+    k_greatest(iter.into_iter(), k, |a, b| a < b)
+}
+
+/// Returns the median of a slice, or `None` if it's empty.
+///
+/// For a slice of even length, it's the lower of the two middle elements,
+/// so it's always one of the elements. The slice is reordered by
+/// [`select_nth_deterministic`], so it takes linear time.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut odd = [5, 1, 4, 2, 3];
+/// assert_eq!(sort::median(&mut odd), Some(&3));
+///
+/// let mut even = [5, 1, 4, 2];
+/// assert_eq!(sort::median(&mut even), Some(&2));
+///
+/// assert_eq!(sort::median::<i32>(&mut []), None);
+/// ```
+pub fn median<T: Ord>(slice: &mut [T]) -> Option<&T> {
+    if slice.is_empty() {
+        return None;
+    }
+    let mid = (slice.len() - 1) / 2;
+    Some(select_nth_deterministic(slice, mid))
+}
+
+/// Returns the quantiles of a slice for every fraction in `qs`, in the same
+/// order, or `None` if the slice is empty.
+///
+/// The quantile `q` is the element at the position `⌊q·(n - 1)⌋` of the
+/// sorted slice, so `0.0` is the minimum, `1.0` is the maximum and `0.5` is
+/// the [`median`]. Values are never interpolated. All the positions are
+/// selected at once by [`multi_select`], which reorders the slice.
+///
+/// # Panics
+///
+/// Panics if any of the fractions isn't in `0.0..=1.0`.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut latencies: Vec<u32> = (1..=100).rev().collect();
+/// let q = sort::quantiles(&mut latencies, &[0.5, 0.9, 0.99, 1.0]).unwrap();
+/// assert_eq!(q, [&50, &90, &99, &100]);
+/// ```
+pub fn quantiles<'a, T: Ord>(slice: &'a mut [T], qs: &[f64]) -> Option<Vec<&'a T>> {
+    if slice.is_empty() {
+        return None;
+    }
+
+    let last = (slice.len() - 1) as f64;
+    let positions: Vec<_> = qs
+        .iter()
+        .map(|&q| {
+            assert!((0.0..=1.0).contains(&q), "the quantile isn't in 0..=1");
+            (q * last).floor() as usize
+        })
+        .collect();
+    multi_select(slice, &mut positions.clone());
+
+    let slice = &*slice;
+    Some(positions.iter().map(|&p| &slice[p]).collect())
+}
+
+/// Returns the `k` greatest items of an iterator by `is_less`, from the
+/// greatest one.
+fn k_greatest<I, F>(mut iter: I, k: usize, mut is_less: F) -> Vec<I::Item>
+where
+    I: Iterator,
+    F: FnMut(&I::Item, &I::Item) -> bool,
+{
+    // a min-heap of the greatest items so far
+    let mut is_greater = |a: &I::Item, b: &I::Item| is_less(b, a);
+    let mut heap: Vec<_> = iter.by_ref().take(k).collect();
+    for root in (0..(heap.len() / 2)).rev() {
+        sift_down(&mut heap, root, &mut is_greater);
+    }
+
+    if k > 0 {
+        for item in iter {
+            if is_greater(&item, &heap[0]) {
+                heap[0] = item;
+                sift_down(&mut heap, 0, &mut is_greater);
+            }
+        }
+    }
+
+    heap_by_less(&mut heap, is_greater);
+    heap
+}
+
+/// Inserts a value into a sorted vector, keeping it sorted, and returns its
+/// position.
+///
+/// The position is found by binary search, after all the elements equal to
+/// the value, so inserting elements one by one keeps them in a stable order.
+/// Moving the following elements takes `O(n)` time.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut vec = vec![1, 3, 3, 7];
+/// assert_eq!(sort::insort(&mut vec, 3), 3);
+/// assert_eq!(sort::insort(&mut vec, 0), 0);
+/// assert_eq!(vec, [0, 1, 3, 3, 3, 7]);
+/// ```
+pub fn insort<T: Ord>(vec: &mut Vec<T>, value: T) -> usize {
+    insort_by(vec, value, T::cmp)
+}
+
+/// Like [`insort`], but for a vector sorted by a comparator function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut vec = vec![9, 4, 1];
+/// sort::insort_by(&mut vec, 5, |a, b| b.cmp(a));
+/// assert_eq!(vec, [9, 5, 4, 1]);
+/// ```
+pub fn insort_by<T, F>(vec: &mut Vec<T>, value: T, mut compare: F) -> usize
+where
+    F: FnMut(&T, &T) -> Ordering,
+{
+    let pos = vec.partition_point(|v| compare(v, &value) != Ordering::Greater);
+    vec.insert(pos, value);
+    pos
+}
+
+/// Like [`insort`], but for a vector sorted by a key extraction function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut vec = vec![(1, 'a'), (3, 'b')];
+/// sort::insort_by_key(&mut vec, (1, 'c'), |p| p.0);
+/// assert_eq!(vec, [(1, 'a'), (1, 'c'), (3, 'b')]);
+/// ```
+pub fn insort_by_key<T, K, F>(vec: &mut Vec<T>, value: T, mut key_fn: F) -> usize
+where
+    F: FnMut(&T) -> K,
+    K: Ord,
+{
+    insort_by(vec, value, |a, b| key_fn(a).cmp(&key_fn(b)))
+}
+
+/// Moves the first element of every group of adjacent equal elements to the
+/// front of a slice, and returns the number of these elements.
+///
+/// After sorting, it removes all the duplicates. The order of the kept
+/// elements doesn't change, and the duplicates are moved after them in an
+/// unspecified order, so a [`Vec`] can be just truncated. It takes linear
+/// time.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut vec = vec![3, 1, 3, 2, 1, 3];
+/// sort::intro(&mut vec);
+/// let len = sort::dedup(&mut vec);
+/// vec.truncate(len);
+/// assert_eq!(vec, [1, 2, 3]);
+/// ```
+pub fn dedup<T: PartialEq>(slice: &mut [T]) -> usize {
+    dedup_by(slice, |a, b| a == b)
+}
+
+/// Like [`dedup`], but decides if the elements are equal with a function.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = ["apple", "Apple", "fig", "FIG", "kiwi"];
+/// let len = sort::dedup_by(&mut slice, |a, b| a.eq_ignore_ascii_case(b));
+/// assert_eq!(slice[..len], ["apple", "fig", "kiwi"]);
+/// ```
+pub fn dedup_by<T, F>(slice: &mut [T], mut same: F) -> usize
+where
+    F: FnMut(&T, &T) -> bool,
+{
+    if slice.is_empty() {
+        return 0;
+    }
+
+    // the kept elements are before len
+    let mut len = 1;
+    for i in 1..slice.len() {
+        if !same(&slice[len - 1], &slice[i]) {
+            slice.swap(len, i);
+            len += 1;
+        }
+    }
+
+    len
+}
+
+/// Like [`dedup`], but compares the keys extracted by `key_fn`.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut slice = [10, 12, 25, 31, 38];
+/// let len = sort::dedup_by_key(&mut slice, |x| *x / 10);
+/// assert_eq!(slice[..len], [10, 25, 31]);
+/// ```
+pub fn dedup_by_key<T, K, F>(slice: &mut [T], mut key_fn: F) -> usize
+where
+    F: FnMut(&T) -> K,
+    K: PartialEq,
+{
+    dedup_by(slice, |a, b| key_fn(a) == key_fn(b))
+}
+
+/// Sorts a vector and removes the duplicates, so that it becomes a sorted
+/// set.
+///
+/// It's done with [`intro`] sort and [`dedup`], in `O(n log n)` time. For
+/// small unsigned integers [`unique_counting`] is faster, and for other
+/// numbers [`unique_radix`].
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut vec = vec!["pear", "fig", "pear", "apple", "fig"];
+/// sort::unique(&mut vec);
+/// assert_eq!(vec, ["apple", "fig", "pear"]);
+/// ```
+pub fn unique<T: Ord>(vec: &mut Vec<T>) {
+    intro(vec);
+    let len = dedup(vec);
+    vec.truncate(len);
+}
+
+/// Like [`unique`], but the vector is sorted with [`radix_lsd`] sort, without
+/// comparisons.
+///
+/// The duplicates are found with `==`, so NaNs are never removed, and `0.0`
+/// is removed as a duplicate of `-0.0`, which is sorted before it.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut vec = vec![7i64, -2, 7, 1_000_000, -2];
+/// sort::unique_radix(&mut vec);
+/// assert_eq!(vec, [-2, 7, 1_000_000]);
+/// ```
+pub fn unique_radix<T: Radix + PartialEq>(vec: &mut Vec<T>) {
+    radix_lsd(vec);
+    let len = dedup(vec);
+    vec.truncate(len);
+}
+
+/// Like [`unique`], but the values are counted in a histogram, like in
+/// [`counting`] sort, and every counted value is written once.
+///
+/// The histogram spans the values from the smallest to the largest one, so
+/// it's fast only when they're close. If the span is at least four times the
+/// length of the vector, the values are sorted by [`unique`] instead, so the
+/// histogram never takes more memory than that.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut vec = vec![103u8, 100, 103, 101, 100];
+/// sort::unique_counting(&mut vec);
+/// assert_eq!(vec, [100, 101, 103]);
+/// ```
+pub fn unique_counting<T: Unsigned>(vec: &mut Vec<T>) {
+    let (min, max) = match min_max(vec) {
+        Some((min, max)) => (min.to_usize(), max.to_usize()),
+        None => return,
+    };
+
+    let span = max - min;
+    if span >= vec.len().saturating_mul(4) {
+        return unique(vec);
+    }
+
+    let histogram = histogram(vec, |v| v.to_usize() - min, span + 1);
+    vec.clear();
+    for (k, &count) in histogram.iter().enumerate() {
+        if count > 0 {
+            vec.push(T::from_usize(min + k));
+        }
+    }
+}
+
+/// Returns every distinct value of a sorted slice with the number of its
+/// occurrences, in order.
+///
+/// The groups of equal elements are found by
+/// [`group_boundaries`](search::group_boundaries), so long groups take only
+/// a logarithmic number of comparisons.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let mut words = ["to", "be", "or", "not", "to", "be"];
+/// sort::intro(&mut words);
+/// assert_eq!(
+///     sort::counts(&words),
+///     [(&"be", 2), (&"not", 1), (&"or", 1), (&"to", 2)]
+/// );
+/// ```
+pub fn counts<T: Ord>(slice: &[T]) -> Vec<(&T, usize)> {
+    search::group_boundaries(slice)
+        .map(|group| (&slice[group.start], group.len()))
+        .collect()
+}
+
+/// Returns the `k` most frequent values of a slice with the numbers of their
+/// occurrences, from the most frequent one. Values with equal numbers are
+/// ordered from the smallest one.
+///
+/// References to the elements are sorted with [`intro`] sort, so the slice
+/// isn't changed. Then the values are counted by [`counts`], and the `k` most
+/// frequent of them are sorted to the front by [`partial`] sort.
+///
+/// # Examples
+/// ```
+/// use search_sort::sort;
+///
+/// let words: Vec<_> = "the cat and the dog and the bird".split(' ').collect();
+/// assert_eq!(sort::top_k_frequent(&words, 2), [(&"the", 3), (&"and", 2)]);
+/// ```
+pub fn top_k_frequent<T: Ord>(slice: &[T], k: usize) -> Vec<(&T, usize)> {
+    let mut refs: Vec<_> = slice.iter().collect();
+    intro(&mut refs);
+
+    let mut groups: Vec<_> = counts(&refs).into_iter().map(|(&v, c)| (v, c)).collect();
+    partial_by(&mut groups, k, |a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    groups.truncate(k);
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::american_flag;
+    use super::apply_permutation;
+    use super::argsort;
+    use super::argsort_by_key;
+    use super::bead;
+    use super::bitonic;
+    use super::bitonic_by;
+    use super::bitonic_by_key;
+    use super::bitonic_desc;
+    use super::bitonic_schedule;
+    use super::bubble;
+    use super::bubble_by;
+    use super::bubble_by_key;
+    use super::bubble_deque;
+    use super::bubble_desc;
+    use super::bubble_storage;
+    use super::bucket;
+    use super::bucket_uniform;
+    use super::by_cached_key;
+    use super::cartesian_tree;
+    use super::cartesian_tree_by;
+    use super::cartesian_tree_by_key;
+    use super::cartesian_tree_desc;
+    use super::chunked;
+    use super::chunked_by;
+    use super::chunked_by_key;
+    use super::chunked_desc;
+    use super::chunked_range;
+    use super::co_sort;
+    use super::comb;
+    use super::comb_by;
+    use super::comb_by_key;
+    use super::comb_desc;
+    use super::count_inversions;
+    use super::count_inversions_by;
+    use super::counting;
+    use super::counting_by_key;
+    use super::counting_with_histogram;
+    use super::counts;
+    use super::dedup;
+    use super::dedup_by_key;
+    use super::difference;
+    use super::difference_iter;
+    use super::flash;
+    use super::flip;
+    use super::floats;
+    use super::group;
+    use super::group_by_key;
+    use super::heap;
+    use super::heap_by;
+    use super::heap_by_key;
+    use super::heap_desc;
+    use super::heap_range;
+    use super::heap_storage;
+    use super::histogram;
+    use super::insertion;
+    use super::insertion_by;
+    use super::insertion_by_key;
+    use super::insertion_desc;
+    use super::insort;
+    use super::insort_by_key;
+    use super::intersect_indices;
+    use super::intersect_iter;
+    use super::intersection;
+    use super::intro;
+    use super::intro_by;
+    use super::intro_by_key;
+    use super::intro_desc;
+    use super::intro_total;
+    use super::invert_permutation;
+    use super::is_sorted_by;
+    use super::is_sorted_by_key;
+    use super::is_sorted_desc;
+    use super::is_sorted_until;
+    use super::is_sorted_until_by_key;
+    use super::is_sorted_within;
+    use super::is_strictly_sorted;
+    use super::kmerge;
+    use super::median;
     use super::merge;
+    use super::merge_by;
+    use super::merge_by_key;
+    use super::merge_deque;
+    use super::merge_desc;
+    use super::merge_in_place;
+    use super::merge_in_place_by;
+    use super::merge_in_place_by_key;
+    use super::merge_in_place_desc;
+    use super::merge_insertion;
+    use super::merge_insertion_by;
+    use super::merge_insertion_by_key;
+    use super::merge_insertion_desc;
+    use super::merge_into;
+    use super::merge_join;
+    use super::merge_range;
+    use super::merge_total;
+    use super::merge_with;
+    use super::merge_with_buf;
+    use super::merge_with_cutoff;
+    use super::merged;
+    use super::min_max;
+    use super::min_max_by;
+    use super::multi_select;
+    use super::network;
+    use super::network_by;
+    use super::network_by_key;
+    use super::network_desc;
+    use super::odd_even;
+    use super::odd_even_by;
+    use super::odd_even_by_key;
+    use super::odd_even_desc;
+    use super::pancake;
+    use super::pancake_by;
+    use super::pancake_by_key;
+    use super::pancake_desc;
+    use super::partial;
+    use super::partition;
+    use super::partition3;
+    use super::partition_by_key;
+    use super::partition_stable;
+    use super::pigeonhole;
+    use super::quantiles;
     use super::quick;
+    use super::quick_by;
+    use super::quick_by_key;
+    use super::quick_deque;
+    use super::quick_desc;
+    use super::quick_dyn;
+    use super::quick_partition;
+    use super::quick_partition_with;
+    use super::quick_range;
+    use super::quick_storage;
+    use super::quick_total;
+    use super::quick_with;
+    use super::quick_with_cutoff;
+    use super::quick_with_pivot;
+    use super::radix_bytes;
+    use super::radix_lsd;
+    use super::radix_str;
+    use super::ranges;
+    use super::rle;
+    use super::rle_expand;
+    use super::rle_ref;
+    use super::runs;
+    use super::sample;
+    use super::sample_by;
+    use super::sample_by_key;
+    use super::sample_desc;
+    use super::select_nth_deterministic;
+    use super::select_nth_deterministic_by;
+    use super::selection;
+    use super::selection_by;
+    use super::selection_by_key;
+    use super::selection_desc;
+    use super::sorted_iter;
+    use super::spread;
+    use super::symmetric_difference;
+    use super::symmetric_difference_iter;
+    use super::tag_sort;
+    use super::tag_sort_gather;
     use super::test;
+    use super::tim;
+    use super::tim_by;
+    use super::tim_by_key;
+    use super::tim_desc;
+    use super::tim_range;
+    use super::tim_total;
+    use super::top_k;
+    use super::top_k_cloned;
+    use super::top_k_frequent;
+    use super::top_k_iter;
+    use super::try_merge_by;
+    use super::try_quick_by;
+    use super::union;
+    use super::union_iter;
+    use super::unique;
+    use super::unique_counting;
+    use super::unique_radix;
+    use super::weak_heap;
+    use super::weak_heap_by;
+    use super::weak_heap_by_key;
+    use super::weak_heap_desc;
+    use super::Direction;
+    use super::IteratorSortExt;
+    use super::Joined;
+    use super::NanError;
+    use super::NanPolicy;
+    use super::Ordering;
+    use super::Pivot;
+    use super::SortConfig;
+    use super::SortableStorage;
+    use super::INSERTION_CUTOFF;
+    use super::NETWORKS;
+
+    /// An element compared only by its key; the tag checks the stability.
+    #[derive(Debug, Clone)]
+    struct Tagged(i32, usize);
+
+    impl PartialEq for Tagged {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl Eq for Tagged {}
+
+    impl PartialOrd for Tagged {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for Tagged {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.0.cmp(&other.0)
+        }
+    }
+
+    /// Checks that the elements are sorted, and equal ones by their tags.
+    fn assert_stable(data: &[Tagged]) {
+        for w in data.windows(2) {
+            assert!(w[0].0 < w[1].0 || (w[0].0 == w[1].0 && w[0].1 < w[1].1));
+        }
+    }
+
+    #[test]
+    fn test_test() {
+        // lol
+        assert!(test(&[0, 3, 4, 10, 12]));
+        assert!(!test(&[6, 2, 1, 10, -2]));
+    }
+
+    #[test]
+    fn bubble_test() {
+        let mut data = [4, 2, 1, 8, 7, 9, -11];
+        bubble(&mut data);
+        assert_eq!(data, [-11, 1, 2, 4, 7, 8, 9]);
+    }
+
+    #[test]
+    fn quick_test() {
+        let mut data = [6, 7, 3, 5, 4, -12];
+        quick(&mut data);
+        assert_eq!(data, [-12, 3, 4, 5, 6, 7]);
+    }
+
+    #[test]
+    fn merge_test() {
+        let mut data1 = [6, 1, 2, 99, -1, 13, 7, 1];
+        let mut data2 = [5, 7, 11, -1, 2, 3];
+        let mut data3 = [11, 16, 20, 12, 13, 15];
+
+        merge(&mut data1);
+        merge(&mut data2);
+        merge(&mut data3);
+
+        assert_eq!(data1, [-1, 1, 1, 2, 6, 7, 13, 99]);
+        assert_eq!(data2, [-1, 2, 3, 5, 7, 11]);
+        assert_eq!(data3, [11, 12, 13, 15, 16, 20]);
+
+        let mut data4 = [3, 1, 2];
+        merge(&mut data4);
+        assert_eq!(data4, [1, 2, 3]);
+
+        // clustered duplicates keep their order, also without insertion sort
+        for cutoff in [0, 4, INSERTION_CUTOFF].iter() {
+            let mut data: Vec<_> = (0..300)
+                .map(|x| Tagged(x / 40 + (x % 3) * 10, x as usize))
+                .collect();
+            merge_with_cutoff(&mut data, *cutoff);
+            assert_stable(&data);
+
+            let mut data: Vec<_> = (0..300).map(|x| Tagged(x % 2, x as usize)).collect();
+            merge_with_cutoff(&mut data, *cutoff);
+            assert_stable(&data);
+        }
+
+        // sorting by the minor key first, then by the major one
+        let mut pairs: Vec<_> = (0..100).map(|x| ((x * 7) % 5, (x * 13) % 11)).collect();
+        merge_by_key(&mut pairs, |p| p.1);
+        merge_by_key(&mut pairs, |p| p.0);
+        let mut expected = pairs.clone();
+        expected.sort();
+        assert_eq!(pairs, expected);
+    }
+
+    #[test]
+    fn tag_sort_test() {
+        let records = [(3, 'a'), (1, 'b'), (2, 'c'), (1, 'd')];
+        assert_eq!(tag_sort(&records, |r| r.0), [1, 3, 2, 0]);
+        assert_eq!(
+            tag_sort_gather(&records, |r| r.0),
+            [(1, 'b'), (1, 'd'), (2, 'c'), (3, 'a')]
+        );
+        assert!(tag_sort(&[] as &[i32], |&r| r).is_empty());
+
+        // the keys don't have to be cloned
+        #[derive(PartialEq, Eq, PartialOrd, Ord)]
+        struct Key(i32);
+
+        let records: Vec<_> = (0..500).map(|x| (x * 37) % 11).collect();
+        let perm = tag_sort(&records, |&r| Key(r));
+        let mut expected: Vec<_> = (0..records.len()).collect();
+        expected.sort_by_key(|&i| records[i]);
+        assert_eq!(perm, expected);
+    }
+
+    #[test]
+    fn counting_with_histogram_test() {
+        let mut data = [5u32, 0, 2, 2, 9, 5, 1];
+        let histogram = counting_with_histogram(&mut data);
+        assert_eq!(data, [0, 1, 2, 2, 5, 5, 9]);
+        assert_eq!(histogram, [1, 1, 2, 0, 0, 2, 0, 0, 0, 1]);
+
+        let mut empty: [u8; 0] = [];
+        assert!(counting_with_histogram(&mut empty).is_empty());
+    }
+
+    #[test]
+    fn min_max_test() {
+        assert_eq!(min_max(&[3, 8, -1, 8, 2]), Some((&-1, &8)));
+        assert_eq!(min_max(&[5]), Some((&5, &5)));
+        assert_eq!(min_max::<u8>(&[]), None);
+
+        for len in 1..20 {
+            let slice: Vec<_> = (0..len).map(|x| Tagged((x * 7) % 5, x as usize)).collect();
+            let (min, max) = min_max(&slice).unwrap();
+            // the same elements as std, not only equal ones
+            assert_eq!(min.1, slice.iter().min().unwrap().1);
+            assert_eq!(max.1, slice.iter().max().unwrap().1);
+
+            let mut count = 0;
+            min_max_by(&slice, |a, b| {
+                count += 1;
+                a.cmp(b)
+            });
+            assert!(count <= 3 * len as usize / 2);
+        }
+    }
+
+    #[test]
+    fn histogram_test() {
+        let data = ["a", "bb", "cc", "", "ddd"];
+        assert_eq!(histogram(&data, |s| s.len(), 4), [1, 1, 2, 1]);
+        assert_eq!(histogram(&data, |s| s.len(), 5), [1, 1, 2, 1, 0]);
+        assert!(histogram(&[] as &[u8], |&v| v as usize, 0).is_empty());
+    }
+
+    #[test]
+    fn bitonic_schedule_test() {
+        assert!(bitonic_schedule(0).is_empty());
+        assert!(bitonic_schedule(1).is_empty());
+        assert_eq!(bitonic_schedule(2), [(0, 1, Direction::Ascending)]);
+
+        for n in 0..20 {
+            for &(i, j, _) in &bitonic_schedule(n) {
+                assert!(i < j && j < n);
+            }
+        }
+    }
+
+    #[test]
+    fn bitonic_test() {
+        let mut data = [9, 2, 7, 4, 4, -1, 0, 13, 6, 5, 3];
+        bitonic(&mut data);
+        assert_eq!(data, [-1, 0, 2, 3, 4, 4, 5, 6, 7, 9, 13]);
+
+        for n in 0..17 {
+            let mut data: Vec<_> = (0..n).rev().collect();
+            bitonic(&mut data);
+            assert!(test(&data));
+        }
+    }
+
+    #[test]
+    fn chunked_test() {
+        let mut data = [13, -4, 8, 8, 0, 21, 5, -9, 3, 1, 17];
+        chunked(&mut data, 3);
+        assert_eq!(data, [-9, -4, 0, 1, 3, 5, 8, 8, 13, 17, 21]);
+
+        for chunk_size in 1..10 {
+            let mut data: Vec<_> = (0..40).map(|x| (x * 37) % 23).collect();
+            chunked(&mut data, chunk_size);
+            assert!(test(&data));
+        }
+
+        // equal keys keep their order
+        let mut data: Vec<_> = (0..30).map(|x| Tagged((x * 7) % 4, x as usize)).collect();
+        chunked(&mut data, 4);
+        assert_stable(&data);
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn par_chunked_test() {
+        use super::{par_chunked, par_chunked_by};
+
+        for &chunk_size in &[1, 7, 100, 5000] {
+            let mut data: Vec<_> = (0..3001).map(|x| (x * 7919) % 3001).collect();
+            par_chunked(&mut data, chunk_size);
+            assert!(data.iter().copied().eq(0..3001));
+
+            let mut data: Vec<_> = (0..3000)
+                .map(|x| Tagged((x * 7) % 13, x as usize))
+                .collect();
+            par_chunked_by(&mut data, chunk_size, Tagged::cmp);
+            assert_stable(&data);
+        }
+    }
+
+    #[test]
+    fn ranges_test() {
+        let mut data = [5, 4, 3, 2, 1, 0, 9, 8];
+        ranges(&mut data, &[6..8, 1..4, 4..4], bubble);
+        assert_eq!(data, [5, 2, 3, 4, 1, 0, 8, 9]);
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn par_ranges_test() {
+        use super::par_ranges;
+
+        let mut data = [5, 4, 3, 2, 1, 0, 9, 8];
+        par_ranges(&mut data, &[6..8, 1..4, 4..4], bubble);
+        assert_eq!(data, [5, 2, 3, 4, 1, 0, 8, 9]);
+
+        let mut data: Vec<_> = (0..1000).rev().collect();
+        let parts: Vec<_> = (0..10).map(|i| (i * 100)..(i * 100 + 90)).collect();
+        par_ranges(&mut data, &parts, quick);
+        for (i, part) in data.chunks(100).enumerate() {
+            let expected: Vec<_> = (0..90).map(|x| 999 - i * 100 - 89 + x).collect();
+            assert_eq!(part[..90], expected[..]);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn ranges_overlap_test() {
+        let mut data = [5, 4, 3, 2, 1];
+        ranges(&mut data, &[0..3, 2..4], bubble);
+    }
+
+    #[test]
+    #[should_panic]
+    fn ranges_out_of_bounds_test() {
+        let mut data = [5, 4, 3];
+        ranges(&mut data, &[1..2, 2..4], bubble);
+    }
+
+    #[test]
+    fn group_test() {
+        let mut data = [5, 5, 5];
+        assert_eq!(group(&mut data), vec![0..3]);
+
+        let mut data: [i32; 0] = [];
+        assert!(group(&mut data).is_empty());
+
+        let mut data = [(2, 'a'), (1, 'b'), (2, 'c'), (0, 'd')];
+        assert_eq!(group_by_key(&mut data, |p| p.0), [0..1, 1..2, 2..4]);
+        assert_eq!(data, [(0, 'd'), (1, 'b'), (2, 'a'), (2, 'c')]);
+    }
+
+    #[test]
+    fn rle_test() {
+        let data = [-3, 0, 0, 0, 8, 8, 11];
+        let runs = rle(&data);
+        assert_eq!(runs, [(-3, 1), (0, 3), (8, 2), (11, 1)]);
+        assert_eq!(rle_expand(&runs), data);
+        assert_eq!(rle_ref(&data)[1], (&0, 3));
+
+        assert!(rle::<u8>(&[]).is_empty());
+        assert!(rle_expand::<u8>(&[]).is_empty());
+    }
+
+    #[test]
+    fn partition_stable_test() {
+        let mut data: Vec<_> = (0..20).collect();
+        let split = partition_stable(&mut data, |x| x % 2 == 1);
+        assert_eq!(split, 10);
+        assert_eq!(data[..split], [1, 3, 5, 7, 9, 11, 13, 15, 17, 19]);
+        assert_eq!(data[split..], [0, 2, 4, 6, 8, 10, 12, 14, 16, 18]);
+
+        let mut data: [i32; 0] = [];
+        assert_eq!(partition_stable(&mut data, |_| true), 0);
+    }
+
+    #[test]
+    fn partition_test() {
+        let mut data: Vec<_> = (0..21).map(|x| (x * 13) % 21).collect();
+        let split = partition(&mut data, |x| x % 3 == 0);
+        assert_eq!(split, 7);
+        assert!(data[..split].iter().all(|x| x % 3 == 0));
+        assert!(data[split..].iter().all(|x| x % 3 != 0));
+
+        let mut data = [(1, 'a'), (2, 'b'), (3, 'c')];
+        assert_eq!(partition_by_key(&mut data, |p| p.0, |&k| k > 3), 0);
+        assert_eq!(partition_by_key(&mut data, |p| p.1, |_| true), 3);
+
+        let mut data: [i32; 0] = [];
+        assert_eq!(partition(&mut data, |_| false), 0);
+    }
+
+    #[test]
+    fn partition3_test() {
+        let mut data = [4, 8, 1, 4, 0, 9, 4, 2];
+        let (lt, gt) = partition3(&mut data, &4);
+        assert_eq!((lt, gt), (3, 6));
+        assert!(data[..lt].iter().all(|&x| x < 4));
+        assert!(data[lt..gt].iter().all(|&x| x == 4));
+        assert!(data[gt..].iter().all(|&x| x > 4));
+
+        let mut data = [3, 1, 2];
+        assert_eq!(partition3(&mut data, &10), (3, 3));
+        assert_eq!(partition3(&mut data, &-1), (0, 0));
+    }
+
+    #[test]
+    fn quick_dyn_test() {
+        let values: Vec<u32> = vec![70000, 3, 255, 3, 0, 1 << 20, 42];
+        let mut data: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        quick_dyn(&mut data, 4, &mut |a, b| {
+            let a = u32::from_le_bytes([a[0], a[1], a[2], a[3]]);
+            let b = u32::from_le_bytes([b[0], b[1], b[2], b[3]]);
+            a.cmp(&b)
+        });
+
+        let sorted: Vec<u32> = data
+            .chunks(4)
+            .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+            .collect();
+        assert_eq!(sorted, [0, 3, 3, 42, 255, 70000, 1 << 20]);
+
+        let mut data = [7u8; 64];
+        quick_dyn(&mut data, 1, &mut |a, b| a.cmp(b));
+        assert_eq!(data, [7u8; 64]);
+
+        quick_dyn(&mut [], 3, &mut |a, b| a.cmp(b));
+    }
+
+    #[test]
+    fn insertion_test() {
+        let mut data = [5, 2, 8, -1, 2, 0, 13];
+        insertion(&mut data);
+        assert_eq!(data, [-1, 0, 2, 2, 5, 8, 13]);
+
+        let mut data: Vec<_> = (0..20).map(|x| Tagged(x % 3, x as usize)).collect();
+        insertion(&mut data);
+        assert_stable(&data);
+    }
+
+    #[test]
+    fn selection_test() {
+        let mut data = [9, 4, 4, -6, 1, 0, 22, 3];
+        selection(&mut data);
+        assert_eq!(data, [-6, 0, 1, 3, 4, 4, 9, 22]);
+
+        let mut data: Vec<_> = (0..50).map(|x| (x * 17) % 31).collect();
+        selection(&mut data);
+        assert!(test(&data));
+    }
+
+    #[test]
+    fn heap_test() {
+        let mut data = [2, 11, -5, 7, 7, 1, 0, 30, -2];
+        heap(&mut data);
+        assert_eq!(data, [-5, -2, 0, 1, 2, 7, 7, 11, 30]);
+
+        for n in 0..20 {
+            let mut data: Vec<_> = (0..n).rev().collect();
+            heap(&mut data);
+            assert!(test(&data));
+        }
+    }
+
+    #[test]
+    fn comb_test() {
+        let mut data = [10, 3, 7, -4, 0, 10, 1, 5, 2];
+        comb(&mut data);
+        assert_eq!(data, [-4, 0, 1, 2, 3, 5, 7, 10, 10]);
+
+        for n in 0..30 {
+            let mut data: Vec<_> = (0..n).map(|x| (x * 11) % 7).collect();
+            comb(&mut data);
+            assert!(test(&data));
+        }
+    }
+
+    #[test]
+    fn pancake_test() {
+        let mut data = [5, 9, 1, 1, -8, 4, 7];
+        pancake(&mut data);
+        assert_eq!(data, [-8, 1, 1, 4, 5, 7, 9]);
+
+        let mut data = [1, 2, 3];
+        flip(&mut data, 0);
+        assert_eq!(data, [1, 2, 3]);
+        flip(&mut data, 3);
+        assert_eq!(data, [3, 2, 1]);
+    }
+
+    #[test]
+    fn odd_even_test() {
+        let mut data = [12, 11, 10, 9, 8, 7, 6, 5, 4, 3, 2, 1, 0];
+        odd_even(&mut data);
+        assert_eq!(data, [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12]);
+
+        for n in 0..10 {
+            let mut data: Vec<_> = (0..n).map(|x| (x * 5) % 3).collect();
+            odd_even(&mut data);
+            assert!(test(&data));
+        }
+    }
+
+    #[test]
+    fn tim_test() {
+        let mut data = [6, 3, 3, 9, 0, -4, 5, 5, 2, 1];
+        tim(&mut data);
+        assert_eq!(data, [-4, 0, 1, 2, 3, 3, 5, 5, 6, 9]);
+
+        // long runs, so that the merges and galloping are used
+        for &n in &[0, 1, 63, 64, 65, 200, 1000] {
+            let mut data: Vec<_> = (0..n)
+                .map(|x| {
+                    Tagged(
+                        if x % 300 < 150 {
+                            x % 150
+                        } else {
+                            300 - x % 300
+                        },
+                        x as usize,
+                    )
+                })
+                .collect();
+            tim(&mut data);
+            assert_stable(&data);
+        }
+
+        let mut data: Vec<_> = (0..500).map(|x| (x * 7919) % 503).collect();
+        tim(&mut data);
+        assert!(test(&data));
+
+        // a long run followed by a shorter one, which is merged backward
+        for &(left, right) in &[(400, 100), (1000, 1), (300, 299)] {
+            let mut data: Vec<_> = (0..left)
+                .map(|x| Tagged(x as i32 / 2, x))
+                .chain((0..right).map(|x| Tagged((x * left / right) as i32 / 2, left + x)))
+                .collect();
+            tim(&mut data);
+            assert_stable(&data);
+        }
+    }
+
+    #[test]
+    fn intro_test() {
+        let mut data = [8, 1, 1, 0, 13, -5, 2, 21, 3, 34, -8, 5];
+        intro(&mut data);
+        assert_eq!(data, [-8, -5, 0, 1, 1, 2, 3, 5, 8, 13, 21, 34]);
+
+        // sorted, reversed and equal elements
+        let mut data: Vec<_> = (0..1000).collect();
+        intro(&mut data);
+        assert!(test(&data));
+        data.reverse();
+        intro(&mut data);
+        assert!(test(&data));
+        let mut data = vec![4; 1000];
+        intro(&mut data);
+        assert!(test(&data));
+    }
+
+    #[test]
+    fn merge_in_place_test() {
+        let mut data = [9, 7, 7, 2, 11, -6, 0, 4, 3];
+        merge_in_place(&mut data);
+        assert_eq!(data, [-6, 0, 2, 3, 4, 7, 7, 9, 11]);
+
+        let mut data: Vec<_> = (0..300)
+            .map(|x| Tagged((x * 37) % 11, x as usize))
+            .collect();
+        merge_in_place(&mut data);
+        assert_stable(&data);
+
+        // with enough distinct elements for a buffer, and with too few
+        for &len in &[33, 100, 257, 1000, 4099] {
+            for &distinct in &[2, 3, 7, 40, 200, 1 << 20] {
+                let mut data: Vec<_> = (0..len)
+                    .map(|x| Tagged(((x as u64 * 7919) % 10007 % distinct) as i32, x))
+                    .collect();
+                merge_in_place(&mut data);
+                assert_stable(&data);
+                assert_eq!(data.len(), len);
+            }
+        }
+    }
+
+    #[test]
+    fn merge_in_place_comparisons_test() {
+        // it takes O(n log n) comparisons, also with few distinct elements
+        for &distinct in &[4, 100, 1 << 20] {
+            let len = 1 << 14;
+            let mut data: Vec<_> = (0..len).map(|x| (x * 7919) % 10007 % distinct).collect();
+            let mut comparisons = 0;
+            merge_in_place_by(&mut data, |a, b| {
+                comparisons += 1;
+                a.cmp(b)
+            });
+            assert!(test(&data));
+            assert!(comparisons < 8 * len * 14, "{} comparisons", comparisons);
+        }
+    }
+
+    #[test]
+    fn counting_test() {
+        let mut data = [3u64, 3, 0, 8, 1, 0, 5];
+        counting(&mut data);
+        assert_eq!(data, [0, 0, 1, 3, 3, 5, 8]);
+
+        let mut data: Vec<_> = (0..100).map(|x| Tagged((x * 7) % 5, x as usize)).collect();
+        counting_by_key(&mut data, |t| t.0 as usize, 4);
+        assert_stable(&data);
+    }
+
+    #[test]
+    fn pigeonhole_test() {
+        let mut data = [usize::MAX, usize::MAX - 3, usize::MAX - 1, usize::MAX - 3];
+        pigeonhole(&mut data);
+        assert_eq!(
+            data,
+            [usize::MAX - 3, usize::MAX - 3, usize::MAX - 1, usize::MAX]
+        );
+
+        let mut data: [u8; 0] = [];
+        pigeonhole(&mut data);
+    }
+
+    #[test]
+    fn radix_lsd_test() {
+        let mut data: Vec<u64> = (0..500)
+            .map(|x: u64| x.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+            .collect();
+        let mut expected = data.clone();
+        quick(&mut expected);
+        radix_lsd(&mut data);
+        assert_eq!(data, expected);
+
+        let mut data = [3u8, 255, 0, 3, 128];
+        radix_lsd(&mut data);
+        assert_eq!(data, [0, 3, 3, 128, 255]);
+
+        let mut data = [7usize; 10];
+        radix_lsd(&mut data);
+        assert_eq!(data, [7; 10]);
+
+        let mut data = [i64::MAX, -7, 0, i64::MIN, 7, -1];
+        radix_lsd(&mut data);
+        assert_eq!(data, [i64::MIN, -7, -1, 0, 7, i64::MAX]);
+
+        let mut data = [-128i8, 127, 0, -1, 1];
+        radix_lsd(&mut data);
+        assert_eq!(data, [-128, -1, 0, 1, 127]);
+
+        let mut data = [1.5f32, -0.0, f32::NEG_INFINITY, 0.0, -1e-30, f32::NAN, 1e30];
+        radix_lsd(&mut data);
+        assert_eq!(data[..6], [f32::NEG_INFINITY, -1e-30, -0.0, 0.0, 1.5, 1e30]);
+        assert!(data[1].is_sign_negative() && data[2].is_sign_negative());
+        assert!(data[6].is_nan());
+    }
+
+    #[test]
+    fn radix_bytes_test() {
+        let words: Vec<String> = (0..300u32)
+            .map(|x| format!("{:x}", x.wrapping_mul(2_654_435_761) % 5000))
+            .collect();
+        let mut data: Vec<&str> = words.iter().map(|s| s.as_str()).collect();
+        let mut expected = data.clone();
+        quick(&mut expected);
+        radix_str(&mut data);
+        assert_eq!(data, expected);
+
+        let mut data: Vec<&[u8]> = vec![b"", b"\xff", b"\x00", b"", b"\x00\x00"];
+        radix_bytes(&mut data);
+        assert_eq!(data, [&b""[..], b"", b"\x00", b"\x00\x00", b"\xff"]);
+    }
+
+    #[test]
+    fn radix_long_prefix_test() {
+        // used to recurse once for every byte of the shared prefix
+        let long = vec![b'a'; 20_000];
+        let mut data: Vec<&[u8]> = vec![&long; 40];
+        radix_bytes(&mut data);
+        assert!(data.iter().all(|s| s.len() == 20_000));
+
+        // every bucket splits off only one string
+        let mut data: Vec<&[u8]> = (0..2000).rev().map(|len| &long[..len]).collect();
+        radix_bytes(&mut data);
+        assert!(data.windows(2).all(|w| w[0] < w[1]));
+
+        let mut strings: Vec<Vec<u8>> = (0..100).map(|x| long[..(x % 7)].to_vec()).collect();
+        for (i, s) in strings.iter_mut().enumerate() {
+            s.extend_from_slice(&long);
+            s.push(i as u8 % 3);
+        }
+        let mut data: Vec<&[u8]> = strings.iter().map(|s| &s[..]).collect();
+        let mut expected = data.clone();
+        expected.sort();
+        radix_bytes(&mut data);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn american_flag_test() {
+        let mut data: Vec<i32> = (0..1000)
+            .map(|x: i32| x.wrapping_mul(-1_640_531_535) >> (x % 20))
+            .collect();
+        let mut expected = data.clone();
+        quick(&mut expected);
+        american_flag(&mut data);
+        assert_eq!(data, expected);
+
+        let mut data = [0.5f64, -2.0, 0.25, -2.0];
+        american_flag(&mut data);
+        assert_eq!(data, [-2.0, -2.0, 0.25, 0.5]);
+    }
+
+    #[test]
+    fn bucket_test() {
+        let mut data: Vec<_> = (0..200)
+            .map(|x| Tagged((x * 31) % 97, x as usize))
+            .collect();
+        bucket(&mut data, 10, |t| t.0 as usize / 10);
+        assert_stable(&data);
+
+        let mut data: Vec<f64> = (0..100).map(|x| ((x * 37) % 100) as f64 / 100.0).collect();
+        data.push(-1.0);
+        data.push(5.0);
+        bucket_uniform(&mut data);
+        for w in data.windows(2) {
+            assert!(w[0] <= w[1]);
+        }
+
+        let mut data: [f64; 0] = [];
+        bucket_uniform(&mut data);
+    }
+
+    #[test]
+    fn flash_test() {
+        let mut data: Vec<u64> = (0..1000).map(|x: u64| (x * 7919) % 1009).collect();
+        let mut expected = data.clone();
+        quick(&mut expected);
+        flash(&mut data);
+        assert_eq!(data, expected);
+
+        let mut data = [i8::MAX, i8::MIN, 0, -1];
+        flash(&mut data);
+        assert_eq!(data, [i8::MIN, -1, 0, i8::MAX]);
+
+        let mut data = [u64::MAX, 0, u64::MAX / 2];
+        flash(&mut data);
+        assert_eq!(data, [0, u64::MAX / 2, u64::MAX]);
+    }
+
+    #[test]
+    fn spread_test() {
+        // clustered values
+        let mut data: Vec<i64> = (0..5000)
+            .map(|x: i64| match x % 3 {
+                0 => (x * 7919) % 1009,
+                1 => (1 << 40) + (x * 31) % 97,
+                _ => -(x * 13) % 100_003,
+            })
+            .collect();
+        let mut expected = data.clone();
+        quick(&mut expected);
+        spread(&mut data);
+        assert_eq!(data, expected);
+
+        let mut data: Vec<f32> = (0..1000).map(|x| ((x * 37) % 101) as f32 - 50.5).collect();
+        spread(&mut data);
+        for w in data.windows(2) {
+            assert!(w[0] <= w[1]);
+        }
+    }
+
+    #[test]
+    fn network_test() {
+        // every network sorts all the sequences of 0s and 1s, so it sorts any
+        // sequence
+        for (n, network) in NETWORKS.iter().enumerate() {
+            for bits in 0u32..(1 << n) {
+                let mut data: Vec<_> = (0..n).map(|i| bits >> i & 1).collect();
+                for &(i, j) in network.iter() {
+                    if data[j] < data[i] {
+                        data.swap(i, j);
+                    }
+                }
+                assert!(test(&data));
+            }
+        }
+
+        let mut data = [3, 1, 2];
+        network(&mut data);
+        assert_eq!(data, [1, 2, 3]);
+
+        let mut data = [0; 20];
+        for (i, v) in data.iter_mut().enumerate() {
+            *v = (i * 7) % 20;
+        }
+        network(&mut data);
+        assert!(test(&data));
+    }
+
+    #[test]
+    fn sample_test() {
+        let mut data: Vec<_> = (0..20_000).map(|x| (x * 7919) % 10_007).collect();
+        sample(&mut data);
+        assert!(test(&data));
+
+        // many duplicates
+        let mut data: Vec<_> = (0..5000).map(|x| (x * 31) % 3).collect();
+        sample(&mut data);
+        assert!(test(&data));
+
+        let mut data = [4, 1, 3];
+        sample(&mut data);
+        assert_eq!(data, [1, 3, 4]);
+    }
+
+    #[test]
+    fn merge_insertion_test() {
+        use std::cell::Cell;
+
+        /// Counts the comparisons.
+        #[derive(PartialEq, Eq)]
+        struct Counted<'a>(i32, &'a Cell<usize>);
+
+        impl PartialOrd for Counted<'_> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for Counted<'_> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.1.set(self.1.get() + 1);
+                self.0.cmp(&other.0)
+            }
+        }
+
+        // the optimal numbers of comparisons for n = 0..=12
+        let optimal = [0, 0, 1, 3, 5, 7, 10, 13, 16, 19, 22, 26, 30];
+        for (n, &max) in optimal.iter().enumerate() {
+            let counter = Cell::new(0);
+            let mut data: Vec<_> = (0..n as i32)
+                .map(|x| Counted((x * 7) % 13, &counter))
+                .collect();
+            merge_insertion(&mut data);
+            assert!(data.windows(2).all(|w| w[0].0 <= w[1].0));
+            assert!(counter.get() <= max);
+        }
+
+        let mut data: Vec<_> = (0..500).map(|x| (x * 7919) % 503).collect();
+        merge_insertion(&mut data);
+        assert!(test(&data));
+    }
+
+    #[test]
+    fn weak_heap_test() {
+        let mut data = [5, 5, 1, -3, 12, 8, 0, 2, 7, 7];
+        weak_heap(&mut data);
+        assert_eq!(data, [-3, 0, 1, 2, 5, 5, 7, 7, 8, 12]);
+
+        for n in 0..40 {
+            let mut data: Vec<_> = (0..n).map(|x| (x * 13) % 17).collect();
+            weak_heap(&mut data);
+            assert!(test(&data));
+        }
+    }
+
+    #[test]
+    fn cartesian_tree_test() {
+        let mut data: Vec<_> = (0..300)
+            .map(|x| Tagged((x * 37) % 23, x as usize))
+            .collect();
+        cartesian_tree(&mut data);
+        assert_stable(&data);
+
+        let mut data = [3, 3, 2, 1];
+        cartesian_tree(&mut data);
+        assert_eq!(data, [1, 2, 3, 3]);
+    }
+
+    #[test]
+    #[cfg(feature = "educational")]
+    fn educational_test() {
+        use super::{bogo, slow, stooge};
+
+        // keep the inputs tiny, these algorithms are hopelessly slow
+        for sort in [bogo::<i32>, stooge::<i32>, slow::<i32>].iter() {
+            let mut data = [4, -1, 4, 0, 7, 2];
+            sort(&mut data);
+            assert_eq!(data, [-1, 0, 2, 4, 4, 7]);
+
+            let mut empty: [i32; 0] = [];
+            sort(&mut empty);
+        }
+    }
+
+    #[test]
+    fn bead_test() {
+        let mut data: Vec<u16> = (0..200).map(|x| (x * 31) % 17).collect();
+        let mut expected = data.clone();
+        expected.sort();
+        bead(&mut data);
+        assert_eq!(data, expected);
+
+        let mut data = [0u8, 0, 0];
+        bead(&mut data);
+        assert_eq!(data, [0, 0, 0]);
+    }
+
+    #[test]
+    fn by_test() {
+        type Pair = (i32, usize);
+        type Sort = fn(&mut [Pair]);
+        fn desc(a: &Pair, b: &Pair) -> Ordering {
+            b.0.cmp(&a.0)
+        }
+
+        // (sort, whether it's stable)
+        let sorts: [(Sort, bool); 18] = [
+            (|s| bubble_by(s, desc), true),
+            (|s| comb_by(s, desc), false),
+            (|s| odd_even_by(s, desc), true),
+            (|s| insertion_by(s, desc), true),
+            (|s| selection_by(s, desc), false),
+            (|s| pancake_by(s, desc), false),
+            (|s| quick_by(s, desc), false),
+            (|s| intro_by(s, desc), false),
+            (|s| sample_by(s, desc), false),
+            (|s| merge_insertion_by(s, desc), false),
+            (|s| merge_by(s, desc), true),
+            (|s| heap_by(s, desc), false),
+            (|s| weak_heap_by(s, desc), false),
+            (|s| cartesian_tree_by(s, desc), true),
+            (|s| tim_by(s, desc), true),
+            (|s| bitonic_by(s, desc), false),
+            (|s| chunked_by(s, 16, desc), true),
+            (|s| merge_in_place_by(s, desc), true),
+        ];
+
+        let data: Vec<_> = (0..1500).map(|x| ((x * 37) % 23, x as usize)).collect();
+        let mut expected = data.clone();
+        expected.sort_by(desc);
+        for (sort, stable) in sorts.iter() {
+            let mut sorted = data.clone();
+            sort(&mut sorted);
+            if *stable {
+                assert_eq!(sorted, expected);
+            } else {
+                let keys = |v: &[Pair]| v.iter().map(|p| p.0).collect::<Vec<_>>();
+                assert_eq!(keys(&sorted), keys(&expected));
+            }
+        }
+
+        let mut array = [3, 1, 4, 1, 5, 9, 2, 6];
+        network_by(&mut array, |a, b| b.cmp(a));
+        assert_eq!(array, [9, 6, 5, 4, 3, 2, 1, 1]);
+    }
+
+    #[test]
+    fn by_key_test() {
+        type Pair = (i32, usize);
+        type Sort = fn(&mut [Pair]);
+
+        // (sort, whether it's stable)
+        let sorts: [(Sort, bool); 18] = [
+            (|s| bubble_by_key(s, |p| p.0), true),
+            (|s| comb_by_key(s, |p| p.0), false),
+            (|s| odd_even_by_key(s, |p| p.0), true),
+            (|s| insertion_by_key(s, |p| p.0), true),
+            (|s| selection_by_key(s, |p| p.0), false),
+            (|s| pancake_by_key(s, |p| p.0), false),
+            (|s| quick_by_key(s, |p| p.0), false),
+            (|s| intro_by_key(s, |p| p.0), false),
+            (|s| sample_by_key(s, |p| p.0), false),
+            (|s| merge_insertion_by_key(s, |p| p.0), false),
+            (|s| merge_by_key(s, |p| p.0), true),
+            (|s| heap_by_key(s, |p| p.0), false),
+            (|s| weak_heap_by_key(s, |p| p.0), false),
+            (|s| cartesian_tree_by_key(s, |p| p.0), true),
+            (|s| tim_by_key(s, |p| p.0), true),
+            (|s| bitonic_by_key(s, |p| p.0), false),
+            (|s| chunked_by_key(s, 16, |p| p.0), true),
+            (|s| merge_in_place_by_key(s, |p| p.0), true),
+        ];
+
+        let data: Vec<_> = (0..1500).map(|x| ((x * 37) % 23, x as usize)).collect();
+        let mut expected = data.clone();
+        expected.sort_by_key(|p| p.0);
+        for (sort, stable) in sorts.iter() {
+            let mut sorted = data.clone();
+            sort(&mut sorted);
+            if *stable {
+                assert_eq!(sorted, expected);
+            } else {
+                assert!(sorted.windows(2).all(|w| w[0].0 <= w[1].0));
+            }
+        }
+
+        let mut array = [-3, 1, -4, 2];
+        network_by_key(&mut array, |x: &i32| x.abs());
+        assert_eq!(array, [1, 2, -3, -4]);
+    }
+
+    #[test]
+    fn by_cached_key_test() {
+        let mut calls = 0;
+        let mut data: Vec<_> = (0..500)
+            .map(|x| Tagged((x * 37) % 23, x as usize))
+            .collect();
+        by_cached_key(&mut data, |t| {
+            calls += 1;
+            t.0
+        });
+        assert_stable(&data);
+        assert_eq!(calls, 500);
+    }
+
+    #[test]
+    fn desc_test() {
+        type Sort = fn(&mut [Tagged]);
+
+        // (sort, whether it's stable)
+        let sorts: [(Sort, bool); 18] = [
+            (bubble_desc, true),
+            (comb_desc, false),
+            (odd_even_desc, true),
+            (insertion_desc, true),
+            (selection_desc, false),
+            (pancake_desc, false),
+            (quick_desc, false),
+            (intro_desc, false),
+            (sample_desc, false),
+            (merge_insertion_desc, false),
+            (merge_desc, true),
+            (heap_desc, false),
+            (weak_heap_desc, false),
+            (cartesian_tree_desc, true),
+            (tim_desc, true),
+            (bitonic_desc, false),
+            (|s| chunked_desc(s, 16), true),
+            (merge_in_place_desc, true),
+        ];
+
+        let data: Vec<_> = (0..1500)
+            .map(|x| Tagged((x * 37) % 23, x as usize))
+            .collect();
+        for (sort, stable) in sorts.iter() {
+            let mut sorted = data.clone();
+            sort(&mut sorted);
+            for w in sorted.windows(2) {
+                assert!(w[0].0 > w[1].0 || (w[0].0 == w[1].0 && (!stable || w[0].1 < w[1].1)));
+            }
+        }
+
+        let mut array = [2, 7, 1, 8, 2, 8];
+        network_desc(&mut array);
+        assert_eq!(array, [8, 8, 7, 2, 2, 1]);
+    }
+
+    #[test]
+    fn total_test() {
+        let data: Vec<f64> = (0..500)
+            .map(|x| match x % 50 {
+                0 => f64::NAN,
+                1 => -f64::NAN,
+                2 => -0.0,
+                _ => ((x * 37) % 101) as f64 / 7.0 - 5.0,
+            })
+            .collect();
+        let mut expected = data.clone();
+        expected.sort_by(f64::total_cmp);
+        let bits = |v: &[f64]| v.iter().map(|x| x.to_bits()).collect::<Vec<_>>();
+
+        for sort in [quick_total, intro_total, merge_total, tim_total].iter() {
+            let mut sorted = data.clone();
+            sort(&mut sorted);
+            assert_eq!(bits(&sorted), bits(&expected));
+        }
+    }
+
+    #[test]
+    fn floats_test() {
+        let data = [3.0f32, f32::NAN, -0.0, -f32::NAN, 0.0, -2.5];
+
+        let mut slice = data;
+        floats(&mut slice, NanPolicy::First, merge_total).unwrap();
+        assert!(slice[..2].iter().all(|x| x.is_nan()));
+        assert_eq!(slice[2..], [-2.5, -0.0, 0.0, 3.0]);
+        assert!(slice[2].is_sign_negative() && slice[4].is_sign_positive());
+
+        let mut slice = data;
+        floats(&mut slice, NanPolicy::Last, tim_total).unwrap();
+        assert_eq!(slice[..4], [-2.5, -0.0, 0.0, 3.0]);
+        assert!(slice[4..].iter().all(|x| x.is_nan()));
+
+        let mut slice = data;
+        assert_eq!(
+            floats(&mut slice, NanPolicy::Error, quick_total),
+            Err(NanError { index: 1 })
+        );
+        assert_eq!(slice[0], 3.0);
+
+        let mut slice = [1.5, -1.5];
+        floats(&mut slice, NanPolicy::Error, quick_total).unwrap();
+        assert_eq!(slice, [-1.5, 1.5]);
+    }
+
+    #[test]
+    fn try_by_test() {
+        type Sort = fn(&mut [i32], &mut usize) -> Result<(), usize>;
+        let sorts: [Sort; 2] = [
+            |s, calls| {
+                try_quick_by(s, |a, b| {
+                    *calls += 1;
+                    if *a == 13 || *b == 13 {
+                        Err(*calls)
+                    } else {
+                        Ok(a.cmp(b))
+                    }
+                })
+            },
+            |s, calls| {
+                try_merge_by(s, |a, b| {
+                    *calls += 1;
+                    if *a == 13 || *b == 13 {
+                        Err(*calls)
+                    } else {
+                        Ok(a.cmp(b))
+                    }
+                })
+            },
+        ];
+
+        for sort in sorts.iter() {
+            let mut data: Vec<_> = (0..200).map(|x| (x * 37) % 200).collect();
+            let mut calls = 0;
+            let failed_at = sort(&mut data, &mut calls).unwrap_err();
+            // the comparator isn't called after the error
+            assert_eq!(calls, failed_at);
+            data.sort();
+            assert!(data.iter().copied().eq(0..200));
+
+            let mut data: Vec<_> = (0..200).map(|x| (x * 37) % 200 + 20).collect();
+            sort(&mut data, &mut 0).unwrap();
+            assert!(data.iter().copied().eq(20..220));
+        }
+    }
+
+    #[test]
+    fn range_test() {
+        let data: Vec<_> = (0..100).map(|x| (x * 37) % 100).collect();
+
+        let mut sorted = data.clone();
+        quick_range(&mut sorted, 10..50);
+        assert_eq!(sorted[..10], data[..10]);
+        assert!(test(&sorted[10..50]));
+        assert_eq!(sorted[50..], data[50..]);
+
+        let mut sorted = data.clone();
+        merge_range(&mut sorted, ..=20);
+        assert!(test(&sorted[..21]));
+        assert_eq!(sorted[21..], data[21..]);
+
+        let mut sorted = data.clone();
+        tim_range(&mut sorted, 90..);
+        chunked_range(&mut sorted, 0..0, 4);
+        heap_range(&mut sorted, ..);
+        assert!(sorted.iter().copied().eq(0..100));
+    }
+
+    #[test]
+    #[should_panic]
+    fn range_out_of_bounds_test() {
+        quick_range(&mut [3, 2, 1], 1..4);
+    }
+
+    #[test]
+    fn cutoff_test() {
+        let data: Vec<_> = (0..300).map(|x| (x * 37) % 300).collect();
+        for &cutoff in [0, 1, 2, 24, 299, 300, 1000].iter() {
+            let mut sorted = data.clone();
+            quick_with_cutoff(&mut sorted, cutoff);
+            assert!(sorted.iter().copied().eq(0..300));
+
+            let mut sorted = data.clone();
+            merge_with_cutoff(&mut sorted, cutoff);
+            assert!(sorted.iter().copied().eq(0..300));
+        }
+    }
+
+    #[test]
+    fn config_test() {
+        let data: Vec<_> = (0..300).map(|x| (x * 37) % 300).collect();
+        let configs = [
+            SortConfig::default(),
+            SortConfig::new().insertion_cutoff(0),
+            SortConfig::new().max_depth(0),
+            SortConfig::new().insertion_cutoff(4).max_depth(3),
+        ];
+        for config in configs.iter() {
+            let mut sorted = data.clone();
+            quick_with(&mut sorted, config);
+            assert!(sorted.iter().copied().eq(0..300));
+
+            let mut sorted = data.clone();
+            merge_with(&mut sorted, config);
+            assert!(sorted.iter().copied().eq(0..300));
+        }
+
+        // reversed input makes the last-element pivot quadratic without the limit
+        let mut sorted: Vec<_> = (0..5000).rev().collect();
+        quick_with(&mut sorted, &SortConfig::new().max_depth(30));
+        assert!(sorted.iter().copied().eq(0..5000));
+    }
+
+    #[test]
+    fn pivot_test() {
+        let pivots = [
+            Pivot::Last,
+            Pivot::First,
+            Pivot::Middle,
+            Pivot::MedianOfThree,
+            Pivot::Ninther,
+            Pivot::Random,
+        ];
+        for &pivot in pivots.iter() {
+            for len in 1..40 {
+                let mut data: Vec<_> = (0..len).map(|x| (x * 7) % 5).collect();
+                let pos = quick_partition_with(&mut data, pivot).unwrap();
+                assert!(data[..pos].iter().all(|x| x <= &data[pos]));
+                assert!(data[pos..].iter().all(|x| x >= &data[pos]));
+            }
+
+            let mut data: Vec<_> = (0..3000).map(|x| (x * 37) % 1000).collect();
+            quick_with_pivot(&mut data, pivot);
+            assert!(test(&data));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn quick_random_test() {
+        use super::{quick_random, quick_random_with};
+        use rand::rngs::StdRng;
+        use rand::SeedableRng;
+
+        let mut data: Vec<_> = (0..5000).rev().collect();
+        quick_random(&mut data);
+        assert!(data.iter().copied().eq(0..5000));
+
+        let mut data: Vec<_> = (0..3000).map(|x| (x * 37) % 100).collect();
+        quick_random_with(&mut data, &mut StdRng::seed_from_u64(7));
+        assert!(test(&data));
+    }
 
     #[test]
-    fn test_test() {
-        // lol
-        assert!(test(&[0, 3, 4, 10, 12]));
-        assert!(!test(&[6, 2, 1, 10, -2]));
+    fn quick_worst_case_test() {
+        // the last-element pivot splits reversed input into parts of n - 2 and 0
+        // elements, which would overflow the stack without looping on the larger
+        // part
+        let mut data: Vec<_> = (0..20_000).rev().collect();
+        quick(&mut data);
+        assert!(data.iter().copied().eq(0..20_000));
     }
 
     #[test]
-    fn bubble_test() {
-        let mut data = [4, 2, 1, 8, 7, 9, -11];
-        bubble(&mut data);
-        assert_eq!(data, [-11, 1, 2, 4, 7, 8, 9]);
+    fn quick_partition_test() {
+        assert_eq!(quick_partition::<i32>(&mut []), None);
+        assert_eq!(quick_partition_with::<i32>(&mut [], Pivot::Ninther), None);
+        assert_eq!(quick_partition(&mut [5]), Some(0));
+
+        let mut data = [3, 3, 3, 3];
+        let pos = quick_partition(&mut data).unwrap();
+        assert!(pos < 4);
     }
 
     #[test]
-    fn quick_test() {
-        let mut data = [6, 7, 3, 5, 4, -12];
-        quick(&mut data);
-        assert_eq!(data, [-12, 3, 4, 5, 6, 7]);
+    fn merge_with_buf_test() {
+        let mut buf = vec![42; 100];
+        for len in 0..60 {
+            let mut data: Vec<i32> = (0..len).map(|x| (x * 17) % 23 - 11).collect();
+            let mut expected = data.clone();
+            expected.sort();
+
+            merge_with_buf(&mut data, &mut buf);
+            assert_eq!(data, expected);
+        }
     }
 
     #[test]
-    fn merge_test() {
-        let mut data1 = [6, 1, 2, 99, -1, 13, 7, 1];
-        let mut data2 = [5, 7, 11, -1, 2, 3];
-        let mut data3 = [11, 16, 20, 12, 13, 15];
+    fn merge_panic_test() {
+        use std::panic::{self, AssertUnwindSafe};
 
-        merge(&mut data1);
-        merge(&mut data2);
-        merge(&mut data3);
+        let original: Vec<i32> = (0..100).map(|x| (x * 37) % 101).collect();
+        for limit in (0..700).step_by(7) {
+            let mut data = original.clone();
+            let mut count = 0;
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                merge_by(&mut data, |a, b| {
+                    count += 1;
+                    if count == limit {
+                        panic!("comparison limit reached");
+                    }
+                    a.cmp(b)
+                })
+            }));
 
-        assert_eq!(data1, [-1, 1, 1, 2, 6, 7, 13, 99]);
-        assert_eq!(data2, [-1, 2, 3, 5, 7, 11]);
-        assert_eq!(data3, [11, 12, 13, 15, 16, 20]);
+            if result.is_err() {
+                data.sort();
+                let mut expected = original.clone();
+                expected.sort();
+                assert_eq!(data, expected);
+            } else {
+                assert!(data.windows(2).all(|w| w[0] <= w[1]));
+            }
+        }
+    }
+
+    #[test]
+    fn merge_galloping_test() {
+        // both halves are sorted, and interleave in four long blocks
+        let mut data: Vec<_> = (0..500).chain(1000..1500).collect();
+        data.extend((500..1000).chain(1500..2000));
+
+        let mut count = 0;
+        merge_by(&mut data, |a, b| {
+            count += 1;
+            a.cmp(b)
+        });
+        assert!(data.iter().copied().eq(0..2000));
+        // checking whether the slice and its halves are sorted takes 2998
+        // comparisons, and the merge only a few more
+        assert!(count < 2998 + 100, "{} comparisons", count);
+    }
+
+    #[test]
+    fn runs_test() {
+        assert_eq!(runs::<i32>(&[]).count(), 0);
+        assert_eq!(runs(&[7]).next(), Some(0..1));
+        assert_eq!(runs(&[1, 2, 3, 4]).next(), Some(0..4));
+        // equal elements end a descending run
+        assert_eq!(runs(&[3, 2, 2, 1]).collect::<Vec<_>>(), [0..2, 2..4]);
+
+        let data: Vec<_> = (0..200).map(|x| (x * 37) % 23).collect();
+        let mut end = 0;
+        for run in runs(&data) {
+            assert_eq!(run.start, end);
+            let part = &data[run.clone()];
+            let ascending = part.windows(2).all(|w| w[0] <= w[1]);
+            let descending = part.windows(2).all(|w| w[0] > w[1]);
+            assert!(ascending || descending);
+            end = run.end;
+        }
+        assert_eq!(end, data.len());
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn par_quick_test() {
+        use super::{par_quick, par_quick_by};
+
+        let n = 50_000;
+        let inputs: [Vec<i32>; 4] = [
+            (0..n).map(|x| (x * 7919) % 10_007).collect(),
+            (0..n).collect(),
+            (0..n).rev().collect(),
+            vec![3; n as usize],
+        ];
+        for data in inputs.iter() {
+            let mut expected = data.clone();
+            expected.sort();
+
+            let mut sorted = data.clone();
+            par_quick(&mut sorted);
+            assert_eq!(sorted, expected);
+
+            let mut sorted = data.clone();
+            par_quick_by(&mut sorted, |a, b| b.cmp(a));
+            expected.reverse();
+            assert_eq!(sorted, expected);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn par_merge_test() {
+        use super::{par_merge, par_merge_by};
+
+        let n = 50_000;
+        let mut data: Vec<_> = (0..n)
+            .map(|x| Tagged((x * 7919) % 101, x as usize))
+            .collect();
+        par_merge(&mut data);
+        assert_stable(&data);
+
+        // runs of very different lengths after the split
+        let mut data: Vec<_> = (0..n).map(|x| Tagged(x % 3, x as usize)).collect();
+        par_merge(&mut data);
+        assert_stable(&data);
+
+        let mut data: Vec<i32> = (0..n).collect();
+        par_merge_by(&mut data, |a, b| b.cmp(a));
+        assert!(data.iter().copied().eq((0..n).rev()));
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn par_radix_lsd_test() {
+        use super::par_radix_lsd;
+
+        let mut data: Vec<u64> = (0..50_000u64)
+            .map(|x| x.wrapping_mul(0x9E37_79B9_7F4A_7C15))
+            .collect();
+        let mut expected = data.clone();
+        expected.sort();
+        par_radix_lsd(&mut data);
+        assert_eq!(data, expected);
+
+        let mut data: Vec<i32> = (0..50_000).map(|x| (x * 7919) % 10_007 - 5000).collect();
+        let mut expected = data.clone();
+        expected.sort();
+        par_radix_lsd(&mut data);
+        assert_eq!(data, expected);
+
+        let mut empty: [u8; 0] = [];
+        par_radix_lsd(&mut empty);
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn par_config_test() {
+        use super::{par_merge_in, par_quick_in, par_radix_lsd_in};
+        use rayon::ThreadPoolBuilder;
+
+        /// A number that can be compared only on the threads of the pool.
+        #[derive(Debug, Clone, PartialEq, Eq)]
+        struct OnPool(u32);
+
+        impl PartialOrd for OnPool {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for OnPool {
+            fn cmp(&self, other: &Self) -> Ordering {
+                let thread = std::thread::current();
+                assert!(thread.name().unwrap_or("").starts_with("sort-pool"));
+                self.0.cmp(&other.0)
+            }
+        }
+
+        let pool = ThreadPoolBuilder::new()
+            .num_threads(3)
+            .thread_name(|i| format!("sort-pool-{}", i))
+            .build()
+            .unwrap();
+        let config = SortConfig::new().par_threshold(0);
+        let data: Vec<_> = (0..5000u32).map(|x| (x * 7919) % 1009).collect();
+        let mut expected = data.clone();
+        expected.sort();
+        let expected: Vec<_> = expected.into_iter().map(OnPool).collect();
+
+        let mut sorted: Vec<_> = data.iter().copied().map(OnPool).collect();
+        par_quick_in(&mut sorted, &pool, &config);
+        assert!(sorted == expected);
+
+        let mut sorted: Vec<_> = data.iter().copied().map(OnPool).collect();
+        par_merge_in(&mut sorted, &pool, &config);
+        assert!(sorted == expected);
+
+        let mut sorted = data.clone();
+        par_radix_lsd_in(&mut sorted, &pool, &config);
+        assert!(sorted.into_iter().map(OnPool).eq(expected));
+    }
+
+    #[test]
+    fn sorted_iter_test() {
+        assert_eq!(sorted_iter(Vec::<i32>::new()).next(), None);
+
+        let data: Vec<_> = (0..300).map(|x| (x * 37) % 23).collect();
+        let mut expected = data.clone();
+        expected.sort();
+
+        let mut iter = sorted_iter(data);
+        assert_eq!(iter.len(), 300);
+        assert_eq!(iter.next(), Some(0));
+        assert_eq!(iter.len(), 299);
+        assert!(iter.eq(expected.into_iter().skip(1)));
+    }
+
+    #[test]
+    fn iterator_sort_ext_test() {
+        let data: Vec<_> = (0..300).map(|x| ((x * 37) % 23, x)).collect();
+        let mut expected = data.clone();
+        expected.sort_by_key(|p| p.0);
+
+        assert!(data
+            .iter()
+            .copied()
+            .sorted_by_key(|p| p.0)
+            .eq(expected.iter().copied()));
+        assert!(data
+            .iter()
+            .map(|p| p.0)
+            .sorted()
+            .eq(expected.iter().map(|p| p.0)));
+
+        let smallest = data.iter().map(|p| p.0).k_smallest(30);
+        assert!(smallest.eq(expected.iter().map(|p| p.0).take(30)));
+        assert_eq!(data.iter().k_smallest(0).len(), 0);
+        assert_eq!(data.iter().k_smallest(1000).len(), 300);
+    }
+
+    #[test]
+    fn kmerge_test() {
+        let empty: Vec<Vec<i32>> = Vec::new();
+        assert_eq!(kmerge(empty).next(), None);
+
+        // equal keys keep the order of the sources
+        let sources: Vec<Vec<_>> = (0..7)
+            .map(|s| {
+                (0..(s * 10))
+                    .map(|x| Tagged(x / 3, (s * 1000 + x) as usize))
+                    .collect()
+            })
+            .collect();
+        let total: usize = sources.iter().map(Vec::len).sum();
+        let merged = kmerge(sources.iter().cloned());
+        assert_eq!(merged.size_hint(), (total, Some(total)));
+        let merged: Vec<_> = merged.collect();
+        assert_eq!(merged.len(), total);
+        assert_stable(&merged);
+    }
+
+    #[test]
+    fn merge_into_test() {
+        assert!(merged::<i32>(&[], &[]).is_empty());
+        assert_eq!(merged(&[], &[1, 2]), [1, 2]);
+        assert_eq!(merged(&[1, 2], &[]), [1, 2]);
+
+        let a: Vec<_> = (0..50).map(|x| Tagged(x / 4, x as usize)).collect();
+        let b: Vec<_> = (0..50).map(|x| Tagged(x / 3, 100 + x as usize)).collect();
+        let mut out = Vec::new();
+        merge_into(&a, &b, &mut out);
+        assert_eq!(out.len(), 100);
+        assert_stable(&out);
+    }
+
+    #[test]
+    fn merge_join_test() {
+        let left = [(1, 'a'), (2, 'b'), (2, 'c'), (4, 'd')];
+        let right = [(0, 'w'), (2, 'x'), (2, 'y'), (4, 'z'), (5, 'v')];
+        let joined: Vec<_> = merge_join(left, right, |p| p.0, |p| p.0).collect();
+        assert_eq!(
+            joined,
+            [
+                Joined::Right((0, 'w')),
+                Joined::Left((1, 'a')),
+                Joined::Both((2, 'b'), (2, 'x')),
+                Joined::Both((2, 'b'), (2, 'y')),
+                Joined::Both((2, 'c'), (2, 'x')),
+                Joined::Both((2, 'c'), (2, 'y')),
+                Joined::Both((4, 'd'), (4, 'z')),
+                Joined::Right((5, 'v')),
+            ]
+        );
+
+        let left_join: Vec<_> = joined.into_iter().filter_map(Joined::left).collect();
+        assert_eq!(left_join.len(), 6);
+        assert_eq!(left_join[0], ((1, 'a'), None));
+
+        let empty: [(i32, char); 0] = [];
+        assert_eq!(merge_join(empty, empty, |p| p.0, |p| p.0).next(), None);
+
+        // the sides have different types and keys
+        let names = [(1, "one"), (3, "three")];
+        let joined: Vec<_> = merge_join(names, 1..=3, |n| n.0, |&x| x)
+            .filter_map(Joined::both)
+            .collect();
+        assert_eq!(joined, [((1, "one"), 1), ((3, "three"), 3)]);
+    }
+
+    #[test]
+    fn merge_join_clone_test() {
+        use std::cell::Cell;
+
+        thread_local!(static CLONES: Cell<usize> = const { Cell::new(0) });
+
+        #[derive(Debug, PartialEq)]
+        struct Counted(i32);
+
+        impl Clone for Counted {
+            fn clone(&self) -> Self {
+                CLONES.with(|c| c.set(c.get() + 1));
+                Counted(self.0)
+            }
+        }
+
+        // one-to-one pairs aren't cloned, the single item of a group is
+        // cloned once for every extra pair
+        let left = [Counted(1), Counted(2), Counted(3), Counted(3)];
+        let right = [Counted(1), Counted(2), Counted(2), Counted(3)];
+        let joined: Vec<_> = merge_join(left, right, |c| c.0, |c| c.0).collect();
+        assert_eq!(joined.len(), 5);
+        assert_eq!(CLONES.with(Cell::get), 2);
+    }
+
+    #[test]
+    fn deque_test() {
+        use std::collections::VecDeque;
+
+        for n in 0..60 {
+            for &rotation in &[0, 1, n / 3, n / 2] {
+                let data: Vec<_> = (0..n).map(|x| (x * 37) % 23).collect();
+                let mut deque: VecDeque<_> = data.iter().copied().collect();
+                deque.rotate_left(rotation % (n + 1));
+                let mut expected = data.clone();
+                expected.sort();
+
+                let mut sorted = deque.clone();
+                bubble_deque(&mut sorted);
+                assert!(sorted.iter().eq(expected.iter()));
+
+                let mut sorted = deque.clone();
+                quick_deque(&mut sorted);
+                assert!(sorted.iter().eq(expected.iter()));
+
+                let mut sorted = deque.clone();
+                merge_deque(&mut sorted);
+                assert!(sorted.iter().eq(expected.iter()));
+            }
+        }
+
+        // a deque that wraps around, with equal keys
+        let mut deque: VecDeque<_> = (0..500).map(|x| Tagged(x % 7, 1000 + x as usize)).collect();
+        for x in 0..300 {
+            deque.pop_back();
+            deque.push_front(Tagged(x % 5, 999 - x as usize));
+        }
+        assert!(!deque.as_slices().1.is_empty());
+        let mut sorted = deque.clone();
+        quick_deque(&mut sorted);
+        assert!(sorted
+            .iter()
+            .zip(sorted.iter().skip(1))
+            .all(|(a, b)| a <= b));
+        merge_deque(&mut deque);
+        assert_stable(&Vec::from(deque));
+    }
+
+    #[test]
+    fn storage_test() {
+        /// Two columns, sorted by the first one.
+        struct Columns(Vec<i32>, Vec<usize>);
+
+        impl SortableStorage for Columns {
+            fn len(&self) -> usize {
+                self.0.len()
+            }
+
+            fn compare(&self, i: usize, j: usize) -> Ordering {
+                self.0[i].cmp(&self.0[j])
+            }
+
+            fn swap(&mut self, i: usize, j: usize) {
+                self.0.swap(i, j);
+                self.1.swap(i, j);
+            }
+        }
+
+        type Sort = fn(&mut Columns);
+        let sorts: [Sort; 3] = [bubble_storage, quick_storage, heap_storage];
+        for sort in sorts.iter() {
+            for n in 0..100 {
+                let keys: Vec<_> = (0..n).map(|x| (x * 37) % 23).collect();
+                let mut columns = Columns(keys.clone(), (0..keys.len()).collect());
+                sort(&mut columns);
+
+                assert!(test(&columns.0));
+                // the columns stay aligned
+                for (key, &index) in columns.0.iter().zip(&columns.1) {
+                    assert_eq!(*key, keys[index]);
+                }
+            }
+        }
+
+        let mut data = vec![3; 1000];
+        quick_storage(&mut data[..]);
+    }
+
+    #[test]
+    fn co_sort_test() {
+        let keys: Vec<_> = (0..300).map(|x| (x * 37) % 101).collect();
+        let mut sorted = keys.clone();
+        let mut indices: Vec<_> = (0..300).collect();
+        co_sort(&mut sorted, &mut indices);
+        assert!(test(&sorted));
+        for (key, &index) in sorted.iter().zip(&indices) {
+            assert_eq!(*key, keys[index]);
+        }
+
+        let mut sorted = keys.clone();
+        let mut indices: Vec<_> = (0..300).collect();
+        let mut doubled: Vec<_> = keys.iter().map(|k| 2 * k).collect();
+        crate::co_sort!(sorted, indices, doubled);
+        assert!(test(&sorted));
+        for ((key, &index), &double) in sorted.iter().zip(&indices).zip(&doubled) {
+            assert_eq!(*key, keys[index]);
+            assert_eq!(2 * key, double);
+        }
+
+        let mut only_keys = [3, 1, 2];
+        crate::co_sort!(only_keys);
+        assert_eq!(only_keys, [1, 2, 3]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn co_sort_length_test() {
+        co_sort(&mut [1, 2], &mut [1]);
+    }
+
+    #[test]
+    fn argsort_test() {
+        assert!(argsort::<i32>(&[]).is_empty());
+
+        let data: Vec<_> = (0..300).map(|x| (x * 37) % 23).collect();
+        let perm = argsort(&data);
+        let mut expected: Vec<_> = (0..300).collect();
+        expected.sort_by_key(|&i| data[i]);
+        assert_eq!(perm, expected);
+
+        // descending keys, and the original order among equal ones
+        let perm = argsort_by_key(&data, |x| -x);
+        assert_eq!(perm.len(), data.len());
+        assert!(perm
+            .windows(2)
+            .all(|w| data[w[0]] > data[w[1]] || (data[w[0]] == data[w[1]] && w[0] < w[1])));
+    }
+
+    #[test]
+    fn permutation_test() {
+        let data: Vec<_> = (0..300).map(|x| (x * 37) % 23).collect();
+        let perm = argsort(&data);
+        let mut sorted = data.clone();
+        apply_permutation(&mut sorted, &perm);
+        assert!(test(&sorted));
+
+        let inverse = invert_permutation(&perm);
+        apply_permutation(&mut sorted, &inverse);
+        assert_eq!(sorted, data);
+        assert_eq!(invert_permutation(&inverse), perm);
+
+        apply_permutation::<i32>(&mut [], &[]);
+
+        let mut letters = ['a', 'b', 'c', 'd', 'e'];
+        apply_permutation(&mut letters, &[2, 0, 1, 4, 3]);
+        assert_eq!(letters, ['c', 'a', 'b', 'e', 'd']);
+    }
+
+    #[test]
+    #[should_panic]
+    fn permutation_duplicate_test() {
+        invert_permutation(&[0, 2, 2]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn apply_permutation_duplicate_test() {
+        apply_permutation(&mut [1, 2, 3], &[1, 0, 0]);
+    }
+
+    #[test]
+    fn select_nth_deterministic_test() {
+        for len in 1..80 {
+            let data: Vec<_> = (0..len).map(|x| (x * 37) % 23).collect();
+            let mut sorted = data.clone();
+            sorted.sort();
+            for n in 0..(len as usize) {
+                let mut selected = data.clone();
+                assert_eq!(*select_nth_deterministic(&mut selected, n), sorted[n]);
+                assert!(selected[..n].iter().all(|x| *x <= sorted[n]));
+                assert!(selected[n..].iter().all(|x| *x >= sorted[n]));
+            }
+        }
+
+        // linear number of comparisons on sorted and constant input
+        for data in [(0..10_000).collect::<Vec<_>>(), vec![7; 10_000]].iter() {
+            let mut data = data.clone();
+            let mut count = 0;
+            select_nth_deterministic_by(&mut data, 5000, |a, b| {
+                count += 1;
+                a.cmp(b)
+            });
+            assert!(count < 40 * 10_000, "{} comparisons", count);
+        }
+    }
+
+    #[test]
+    fn multi_select_test() {
+        let data: Vec<_> = (0..500).map(|x| (x * 37) % 101).collect();
+        let mut sorted = data.clone();
+        sorted.sort();
+
+        let mut positions = [250, 0, 499, 250, 10, 450, 451];
+        let mut selected = data.clone();
+        multi_select(&mut selected, &mut positions);
+        assert_eq!(positions, [0, 10, 250, 250, 450, 451, 499]);
+        for &p in positions.iter() {
+            assert_eq!(selected[p], sorted[p]);
+            assert!(selected[..p].iter().all(|&x| x <= sorted[p]));
+            assert!(selected[p..].iter().all(|&x| x >= sorted[p]));
+        }
+
+        let mut selected = data.clone();
+        multi_select(&mut selected, &mut []);
+        assert_eq!(selected, data);
+    }
+
+    #[test]
+    fn partial_test() {
+        let data: Vec<_> = (0..300).map(|x| (x * 37) % 101).collect();
+        let mut sorted = data.clone();
+        sorted.sort();
+
+        for &k in [0, 1, 2, 20, 299, 300, 1000].iter() {
+            let mut partially = data.clone();
+            partial(&mut partially, k);
+            let k = k.min(300);
+            assert_eq!(partially[..k], sorted[..k]);
+
+            let mut rest = partially[k..].to_vec();
+            rest.sort();
+            assert_eq!(rest, sorted[k..]);
+        }
+    }
+
+    #[test]
+    fn top_k_test() {
+        let data: Vec<_> = (0..300).map(|x| (x * 37) % 101).collect();
+        let mut sorted = data.clone();
+        sorted.sort_by(|a, b| b.cmp(a));
+
+        for &k in [0, 1, 2, 20, 299, 300, 1000].iter() {
+            let top = top_k(&data, k);
+            let k = k.min(300);
+            assert_eq!(top, sorted[..k].iter().collect::<Vec<_>>());
+            assert_eq!(top_k_cloned(&data, k), sorted[..k]);
+        }
+        assert_eq!(data, (0..300).map(|x| (x * 37) % 101).collect::<Vec<_>>());
+        assert!(top_k::<i32>(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn top_k_iter_test() {
+        let data: Vec<_> = (0..300).map(|x| (x * 37) % 101).collect();
+        let mut sorted = data.clone();
+        sorted.sort_by(|a, b| b.cmp(a));
+
+        for &k in [0, 1, 2, 20, 299, 300, 1000].iter() {
+            let top = top_k_iter(data.iter().copied(), k);
+            assert_eq!(top, sorted[..k.min(300)]);
+        }
+        assert_eq!(top_k_iter(vec!["b", "c", "a"], 2), ["c", "b"]);
+    }
+
+    #[test]
+    fn median_test() {
+        for len in 1..50 {
+            let mut data: Vec<_> = (0..len).map(|x| (x * 37) % 53).collect();
+            let mut sorted = data.clone();
+            sorted.sort();
+            assert_eq!(median(&mut data), Some(&sorted[(len - 1) / 2]));
+        }
+        assert_eq!(median(&mut [2, 1]), Some(&1));
+        assert_eq!(median::<i32>(&mut []), None);
+    }
+
+    #[test]
+    fn quantiles_test() {
+        let mut data: Vec<_> = (0..101).map(|x| (x * 37) % 101).collect();
+        let q = quantiles(&mut data, &[1.0, 0.0, 0.25, 0.5, 0.5, 0.999]).unwrap();
+        assert_eq!(q, [&100, &0, &25, &50, &50, &99]);
+
+        let mut even = [4, 3, 2, 1];
+        assert_eq!(quantiles(&mut even, &[0.5, 0.7]).unwrap(), [&2, &3]);
+        assert_eq!(quantiles(&mut even, &[]).unwrap(), Vec::<&i32>::new());
+        assert_eq!(quantiles::<i32>(&mut [], &[0.5]), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn quantiles_range_test() {
+        quantiles(&mut [1, 2, 3], &[1.5]);
+    }
+
+    #[test]
+    fn insort_test() {
+        let mut vec = Vec::new();
+        for x in (0..200).map(|x| (x * 37) % 101) {
+            let pos = insort(&mut vec, x);
+            assert_eq!(vec[pos], x);
+            assert!(vec.windows(2).all(|w| w[0] <= w[1]));
+        }
+        assert_eq!(vec.len(), 200);
+
+        let mut tagged = Vec::new();
+        for (i, x) in (0..100).map(|x| (x * 7) % 5).enumerate() {
+            insort_by_key(&mut tagged, Tagged(x, i), |t| t.0);
+        }
+        assert_stable(&tagged);
+    }
+
+    #[test]
+    fn dedup_test() {
+        let mut data: Vec<_> = (0..300).map(|x| (x * 37) % 101).collect();
+        intro(&mut data);
+        let len = dedup(&mut data);
+        assert_eq!(data[..len], (0..101).collect::<Vec<_>>()[..]);
+        assert_eq!(data.len() - len, 199);
+
+        let mut tagged: Vec<_> = (0..100).map(|x| Tagged(x / 7, x as usize)).collect();
+        let len = dedup_by_key(&mut tagged, |t| t.0);
+        assert_eq!(len, 15);
+        assert!(tagged[..len].iter().all(|t| t.1 == 7 * t.0 as usize));
+        assert_eq!(dedup::<i32>(&mut []), 0);
+    }
+
+    #[test]
+    fn unique_test() {
+        let data: Vec<_> = (0..300u32).map(|x| (x * 37) % 101 + 1000).collect();
+        let expected: Vec<_> = (1000..1101).collect();
+
+        let mut vec = data.clone();
+        unique(&mut vec);
+        assert_eq!(vec, expected);
+
+        let mut vec = data.clone();
+        unique_radix(&mut vec);
+        assert_eq!(vec, expected);
+
+        let mut vec = data;
+        unique_counting(&mut vec);
+        assert_eq!(vec, expected);
+
+        let mut empty: Vec<u8> = Vec::new();
+        unique_counting(&mut empty);
+        unique(&mut empty);
+        assert!(empty.is_empty());
+        // the span of the values is too large for a histogram
+        let mut vec = vec![1 << 40, 0u64, 1 << 40, 0];
+        unique_counting(&mut vec);
+        assert_eq!(vec, [0, 1 << 40]);
+    }
+
+    #[test]
+    fn counts_test() {
+        let mut data: Vec<_> = (0..1000).map(|x| (x * x) % 13).collect();
+        intro(&mut data);
+        let groups = counts(&data);
+        assert_eq!(groups.iter().map(|&(_, c)| c).sum::<usize>(), 1000);
+        for (v, c) in groups {
+            assert_eq!(data.iter().filter(|&x| x == v).count(), c);
+        }
+        assert!(counts::<i32>(&[]).is_empty());
+    }
+
+    #[test]
+    fn top_k_frequent_test() {
+        // the value x occurs x times
+        let data: Vec<_> = (0..20).flat_map(|x| vec![x; x]).collect();
+        let mut shuffled = data.clone();
+        shuffled.reverse();
+        let top = top_k_frequent(&shuffled, 3);
+        assert_eq!(top, [(&19, 19), (&18, 18), (&17, 17)]);
+        assert_eq!(top_k_frequent(&data, 100).len(), 19);
+
+        assert_eq!(top_k_frequent(&[2, 1, 2, 1, 3], 2), [(&1, 2), (&2, 2)]);
+        assert!(top_k_frequent(&[1], 0).is_empty());
+    }
+
+    #[test]
+    fn set_operations_test() {
+        use std::collections::BTreeSet;
+
+        let a: BTreeSet<_> = (0..100)
+            .map(|x| (x * 37) % 101)
+            .filter(|x| x % 2 == 0)
+            .collect();
+        let b: BTreeSet<_> = (0..100)
+            .map(|x| (x * 37) % 101)
+            .filter(|x| x % 3 == 0)
+            .collect();
+        let (a_vec, b_vec): (Vec<_>, Vec<_>) =
+            (a.iter().copied().collect(), b.iter().copied().collect());
+
+        let expected: Vec<_> = a.union(&b).copied().collect();
+        assert_eq!(union(&a_vec, &b_vec), expected);
+        let expected: Vec<_> = a.intersection(&b).copied().collect();
+        assert_eq!(intersection(&a_vec, &b_vec), expected);
+        let expected: Vec<_> = a.difference(&b).copied().collect();
+        assert_eq!(difference(&a_vec, &b_vec), expected);
+        let expected: Vec<_> = a.symmetric_difference(&b).copied().collect();
+        assert_eq!(symmetric_difference(&a_vec, &b_vec), expected);
+
+        // multisets
+        let (a, b) = ([1, 1, 1, 2, 3, 3], [1, 3, 3, 3, 4]);
+        assert_eq!(union(&a, &b), [1, 1, 1, 2, 3, 3, 3, 4]);
+        assert_eq!(intersection(&a, &b), [1, 3, 3]);
+        assert_eq!(difference(&a, &b), [1, 1, 2]);
+        assert_eq!(symmetric_difference(&a, &b), [1, 1, 2, 3, 4]);
+        assert!(union::<i32>(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn set_iter_test() {
+        let (a, b) = ([1, 1, 1, 2, 3, 3], [1, 3, 3, 3, 4]);
+        let union: Vec<_> = union_iter(a, b).collect();
+        assert_eq!(union, [1, 1, 1, 2, 3, 3, 3, 4]);
+        let intersection: Vec<_> = intersect_iter(a, b).collect();
+        assert_eq!(intersection, [1, 3, 3]);
+        let difference: Vec<_> = difference_iter(a, b).collect();
+        assert_eq!(difference, [1, 1, 2]);
+        let symmetric: Vec<_> = symmetric_difference_iter(a, b).collect();
+        assert_eq!(symmetric, [1, 1, 2, 3, 4]);
+
+        // the items of a are yielded
+        let a = [Tagged(1, 0), Tagged(2, 0)];
+        let b = [Tagged(1, 1), Tagged(3, 1)];
+        let tags: Vec<_> = union_iter(&a, &b).map(|t| t.1).collect();
+        assert_eq!(tags, [0, 0, 1]);
+
+        // the sources are consumed lazily
+        let merged = kmerge(vec![(0..).step_by(3), (1..).step_by(3)]);
+        let first: Vec<_> = difference_iter(merged, (0..).step_by(2)).take(4).collect();
+        assert_eq!(first, [1, 3, 7, 9]);
+        assert_eq!(union_iter(0..5, 3..10).size_hint(), (7, Some(12)));
+    }
+
+    #[test]
+    fn intersect_indices_test() {
+        let a: Vec<_> = (0..100).map(|x| x * 2).collect();
+        let b: Vec<_> = (0..100).map(|x| x * 3).collect();
+        let pairs = intersect_indices(&a, &b);
+        assert_eq!(pairs.len(), 34);
+        for &(i, j) in &pairs {
+            assert_eq!(a[i], b[j]);
+        }
+        let values: Vec<_> = pairs.iter().map(|&(i, _)| a[i]).collect();
+        assert_eq!(values, intersection(&a, &b));
+
+        assert_eq!(intersect_indices(&[1, 1, 1], &[0, 1, 1]), [(0, 1), (1, 2)]);
+        assert!(intersect_indices::<i32>(&[], &[1]).is_empty());
+    }
+
+    #[test]
+    fn is_sorted_test() {
+        let mut data: Vec<_> = (0..100).map(|x| (x * 37) % 101 - 50).collect();
+        assert!(!is_sorted_by(&data, i32::cmp));
+        assert!(!is_sorted_desc(&data));
+
+        intro_by_key(&mut data, |x| x.abs());
+        assert!(is_sorted_by_key(&data, |x| x.abs()));
+        assert!(!is_sorted_by_key(&data, |x| -x.abs()));
+
+        intro_desc(&mut data);
+        assert!(is_sorted_desc(&data));
+        assert!(is_sorted_by(&data, |a, b| b.cmp(a)));
+        data.reverse();
+        assert!(is_strictly_sorted(&data));
+
+        assert!(!is_strictly_sorted(&[1, 1]));
+        assert!(is_strictly_sorted::<i32>(&[]));
+        assert!(is_sorted_desc(&[1]));
+    }
+
+    #[test]
+    fn is_sorted_until_test() {
+        let mut data: Vec<_> = (0..100).collect();
+        assert_eq!(is_sorted_until(&data), 100);
+        data.swap(40, 41);
+        assert_eq!(is_sorted_until(&data), 41);
+        assert_eq!(is_sorted_until_by_key(&data, |x| x / 2), 100);
+        assert_eq!(is_sorted_until::<i32>(&[]), 0);
+        assert_eq!(is_sorted_until(&[2, 1]), 1);
+    }
+
+    #[test]
+    fn is_sorted_within_test() {
+        let noisy: Vec<_> = (0..100)
+            .map(|x| x as f64 + if x % 2 == 0 { 0.6 } else { -0.6 })
+            .collect();
+        assert!(is_sorted_within(&noisy, 0.5));
+        assert!(!is_sorted_within(&noisy, 0.1));
+
+        let sorted: Vec<_> = (0..100).map(|x| x as f32).collect();
+        assert!(is_sorted_within(&sorted, 0.0));
+        assert!(!is_sorted_within(&[1.0, f32::NAN], 1.0));
+        assert!(!is_sorted_within(&[f64::NAN], 1.0));
+        assert!(is_sorted_within::<f64>(&[], 0.0));
+    }
+
+    #[test]
+    fn count_inversions_test() {
+        for len in 0..60 {
+            let data: Vec<_> = (0..len).map(|x| (x * 37) % 11).collect();
+            let mut expected = 0;
+            for i in 0..data.len() {
+                for j in (i + 1)..data.len() {
+                    if data[i] > data[j] {
+                        expected += 1;
+                    }
+                }
+            }
+            assert_eq!(count_inversions(&data), expected);
+        }
+
+        let reversed: Vec<_> = (0..1000).rev().collect();
+        assert_eq!(count_inversions(&reversed), 1000 * 999 / 2);
+        assert_eq!(count_inversions_by(&reversed, |a, b| b.cmp(a)), 0);
     }
 }
@@ -0,0 +1,431 @@
+//! Collections and slices, whose elements are known to be sorted.
+//!
+//! They're built on the sorting and searching functions of this crate, so
+//! the invariant is checked by the types instead of the callers.
+
+use std::iter::FromIterator;
+use std::mem;
+use std::ops::{Bound, Deref, RangeBounds};
+use std::vec;
+
+use crate::{search, sort};
+
+/// A vector, whose elements are always sorted in ascending order.
+///
+/// The elements are found by binary search, and inserting or removing one
+/// moves the following elements, like in a [`Vec`]. It dereferences to a
+/// sorted slice, so all the slice methods that don't change it can be used.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::sorted::SortedVec;
+///
+/// let mut vec = SortedVec::from(vec![7, 1, 4]);
+/// vec.insert(3);
+/// assert_eq!(vec.as_slice(), [1, 3, 4, 7]);
+/// assert!(vec.contains(&4));
+/// assert_eq!(vec.remove(&1), Some(1));
+/// assert_eq!(vec.range(2..5), [3, 4]);
+/// assert_eq!(vec.into_vec(), [3, 4, 7]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct SortedVec<T> {
+    vec: Vec<T>,
+}
+
+impl<T: Ord> SortedVec<T> {
+    /// Creates an empty vector.
+    pub fn new() -> Self {
+        SortedVec { vec: Vec::new() }
+    }
+
+    /// Creates an empty vector with space for at least `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        SortedVec {
+            vec: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Wraps a vector that is already sorted, or returns it back if it's not.
+    ///
+    /// It only checks the order, so it takes linear time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use search_sort::sorted::SortedVec;
+    ///
+    /// assert!(SortedVec::from_sorted(vec![1, 2, 2]).is_ok());
+    /// assert_eq!(SortedVec::from_sorted(vec![2, 1]), Err(vec![2, 1]));
+    /// ```
+    pub fn from_sorted(vec: Vec<T>) -> Result<Self, Vec<T>> {
+        if sort::test(&vec) {
+            Ok(SortedVec { vec })
+        } else {
+            Err(vec)
+        }
+    }
+
+    /// Inserts a value after all the elements equal to it with
+    /// [`insort`](sort::insort), and returns its position.
+    pub fn insert(&mut self, value: T) -> usize {
+        sort::insort(&mut self.vec, value)
+    }
+
+    /// Removes the first element equal to the value, and returns it, or
+    /// `None` if there's no such element.
+    pub fn remove(&mut self, value: &T) -> Option<T> {
+        search::binary_first(&self.vec, value).map(|i| self.vec.remove(i))
+    }
+
+    /// Removes the element at `index`, and returns it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove_index(&mut self, index: usize) -> T {
+        self.vec.remove(index)
+    }
+
+    /// Returns `true` if the vector contains an element equal to the value.
+    ///
+    /// Unlike the slice method, it uses [`binary`](search::binary) search.
+    pub fn contains(&self, value: &T) -> bool {
+        search::binary(&self.vec, value).is_some()
+    }
+
+    /// Returns the elements, which are in the range of values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use search_sort::sorted::SortedVec;
+    ///
+    /// let vec: SortedVec<_> = (0..10).map(|x| x * 10).collect();
+    /// assert_eq!(vec.range(15..=40), [20, 30, 40]);
+    /// assert_eq!(vec.range(..20), [0, 10]);
+    /// ```
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> &[T] {
+        let start = match range.start_bound() {
+            Bound::Included(start) => self.vec.partition_point(|v| v < start),
+            Bound::Excluded(start) => self.vec.partition_point(|v| v <= start),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(end) => self.vec.partition_point(|v| v <= end),
+            Bound::Excluded(end) => self.vec.partition_point(|v| v < end),
+            Bound::Unbounded => self.vec.len(),
+        };
+
+        &self.vec[start..end.max(start)]
+    }
+}
+
+impl<T> SortedVec<T> {
+    /// Returns the sorted elements as a slice.
+    pub fn as_slice(&self) -> &[T] {
+        &self.vec
+    }
+
+    /// Returns the underlying vector, without copying it.
+    pub fn into_vec(self) -> Vec<T> {
+        self.vec
+    }
+
+    /// Removes all the elements.
+    pub fn clear(&mut self) {
+        self.vec.clear();
+    }
+}
+
+impl<T> Default for SortedVec<T> {
+    fn default() -> Self {
+        SortedVec { vec: Vec::new() }
+    }
+}
+
+impl<T> Deref for SortedVec<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.vec
+    }
+}
+
+impl<T> AsRef<[T]> for SortedVec<T> {
+    fn as_ref(&self) -> &[T] {
+        &self.vec
+    }
+}
+
+/// Sorts the vector with [`cartesian_tree`](sort::cartesian_tree) sort, which
+/// takes linear time if it's already sorted.
+impl<T: Ord> From<Vec<T>> for SortedVec<T> {
+    fn from(mut vec: Vec<T>) -> Self {
+        sort::cartesian_tree(&mut vec);
+        SortedVec { vec }
+    }
+}
+
+impl<T> From<SortedVec<T>> for Vec<T> {
+    fn from(sorted: SortedVec<T>) -> Self {
+        sorted.vec
+    }
+}
+
+impl<T: Ord> FromIterator<T> for SortedVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        SortedVec::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+/// Sorts the values, and merges them with the elements by
+/// [`kmerge`](sort::kmerge). The new values are placed after the equal
+/// elements.
+impl<T: Ord> Extend<T> for SortedVec<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let new = SortedVec::from_iter(iter).vec;
+        let old = mem::take(&mut self.vec);
+        self.vec = sort::kmerge(vec![old, new]).collect();
+    }
+}
+
+impl<T> IntoIterator for SortedVec<T> {
+    type Item = T;
+    type IntoIter = vec::IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.vec.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a SortedVec<T> {
+    type Item = &'a T;
+    type IntoIter = std::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.vec.iter()
+    }
+}
+
+/// A slice, which is known to be sorted in ascending order.
+///
+/// It can be created only by checking the order with [`Sorted::new`], by
+/// sorting the slice with [`Sorted::sort`] or [`Sorted::sort_with`], or by
+/// promising the order with [`Sorted::new_unchecked`]. Then the searches,
+/// which need a sorted slice, are available as its methods. It's just a
+/// reference to the slice, so it's as cheap to copy and pass around.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::sort;
+/// use search_sort::sorted::Sorted;
+///
+/// let mut slice = [5, 1, 91, -45, 11, 5];
+/// let sorted = Sorted::sort_with(&mut slice, sort::merge);
+/// assert_eq!(sorted.binary_first(&5), Some(2));
+/// assert_eq!(sorted.jump(&42), None);
+///
+/// assert!(Sorted::new(&[2, 1]).is_none());
+/// ```
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct Sorted<'a, T> {
+    slice: &'a [T],
+}
+
+impl<'a, T: Ord> Sorted<'a, T> {
+    /// Checks that the slice is sorted with [`sort::test`], and wraps it, or
+    /// returns `None` if it's not sorted.
+    pub fn new(slice: &'a [T]) -> Option<Self> {
+        if sort::test(slice) {
+            Some(Sorted { slice })
+        } else {
+            None
+        }
+    }
+
+    /// Sorts the slice with [`intro`](sort::intro) sort, and wraps it.
+    pub fn sort(slice: &'a mut [T]) -> Self {
+        sort::intro(slice);
+        Sorted { slice }
+    }
+
+    /// Sorts the slice with any sort of this crate, and wraps it.
+    ///
+    /// The order is checked afterwards with [`sort::test`], since `sort_fn`
+    /// can be any function.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sort_fn` doesn't sort the slice.
+    pub fn sort_with<F>(slice: &'a mut [T], sort_fn: F) -> Self
+    where
+        F: FnOnce(&mut [T]),
+    {
+        sort_fn(slice);
+        assert!(sort::test(slice), "the slice isn't sorted");
+        Sorted { slice }
+    }
+
+    /// Searches for the value with [`binary`](search::binary) search.
+    pub fn binary(self, value: &T) -> Option<usize> {
+        search::binary(self.slice, value)
+    }
+
+    /// Searches for the first element equal to the value with
+    /// [`binary_first`](search::binary_first) search.
+    pub fn binary_first(self, value: &T) -> Option<usize> {
+        search::binary_first(self.slice, value)
+    }
+
+    /// Searches for the value with [`jump`](search::jump) search.
+    pub fn jump(self, value: &T) -> Option<usize> {
+        search::jump(self.slice, value)
+    }
+
+    /// Searches for the first element equal to the value with
+    /// [`exp`](search::exp)onential search.
+    pub fn exp(self, value: &T) -> Option<usize> {
+        search::exp(self.slice, value)
+    }
+}
+
+impl<'a, T> Sorted<'a, T> {
+    /// Wraps a slice without checking its order.
+    ///
+    /// If the slice isn't sorted, the searches return unspecified results,
+    /// but it's still memory safe.
+    pub fn new_unchecked(slice: &'a [T]) -> Self {
+        Sorted { slice }
+    }
+
+    /// Returns the sorted slice.
+    pub fn as_slice(self) -> &'a [T] {
+        self.slice
+    }
+}
+
+// not derived, since it would require `T: Clone`
+impl<T> Clone for Sorted<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Sorted<'_, T> {}
+
+impl<T> Deref for Sorted<'_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.slice
+    }
+}
+
+impl<T> SortedVec<T> {
+    /// Returns the elements as a [`Sorted`] slice.
+    pub fn as_sorted(&self) -> Sorted<'_, T> {
+        Sorted::new_unchecked(&self.vec)
+    }
+}
+
+/// Available only with the `serde` feature.
+#[cfg(feature = "serde")]
+impl<T: serde::Serialize> serde::Serialize for SortedVec<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.vec.serialize(serializer)
+    }
+}
+
+/// Deserializes a sequence, and sorts it like [`From<Vec<T>>`], so the order
+/// is kept even if the data was changed. Sorted data takes linear time.
+///
+/// Available only with the `serde` feature.
+#[cfg(feature = "serde")]
+impl<'de, T> serde::Deserialize<'de> for SortedVec<T>
+where
+    T: Ord + serde::Deserialize<'de>,
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Vec::deserialize(deserializer).map(SortedVec::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sorted;
+    use super::SortedVec;
+
+    #[test]
+    fn sorted_vec_test() {
+        let mut vec = SortedVec::new();
+        assert!(!vec.contains(&0));
+        assert_eq!(vec.remove(&0), None);
+        // every value twice
+        for x in (0..202).map(|x| (x * 37) % 101) {
+            vec.insert(x);
+        }
+        assert!(vec.windows(2).all(|w| w[0] <= w[1]));
+        assert_eq!(vec.len(), 202);
+
+        assert_eq!(vec.remove(&100), Some(100));
+        assert!(vec.contains(&100));
+        assert_eq!(vec.remove(&100), Some(100));
+        assert!(!vec.contains(&100));
+        assert_eq!(vec.remove(&100), None);
+        assert_eq!(vec.remove_index(0), 0);
+
+        assert_eq!(vec.range(5..7), [5, 5, 6, 6]);
+        assert_eq!(vec.range(99..), [99, 99]);
+        assert!(vec.range(..0).is_empty());
+
+        vec.extend(vec![1000, -1]);
+        assert_eq!(vec.first(), Some(&-1));
+        assert_eq!(vec.last(), Some(&1000));
+
+        let collected: SortedVec<_> = vec.iter().rev().copied().collect();
+        assert_eq!(collected, vec);
+        assert_eq!(SortedVec::from_sorted(Vec::from(vec.clone())), Ok(vec));
+    }
+
+    #[test]
+    fn sorted_test() {
+        let mut data: Vec<_> = (0..100).map(|x| (x * 37) % 101).collect();
+        let sorted = Sorted::sort(&mut data);
+        assert!(sorted.windows(2).all(|w| w[0] < w[1]));
+        for x in 0..101 {
+            let expected = sorted.iter().position(|&v| v == x);
+            assert_eq!(sorted.binary(&x), expected);
+            assert_eq!(sorted.binary_first(&x), expected);
+            assert_eq!(sorted.jump(&x), expected);
+            assert_eq!(sorted.exp(&x), expected);
+        }
+
+        assert_eq!(Sorted::new(&data), Some(Sorted::new_unchecked(&data[..])));
+        assert!(Sorted::new(&[3, 1, 2]).is_none());
+        assert_eq!(Sorted::<i32>::new(&[]).unwrap().binary(&1), None);
+
+        let vec = SortedVec::from(vec![3, 1, 2]);
+        assert_eq!(vec.as_sorted().exp(&3), Some(2));
+    }
+
+    #[test]
+    #[should_panic]
+    fn sort_with_unsorted_test() {
+        Sorted::sort_with(&mut [2, 1], |_| {});
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_test() {
+        use serde::de::value::{Error, SeqDeserializer};
+        use serde::de::IntoDeserializer;
+        use serde::Deserialize;
+
+        let deserializer: SeqDeserializer<_, Error> = vec![3, 1, 2, 1].into_deserializer();
+        let vec = SortedVec::<i32>::deserialize(deserializer).unwrap();
+        assert_eq!(vec.as_slice(), [1, 1, 2, 3]);
+    }
+}
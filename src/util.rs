@@ -0,0 +1,131 @@
+//! Utilities for rearranging slices, used by the sorting algorithms.
+
+use std::ops::Range;
+
+/// Rotates a slice in place so that the element on position `k` becomes the
+/// first one.
+///
+/// Uses the triple-reversal algorithm: reverses the first `k` elements, the
+/// rest of the slice, and then the whole slice. It does exactly `n` swaps at
+/// most and doesn't allocate.
+///
+/// # Panics
+///
+/// Panics if `k` is greater than the length of the slice.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::util;
+///
+/// let mut slice = [1, 2, 3, 4, 5];
+/// util::rotate_left(&mut slice, 2);
+/// assert_eq!(slice, [3, 4, 5, 1, 2]);
+/// ```
+pub fn rotate_left<T>(slice: &mut [T], k: usize) {
+    assert!(k <= slice.len(), "rotation is larger than the slice");
+
+    slice[..k].reverse();
+    slice[k..].reverse();
+    slice.reverse();
+}
+
+/// Rotates a slice in place so that the last `k` elements become the first
+/// ones.
+///
+/// Equivalent to [`rotate_left`] by `len - k`.
+///
+/// # Panics
+///
+/// Panics if `k` is greater than the length of the slice.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::util;
+///
+/// let mut slice = [1, 2, 3, 4, 5];
+/// util::rotate_right(&mut slice, 2);
+/// assert_eq!(slice, [4, 5, 1, 2, 3]);
+/// ```
+pub fn rotate_right<T>(slice: &mut [T], k: usize) {
+    assert!(k <= slice.len(), "rotation is larger than the slice");
+
+    let len = slice.len();
+    rotate_left(slice, len - k);
+}
+
+/// Swaps two non-overlapping blocks of a slice, which may differ in length.
+///
+/// The elements between the blocks are shifted, so that they stay between
+/// them. It's done by reversing the whole span of the blocks, and then every
+/// block and the middle separately.
+///
+/// # Panics
+///
+/// Panics if the blocks overlap or are out of bounds of the slice.
+///
+/// # Examples
+///
+/// ```
+/// use search_sort::util;
+///
+/// let mut slice = [1, 2, 0, 7, 8, 9];
+/// util::block_swap(&mut slice, 0..2, 3..6);
+/// assert_eq!(slice, [7, 8, 9, 0, 1, 2]);
+/// ```
+pub fn block_swap<T>(slice: &mut [T], a: Range<usize>, b: Range<usize>) {
+    let (a, b) = if a.start <= b.start { (a, b) } else { (b, a) };
+    assert!(
+        a.start <= a.end && b.start <= b.end,
+        "blocks must not be reversed"
+    );
+    assert!(a.end <= b.start, "blocks must not overlap");
+
+    let span = &mut slice[a.start..b.end];
+    let (a_len, b_len) = (a.len(), b.len());
+    let mid_len = span.len() - a_len - b_len;
+
+    // [a, mid, b] -> [b', mid', a'] -> [b, mid, a]
+    span.reverse();
+    span[..b_len].reverse();
+    span[b_len..(b_len + mid_len)].reverse();
+    span[(b_len + mid_len)..].reverse();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::block_swap;
+    use super::rotate_left;
+    use super::rotate_right;
+
+    #[test]
+    fn rotate_test() {
+        let mut data = [0, 1, 2, 3, 4, 5, 6];
+        rotate_left(&mut data, 3);
+        assert_eq!(data, [3, 4, 5, 6, 0, 1, 2]);
+        rotate_right(&mut data, 3);
+        assert_eq!(data, [0, 1, 2, 3, 4, 5, 6]);
+
+        rotate_left(&mut data, 0);
+        rotate_right(&mut data, 7);
+        assert_eq!(data, [0, 1, 2, 3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn block_swap_test() {
+        let mut data = [0, 1, 2, 3, 4, 5, 6];
+        block_swap(&mut data, 5..7, 1..2);
+        assert_eq!(data, [0, 5, 6, 2, 3, 4, 1]);
+
+        block_swap(&mut data, 0..0, 6..7);
+        assert_eq!(data, [1, 0, 5, 6, 2, 3, 4]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn block_swap_overlap_test() {
+        let mut data = [0, 1, 2, 3];
+        block_swap(&mut data, 0..3, 2..4);
+    }
+}